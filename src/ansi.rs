@@ -0,0 +1,176 @@
+//! Removal of ANSI escape sequences from pasted terminal output.
+//!
+//! Terminal output copied into documentation often carries raw color codes
+//! (`ESC[32m`) or hyperlink/title sequences (`ESC]8;;...BEL`). They are invisible
+//! in a terminal but count as columns in the grid and render as junk in Markdown.
+
+use crate::parser::{parse_fence_marker, FenceMarker};
+
+/// Escape character that introduces a sequence
+const ESC: char = '\u{1B}';
+/// Single-character (C1) control sequence introducer
+const CSI_C1: char = '\u{9B}';
+/// Bell, one of the two OSC terminators
+const BEL: char = '\u{07}';
+
+/// Fence languages treated as terminal output when stripping inside fences.
+const TERMINAL_FENCE_LANGUAGES: &[&str] = &[
+    "text",
+    "txt",
+    "console",
+    "terminal",
+    "output",
+    "ansi",
+    "shell-session",
+    "sh-session",
+];
+
+/// Remove ANSI CSI and OSC sequences from a single line.
+///
+/// Returns the cleaned line and the number of sequences removed.
+/// An unterminated sequence at the end of the line is removed as well.
+#[must_use]
+pub fn strip_ansi_line(line: &str) -> (String, usize) {
+    if !line.contains([ESC, CSI_C1]) {
+        return (line.to_string(), 0);
+    }
+
+    let mut result = String::with_capacity(line.len());
+    let mut removed = 0;
+    let mut chars = line.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        let is_csi = match ch {
+            CSI_C1 => true,
+            ESC if chars.peek() == Some(&'[') => {
+                chars.next();
+                true
+            }
+            ESC if chars.peek() == Some(&']') => {
+                chars.next();
+                // OSC: runs until BEL or ST (ESC \)
+                while let Some(c) = chars.next() {
+                    if c == BEL {
+                        break;
+                    }
+                    if c == ESC && chars.peek() == Some(&'\\') {
+                        chars.next();
+                        break;
+                    }
+                }
+                removed += 1;
+                continue;
+            }
+            _ => false,
+        };
+
+        if is_csi {
+            // CSI: parameter and intermediate bytes, then a single final byte
+            for c in chars.by_ref() {
+                if ('\u{40}'..='\u{7E}').contains(&c) {
+                    break;
+                }
+            }
+            removed += 1;
+        } else {
+            result.push(ch);
+        }
+    }
+
+    (result, removed)
+}
+
+/// Remove ANSI escape sequences from Markdown content.
+///
+/// Lines outside code fences are always cleaned. Lines inside fences are only
+/// cleaned when `include_text_fences` is true and the fence is tagged as
+/// terminal output (`text`, `console`, `output`, ...); other code keeps its
+/// escapes untouched. Returns the cleaned content and the number of sequences removed.
+#[must_use]
+pub fn strip_ansi(content: &str, include_text_fences: bool) -> (String, usize) {
+    if !content.contains([ESC, CSI_C1]) {
+        return (content.to_string(), 0);
+    }
+
+    let mut lines = Vec::new();
+    let mut removed = 0;
+    // Opening marker and whether the fence holds terminal output
+    let mut open_fence: Option<(FenceMarker, bool)> = None;
+
+    for line in content.lines() {
+        let fence = parse_fence_marker(line);
+
+        let strip = match (&open_fence, fence) {
+            (None, Some(marker)) => {
+                let language = marker.language().unwrap_or("").to_lowercase();
+                let terminal = TERMINAL_FENCE_LANGUAGES.contains(&language.as_str());
+                open_fence = Some((marker, terminal));
+                false
+            }
+            (Some((opening, _)), Some(marker)) if marker.closes(opening) => {
+                open_fence = None;
+                false
+            }
+            (Some((_, terminal)), _) => include_text_fences && *terminal,
+            (None, None) => true,
+        };
+
+        if strip {
+            let (cleaned, count) = strip_ansi_line(line);
+            removed += count;
+            lines.push(cleaned);
+        } else {
+            lines.push(line.to_string());
+        }
+    }
+
+    let mut result = lines.join("\n");
+    if content.ends_with('\n') {
+        result.push('\n');
+    }
+    (result, removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_sgr_color_codes() {
+        let (line, count) = strip_ansi_line("\u{1b}[32mok\u{1b}[0m done");
+        assert_eq!(line, "ok done");
+        assert_eq!(count, 2);
+    }
+
+    #[test]
+    fn test_strip_osc_hyperlink() {
+        let input = "\u{1b}]8;;https://example.com\u{7}link\u{1b}]8;;\u{1b}\\ text";
+        let (line, count) = strip_ansi_line(input);
+        assert_eq!(line, "link text");
+        assert_eq!(count, 2);
+    }
+
+    #[test]
+    fn test_plain_line_unchanged() {
+        assert_eq!(strip_ansi_line("┌──┐ plain"), ("┌──┐ plain".to_string(), 0));
+    }
+
+    #[test]
+    fn test_code_fences_preserved_by_default() {
+        let input = "\u{1b}[1mBold\u{1b}[0m\n```console\n\u{1b}[31merr\u{1b}[0m\n```";
+        let (output, count) = strip_ansi(input, false);
+        assert_eq!(output, "Bold\n```console\n\u{1b}[31merr\u{1b}[0m\n```");
+        assert_eq!(count, 2);
+    }
+
+    #[test]
+    fn test_terminal_fences_stripped_when_enabled() {
+        let input = "```console\n\u{1b}[31merr\u{1b}[0m\n```\n```rust\nlet s = \"\u{1b}[0m\";\n```";
+        let (output, count) = strip_ansi(input, true);
+        assert_eq!(
+            output,
+            "```console\nerr\n```\n```rust\nlet s = \"\u{1b}[0m\";\n```"
+        );
+        assert_eq!(count, 2);
+    }
+}
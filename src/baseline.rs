@@ -0,0 +1,266 @@
+//! Baselines: findings recorded once so that later checks only fail on new or
+//! changed ones.
+//!
+//! A finding is identified by its file, its rule and a hash of the lines of
+//! its block, never by line number, so editing text above a known problem
+//! does not make it new again.
+
+use crate::report::Diagnostic;
+use crate::rules::Rule;
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::Path;
+
+/// Version of the baseline file format.
+pub const BASELINE_VERSION: u32 = 1;
+
+/// A recorded finding.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct BaselineEntry {
+    /// File the finding is in, with `/` separators
+    pub file: String,
+    /// Rule that found it
+    pub rule: Rule,
+    /// Hash of the lines of its block
+    pub content_hash: String,
+    /// Description at the time it was recorded (not used for matching)
+    pub message: String,
+}
+
+/// Findings recorded with `--write-baseline`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Baseline {
+    /// Format version
+    pub version: u32,
+    /// Recorded findings, sorted by file, rule and hash
+    pub findings: Vec<BaselineEntry>,
+}
+
+/// Diagnostics of one file split against a baseline.
+#[derive(Debug, Default)]
+pub struct BaselineMatch<'a> {
+    /// Diagnostics not in the baseline
+    pub new: Vec<Diagnostic>,
+    /// Diagnostics in the baseline
+    pub known: Vec<Diagnostic>,
+    /// Entries for the file that no diagnostic matches any more
+    pub stale: Vec<&'a BaselineEntry>,
+}
+
+impl Baseline {
+    /// Record every diagnostic.
+    #[must_use]
+    pub fn from_diagnostics<'a>(diagnostics: impl IntoIterator<Item = &'a Diagnostic>) -> Self {
+        let mut findings: Vec<BaselineEntry> = diagnostics
+            .into_iter()
+            .map(|d| BaselineEntry {
+                file: normalize_path(&d.file),
+                rule: d.rule,
+                content_hash: d.content_hash.clone(),
+                message: d.message.clone(),
+            })
+            .collect();
+        findings.sort();
+        Self {
+            version: BASELINE_VERSION,
+            findings,
+        }
+    }
+
+    /// Read a baseline file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read, is not a baseline, or has
+    /// an unsupported version.
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read baseline {}", path.display()))?;
+        let baseline: Self = serde_json::from_str(&content)
+            .with_context(|| format!("Invalid baseline {}", path.display()))?;
+        if baseline.version != BASELINE_VERSION {
+            bail!(
+                "Unsupported baseline version {} in {} (expected {BASELINE_VERSION})",
+                baseline.version,
+                path.display()
+            );
+        }
+        Ok(baseline)
+    }
+
+    /// Write the baseline as JSON.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be written.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let mut json = serde_json::to_string_pretty(self)?;
+        json.push('\n');
+        std::fs::write(path, json)
+            .with_context(|| format!("Failed to write baseline {}", path.display()))
+    }
+
+    /// Split the diagnostics of `file` into new and known ones.
+    ///
+    /// Each entry matches at most one diagnostic, so a second copy of a
+    /// recorded problem is new.
+    #[must_use]
+    pub fn split(&self, file: &str, diagnostics: Vec<Diagnostic>) -> BaselineMatch<'_> {
+        let file = normalize_path(file);
+        let mut unmatched: Vec<&BaselineEntry> = self
+            .findings
+            .iter()
+            .filter(|entry| entry.file == file)
+            .collect();
+        let mut split = BaselineMatch::default();
+        for diagnostic in diagnostics {
+            let position = unmatched.iter().position(|e| {
+                e.rule == diagnostic.rule && e.content_hash == diagnostic.content_hash
+            });
+            if let Some(position) = position {
+                unmatched.remove(position);
+                split.known.push(diagnostic);
+            } else {
+                split.new.push(diagnostic);
+            }
+        }
+        split.stale = unmatched;
+        split
+    }
+
+    /// Entries for files below one of `roots` that are not in `visited`:
+    /// files that were deleted, renamed or are no longer discovered.
+    ///
+    /// Entries outside every root are left alone, since a run on part of a
+    /// repository says nothing about the rest.
+    #[must_use]
+    pub fn unvisited<'a>(
+        &'a self,
+        roots: &[String],
+        visited: &HashSet<String>,
+    ) -> Vec<&'a BaselineEntry> {
+        let roots: Vec<String> = roots.iter().map(|root| normalize_path(root)).collect();
+        self.findings
+            .iter()
+            .filter(|entry| !visited.contains(&entry.file))
+            .filter(|entry| roots.iter().any(|root| is_under(&entry.file, root)))
+            .collect()
+    }
+}
+
+/// Check whether the normalized path `file` is `root` or inside it.
+fn is_under(file: &str, root: &str) -> bool {
+    let root = root.trim_end_matches('/');
+    if root.is_empty() || root == "." {
+        return !file.starts_with('/');
+    }
+    file.strip_prefix(root)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+}
+
+/// Hash of a block's lines (64-bit FNV-1a, as 16 hex digits).
+///
+/// The hash is computed here rather than with the standard library's hasher,
+/// whose output may change between Rust releases.
+#[must_use]
+pub fn content_hash(lines: &[&str]) -> String {
+    const OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0100_0000_01b3;
+    let mut hash = OFFSET;
+    for line in lines {
+        for byte in line.bytes().chain(std::iter::once(b'\n')) {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(PRIME);
+        }
+    }
+    format!("{hash:016x}")
+}
+
+/// Path as recorded in a baseline: `/` separators, no leading `./`.
+#[must_use]
+pub fn normalize_path(file: &str) -> String {
+    let file = file.replace('\\', "/");
+    let mut file = file.as_str();
+    while let Some(rest) = file.strip_prefix("./") {
+        file = rest;
+    }
+    file.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::Severity;
+
+    fn diagnostic(file: &str, line: usize, block: &[&str]) -> Diagnostic {
+        Diagnostic {
+            file: file.to_string(),
+            line,
+            column: 1,
+            end_line: line + block.len() - 1,
+            rule: Rule::RaggedTableRow,
+            severity: Severity::Warning,
+            message: "table columns aligned".to_string(),
+            content_hash: content_hash(block),
+        }
+    }
+
+    #[test]
+    fn test_known_findings_match_wherever_the_block_moves() {
+        let table = ["| a | b |", "|---|---|", "| long | x |"];
+        let baseline = Baseline::from_diagnostics(&[diagnostic("./docs/a.md", 3, &table)]);
+        assert_eq!(baseline.findings[0].file, "docs/a.md");
+
+        let moved = diagnostic("docs/a.md", 40, &table);
+        let edited = diagnostic("docs/a.md", 50, &["| a | b |", "|---|---|", "| c | d e |"]);
+        let split = baseline.split("docs/a.md", vec![moved.clone(), edited.clone(), moved]);
+        assert_eq!(split.known.len(), 1);
+        assert_eq!(split.new.len(), 2);
+        assert_eq!(split.new[0], edited);
+        assert!(split.stale.is_empty());
+
+        let split = baseline.split("docs/a.md", Vec::new());
+        assert_eq!(split.stale, vec![&baseline.findings[0]]);
+        assert!(baseline.split("docs/b.md", Vec::new()).stale.is_empty());
+    }
+
+    #[test]
+    fn test_unvisited_entries_below_the_checked_paths() {
+        let baseline = Baseline::from_diagnostics(&[
+            diagnostic("docs/a.md", 1, &["x"]),
+            diagnostic("docs/gone.md", 1, &["x"]),
+            diagnostic("docsite/b.md", 1, &["x"]),
+            diagnostic("README.md", 1, &["x"]),
+        ]);
+        let visited = HashSet::from(["docs/a.md".to_string()]);
+        let files = |roots: &[&str]| -> Vec<&str> {
+            let roots: Vec<String> = roots.iter().map(ToString::to_string).collect();
+            baseline
+                .unvisited(&roots, &visited)
+                .iter()
+                .map(|entry| entry.file.as_str())
+                .collect()
+        };
+        assert_eq!(files(&["./docs/"]), vec!["docs/gone.md"]);
+        assert_eq!(
+            files(&["."]),
+            vec!["README.md", "docs/gone.md", "docsite/b.md"]
+        );
+        assert!(files(&["docs/a.md"]).is_empty());
+    }
+
+    #[test]
+    fn test_baseline_round_trips() -> Result<()> {
+        let temp_dir = tempfile::TempDir::new()?;
+        let path = temp_dir.path().join("baseline.json");
+        let baseline = Baseline::from_diagnostics(&[diagnostic("a.md", 1, &["x"])]);
+        baseline.save(&path)?;
+        assert_eq!(Baseline::load(&path)?, baseline);
+
+        std::fs::write(&path, r#"{"version": 2, "findings": []}"#)?;
+        let error = Baseline::load(&path).unwrap_err().to_string();
+        assert!(error.contains("Unsupported baseline version 2"));
+        Ok(())
+    }
+}
@@ -0,0 +1,231 @@
+//! Block-level change records: which fixer rewrote which lines.
+//!
+//! Each pass reports the blocks it rewrote in lines of its own input. A
+//! [`LineMap`] follows the line shifts of every pass so the records can be
+//! given in lines of the original file.
+
+use crate::fixes::{Fix, FixSelection};
+use crate::rules::Rule;
+use serde::{Deserialize, Serialize};
+use similar::{DiffTag, TextDiff};
+
+/// Fixer that rewrote a block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Fixer {
+    /// Code fence repair
+    Fence,
+    /// Markdown table normalization
+    Table,
+    /// List normalization
+    List,
+    /// Diagram repair (boxes and arrows)
+    Diagram,
+}
+
+impl Fixer {
+    /// Fixers of the `--fix` selection that make up this one.
+    #[must_use]
+    pub const fn fixes(self) -> FixSelection {
+        match self {
+            Self::Fence => FixSelection::NONE.with(Fix::Fences, true),
+            Self::Table => FixSelection::NONE.with(Fix::Tables, true),
+            Self::List => FixSelection::NONE.with(Fix::Lists, true),
+            Self::Diagram => FixSelection::DIAGRAMS,
+        }
+    }
+}
+
+/// A block rewritten by a fixer, in lines of the original content.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChangeRecord {
+    /// Fixer that made the change
+    pub fixer: Fixer,
+    /// Rule for the issue the change fixes
+    pub rule: Rule,
+    /// First line of the block in the original content (1-indexed)
+    pub start_line: usize,
+    /// Last line of the block in the original content (1-indexed, inclusive)
+    pub end_line: usize,
+    /// Short description of the change
+    pub reason: String,
+    /// Text that replaces lines `start_line..=end_line`
+    pub replacement: String,
+}
+
+/// A block rewritten by one pass, in lines of that pass's input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockChange {
+    /// Fixer that made the change
+    pub fixer: Fixer,
+    /// Rule for the issue the change fixes
+    pub rule: Rule,
+    /// First replaced line (0-indexed)
+    pub start: usize,
+    /// Last replaced line (0-indexed, inclusive)
+    pub end: usize,
+    /// Short description of the change
+    pub reason: String,
+    /// Lines that replace `start..=end`
+    pub lines: Vec<String>,
+}
+
+/// Original line of every line of the content as rewritten so far.
+#[derive(Debug, Clone)]
+pub struct LineMap {
+    origin: Vec<usize>,
+}
+
+impl LineMap {
+    /// Map for unchanged content with `line_count` lines.
+    #[must_use]
+    pub fn new(line_count: usize) -> Self {
+        Self {
+            origin: (0..line_count).collect(),
+        }
+    }
+
+    /// Original line of a line of the current content.
+    #[must_use]
+    pub fn original(&self, line: usize) -> usize {
+        self.origin
+            .get(line)
+            .or_else(|| self.origin.last())
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Turn the changes of one pass into records in original lines, then
+    /// follow the line shifts the pass made. Changes must not overlap.
+    pub fn apply(&mut self, mut changes: Vec<BlockChange>) -> Vec<ChangeRecord> {
+        changes.sort_by_key(|change| change.start);
+        let mut origin = Vec::with_capacity(self.origin.len());
+        let mut next = 0;
+        let mut records = Vec::with_capacity(changes.len());
+        for change in changes {
+            origin.extend(self.origin.get(next..change.start).unwrap_or_default());
+            // Replacement lines map onto the replaced ones; extra lines onto the last
+            let span = change.end - change.start;
+            origin
+                .extend((0..change.lines.len()).map(|i| self.original(change.start + i.min(span))));
+            next = change.end + 1;
+            records.push(ChangeRecord {
+                fixer: change.fixer,
+                rule: change.rule,
+                start_line: self.original(change.start) + 1,
+                end_line: self.original(change.end) + 1,
+                reason: change.reason,
+                replacement: change.lines.join("\n"),
+            });
+        }
+        origin.extend(self.origin.get(next..).unwrap_or_default());
+        self.origin = origin;
+        records
+    }
+}
+
+/// Changes between two versions of a text, one per run of changed lines.
+///
+/// Pure insertions are widened to the line before them (or after them at the
+/// start), so every change replaces at least one line.
+#[must_use]
+pub fn diff_changes(
+    fixer: Fixer,
+    rule: Rule,
+    reason: &str,
+    before: &str,
+    after: &str,
+) -> Vec<BlockChange> {
+    let old: Vec<&str> = before.lines().collect();
+    let new: Vec<&str> = after.lines().collect();
+    let diff = TextDiff::from_lines(before, after);
+    let mut changes = Vec::new();
+    for group in diff.grouped_ops(0) {
+        if group.iter().all(|op| op.tag() == DiffTag::Equal) {
+            continue;
+        }
+        let (Some(first), Some(last)) = (group.first(), group.last()) else {
+            continue;
+        };
+        let (mut start, mut end) = (first.old_range().start, last.old_range().end);
+        let (mut new_start, mut new_end) = (first.new_range().start, last.new_range().end);
+        if start == end {
+            if start > 0 {
+                start -= 1;
+                new_start -= 1;
+            } else {
+                end += 1;
+                new_end += 1;
+            }
+        }
+        let end = end.min(old.len());
+        let lines = new
+            .get(new_start..new_end.min(new.len()))
+            .unwrap_or_default();
+        // A changed line ending alone is not a change of the block
+        if start >= end || old.get(start..end) == Some(lines) {
+            continue;
+        }
+        changes.push(BlockChange {
+            fixer,
+            rule,
+            start,
+            end: end - 1,
+            reason: reason.to_string(),
+            lines: lines.iter().map(ToString::to_string).collect(),
+        });
+    }
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_changes_widen_insertions() {
+        let changes = diff_changes(
+            Fixer::Fence,
+            Rule::UnclosedFence,
+            "fence",
+            "```\ncode",
+            "```\ncode\n```",
+        );
+        assert_eq!(changes.len(), 1);
+        assert_eq!((changes[0].start, changes[0].end), (1, 1));
+        assert_eq!(changes[0].lines, ["code", "```"]);
+
+        // Only the missing final newline differs
+        assert!(
+            diff_changes(Fixer::Fence, Rule::UnclosedFence, "fence", "a\nb", "a\nb\n").is_empty()
+        );
+    }
+
+    #[test]
+    fn test_line_map_follows_shifts() {
+        let mut map = LineMap::new(6);
+        // Line 1 is split in two
+        let records = map.apply(vec![BlockChange {
+            fixer: Fixer::Fence,
+            rule: Rule::UnclosedFence,
+            start: 1,
+            end: 1,
+            reason: "split".to_string(),
+            lines: vec!["b".to_string(), "b2".to_string()],
+        }]);
+        assert_eq!((records[0].start_line, records[0].end_line), (2, 2));
+
+        // Lines 3..=5 of the new content were lines 2..=4 of the original
+        let records = map.apply(vec![BlockChange {
+            fixer: Fixer::Table,
+            rule: Rule::RaggedTableRow,
+            start: 3,
+            end: 5,
+            reason: "table".to_string(),
+            lines: vec!["| a |".to_string()],
+        }]);
+        assert_eq!((records[0].start_line, records[0].end_line), (3, 5));
+        assert_eq!(records[0].replacement, "| a |");
+        assert_eq!(map.original(4), 5);
+    }
+}
@@ -0,0 +1,210 @@
+//! Diagram-vs-prose classification of scanned blocks.
+//!
+//! The scanner hands every paragraph to diagram mode, including prose that
+//! merely mentions an arrow ("Input → Output"). This stage scores each block
+//! so that only blocks that look like drawings reach the detectors.
+
+use serde::{Deserialize, Serialize};
+
+/// Default minimum score for a block to be treated as a diagram.
+pub const DEFAULT_DIAGRAM_THRESHOLD: f64 = 0.3;
+
+/// Weights of the individual signals (sum to 1.0).
+const GLYPH_DENSITY_WEIGHT: f64 = 0.35;
+const LINE_STRUCTURE_WEIGHT: f64 = 0.2;
+const CLOSURE_WEIGHT: f64 = 0.25;
+const NON_ALPHA_WEIGHT: f64 = 0.2;
+
+/// Classification result for a single diagram block.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BlockScore {
+    /// Line of the block's first row (1-based)
+    pub line: usize,
+    /// Confidence that the block is a diagram, from 0.0 (prose) to 1.0
+    pub score: f64,
+    /// Whether the score reached the threshold and the block was processed
+    pub processed: bool,
+}
+
+/// Check if a character is a drawing glyph (box-drawing, arrows, connectors).
+#[must_use]
+pub const fn is_diagram_glyph(ch: char) -> bool {
+    matches!(
+        ch,
+        '─' | '│'
+            | '┌'
+            | '┐'
+            | '└'
+            | '┘'
+            | '├'
+            | '┤'
+            | '┬'
+            | '┴'
+            | '┼'
+            | '━'
+            | '┃'
+            | '═'
+            | '║'
+            | '╔'
+            | '╗'
+            | '╚'
+            | '╝'
+            | '╠'
+            | '╣'
+            | '╦'
+            | '╩'
+            | '╬'
+            | '╭'
+            | '╮'
+            | '╰'
+            | '╯'
+            | '→'
+            | '←'
+            | '↑'
+            | '↓'
+            | '⇒'
+            | '⇐'
+            | '⇑'
+            | '⇓'
+            | '⟶'
+            | '⟹'
+            | '▶'
+            | '◀'
+            | '▲'
+            | '▼'
+    )
+}
+
+/// Check if a character is a top-left corner.
+const fn is_top_left(ch: char) -> bool {
+    matches!(ch, '┌' | '╔' | '╭')
+}
+
+/// Check if a character is a bottom-right corner.
+const fn is_bottom_right(ch: char) -> bool {
+    matches!(ch, '┘' | '╝' | '╯')
+}
+
+/// Ratio helper returning 0.0 for an empty denominator.
+#[allow(clippy::cast_precision_loss)] // Reason: Character counts are far below 2^52
+fn ratio(part: usize, whole: usize) -> f64 {
+    if whole == 0 {
+        0.0
+    } else {
+        part as f64 / whole as f64
+    }
+}
+
+/// Check whether the block closes at least one rectangle: a top-left corner
+/// with a bottom-right corner below and to the right of it.
+fn has_rectangular_closure(rows: &[Vec<char>]) -> bool {
+    rows.iter().enumerate().any(|(r, row)| {
+        row.iter().enumerate().any(|(c, &ch)| {
+            is_top_left(ch)
+                && rows[r + 1..]
+                    .iter()
+                    .any(|below| below.iter().skip(c + 1).any(|&b| is_bottom_right(b)))
+        })
+    })
+}
+
+/// Score how much a block of lines looks like a diagram rather than prose.
+///
+/// Combines four signals:
+/// - glyph density: share of non-space characters that are drawing glyphs
+/// - line structure: share of lines carrying glyphs (halved for one-line blocks)
+/// - rectangular closure: whether at least one box is closed
+/// - alphabetic share: prose is mostly letters, so it lowers the score
+///
+/// Returns a value between 0.0 and 1.0.
+#[must_use]
+pub fn score_block(lines: &[String]) -> f64 {
+    let rows: Vec<Vec<char>> = lines.iter().map(|line| line.chars().collect()).collect();
+
+    let visible: Vec<char> = rows
+        .iter()
+        .flatten()
+        .copied()
+        .filter(|ch| !ch.is_whitespace())
+        .collect();
+    if visible.is_empty() {
+        return 0.0;
+    }
+    let glyphs = visible.iter().filter(|&&ch| is_diagram_glyph(ch)).count();
+    let alphabetic = visible.iter().filter(|ch| ch.is_alphabetic()).count();
+
+    let non_blank_lines = rows
+        .iter()
+        .filter(|row| row.iter().any(|ch| !ch.is_whitespace()))
+        .count();
+    let glyph_lines = rows
+        .iter()
+        .filter(|row| row.iter().any(|&ch| is_diagram_glyph(ch)))
+        .count();
+    let mut line_structure = ratio(glyph_lines, non_blank_lines);
+    if non_blank_lines < 2 {
+        line_structure /= 2.0;
+    }
+
+    let closure = if has_rectangular_closure(&rows) {
+        1.0
+    } else {
+        0.0
+    };
+
+    let signals = [
+        (GLYPH_DENSITY_WEIGHT, ratio(glyphs, visible.len())),
+        (LINE_STRUCTURE_WEIGHT, line_structure),
+        (CLOSURE_WEIGHT, closure),
+        (NON_ALPHA_WEIGHT, 1.0 - ratio(alphabetic, visible.len())),
+    ];
+    let score = signals
+        .iter()
+        .fold(0.0, |acc, (weight, signal)| weight.mul_add(*signal, acc));
+
+    // Round so reported scores are stable and readable
+    (score.clamp(0.0, 1.0) * 100.0).round() / 100.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(text: &str) -> Vec<String> {
+        text.lines().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_box_scores_high() {
+        let score = score_block(&lines("┌─────┐\n│ API │\n└─────┘"));
+        assert!(score > 0.8, "score was {score}");
+    }
+
+    #[test]
+    fn test_prose_with_arrow_scores_low() {
+        let score = score_block(&lines("Input → Output"));
+        assert!(score < DEFAULT_DIAGRAM_THRESHOLD, "score was {score}");
+        let score = score_block(&lines("A ─ B"));
+        assert!(score < DEFAULT_DIAGRAM_THRESHOLD, "score was {score}");
+    }
+
+    #[test]
+    fn test_arrow_chain_passes_threshold() {
+        let score = score_block(&lines("A ───→ B\n    ↓\n    C"));
+        assert!(score >= DEFAULT_DIAGRAM_THRESHOLD, "score was {score}");
+    }
+
+    #[test]
+    fn test_empty_block_scores_zero() {
+        assert!(score_block(&[]).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_closure_requires_matching_corner() {
+        assert!(has_rectangular_closure(&[
+            "┌─┐".chars().collect(),
+            "└─┘".chars().collect()
+        ]));
+        assert!(!has_rectangular_closure(&["┌──".chars().collect()]));
+    }
+}
@@ -1,13 +1,20 @@
 //! Command-line argument parsing and configuration.
 
-use clap::{Parser, ValueEnum};
+use crate::fixes::{Fix, FixRequest, FixSelection};
+use clap::{Parser, Subcommand, ValueEnum};
+use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
 const KB: u64 = 1024;
 const MB: u64 = 1024 * KB;
 const GB: u64 = 1024 * MB;
 
-fn parse_size(s: &str) -> Result<u64, String> {
+/// Parse a size such as `5MB`, `1024KB` or `100` (bytes).
+///
+/// # Errors
+///
+/// Returns an error if the value is empty, not a number, or overflows.
+pub fn parse_size(s: &str) -> Result<u64, String> {
     let original = s.trim();
     if original.is_empty() {
         return Err("size cannot be empty".into());
@@ -43,16 +50,47 @@ fn parse_size(s: &str) -> Result<u64, String> {
         .ok_or_else(|| format!("size too large: '{original}'"))
 }
 
+fn parse_threshold(s: &str) -> Result<f64, String> {
+    let value: f64 = s
+        .trim()
+        .parse()
+        .map_err(|_| format!("invalid threshold: '{s}'"))?;
+    if (0.0..=1.0).contains(&value) {
+        Ok(value)
+    } else {
+        Err(format!("threshold must be between 0.0 and 1.0: '{s}'"))
+    }
+}
+
 #[derive(Parser, Debug, Clone)]
 #[command(name = "ascfix")]
 #[command(about = "Repair ASCII diagrams in Markdown and text files")]
+#[command(subcommand_negates_reqs = true)]
+#[command(
+    after_help = "`config` names the config subcommand. Write `./config` or `-- config` to \
+                  process a file or directory of that name."
+)]
 #[allow(clippy::struct_excessive_bools)] // CLI flags are naturally boolean
 pub struct Args {
     #[arg(required = true)]
     pub paths: Vec<PathBuf>,
 
-    #[arg(long, value_enum, default_value = "safe")]
-    pub mode: Mode,
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Preset of fixers (default: `mode` from .ascfix.toml, or safe)
+    #[arg(long, value_enum)]
+    pub mode: Option<Mode>,
+
+    /// Run exactly these fixers: fences, tables, lists, boxes, arrows, diagrams, all
+    /// (there is no links fixer: links are preserved, never rewritten); with a
+    /// leading `+` (`+fences`), run them on top of the mode preset
+    #[arg(long, value_name = "FIXERS", value_parser = FixRequest::parse)]
+    pub fix: Option<FixRequest>,
+
+    /// Do not run these fixers
+    #[arg(long, value_name = "FIXERS", value_parser = FixSelection::parse)]
+    pub skip: Option<FixSelection>,
 
     #[arg(short, long)]
     pub in_place: bool,
@@ -60,22 +98,36 @@ pub struct Args {
     #[arg(long, conflicts_with = "in_place")]
     pub check: bool,
 
+    /// Exit with code 2 when a repair was refused because it would change the text
+    #[arg(long)]
+    pub strict: bool,
+
+    /// Exit with code 1 when a finding is at least this severe, instead of when a file would change
+    #[arg(long, value_enum, value_name = "SEVERITY")]
+    pub fail_on: Option<crate::rules::Severity>,
+
+    /// Ignore findings recorded in this baseline file; only new or changed findings fail
+    #[arg(long, value_name = "FILE")]
+    pub baseline: Option<PathBuf>,
+
+    /// Record every current finding in this baseline file
+    #[arg(long, value_name = "FILE")]
+    pub write_baseline: Option<PathBuf>,
+
     #[arg(long, value_parser = parse_size)]
     pub max_size: Option<u64>,
 
-    #[arg(long)]
+    /// Alias for `--fix +fences`
+    #[arg(long, hide = true)]
     pub fences: bool,
 
-    #[arg(long)]
+    /// Alias for `--fix all`
+    #[arg(long, hide = true)]
     pub all: bool,
 
-    #[arg(
-        long,
-        short = 'e',
-        value_delimiter = ',',
-        default_value = ".md,.mdx,.txt"
-    )]
-    pub ext: Vec<String>,
+    /// File extensions to process (default: `extensions` from .ascfix.toml, or .md,.mdx,.txt)
+    #[arg(long, short = 'e', value_delimiter = ',')]
+    pub ext: Option<Vec<String>>,
 
     #[arg(long)]
     pub no_gitignore: bool,
@@ -92,34 +144,90 @@ pub struct Args {
     #[arg(long)]
     pub json: bool,
 
+    /// Show a unified diff of the changes instead of the processed content
     #[arg(long)]
     pub diff: bool,
+
+    /// Unchanged lines shown around each change in diffs and patches
+    #[arg(long, value_name = "N", default_value_t = 3)]
+    pub context: usize,
+
+    /// When to color output: auto (terminal without `NO_COLOR`), always or never
+    #[arg(long, value_enum, value_name = "WHEN", default_value = "auto")]
+    pub color: crate::output::ColorChoice,
+
+    /// Write a patch of every change, for `git apply`, to this file
+    #[arg(long, value_name = "FILE", conflicts_with = "in_place")]
+    pub patch: Option<PathBuf>,
+
+    /// Report findings as sarif, github (workflow annotations), junit or checkstyle
+    #[arg(long, value_enum, conflicts_with_all = ["json", "list_files", "diff"])]
+    pub format: Option<crate::report::ReportFormat>,
+
+    /// Strip ANSI escape sequences (terminal colors) outside code fences
+    #[arg(long)]
+    pub strip_ansi: bool,
+
+    /// Also strip ANSI escape sequences inside text/console fences
+    #[arg(long, requires = "strip_ansi")]
+    pub strip_ansi_fences: bool,
+
+    /// Minimum diagram-vs-prose score (0.0-1.0) for a block to be processed
+    #[arg(long, value_parser = parse_threshold)]
+    pub diagram_threshold: Option<f64>,
+
+    /// Use this configuration file instead of discovering .ascfix.toml files
+    #[arg(long, value_name = "PATH", conflicts_with = "no_config", global = true)]
+    pub config: Option<PathBuf>,
+
+    /// Ignore all configuration files and use the built-in defaults
+    #[arg(long, global = true)]
+    pub no_config: bool,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum Command {
+    /// Create, inspect and check configuration files
+    #[command(subcommand)]
+    Config(ConfigCommand),
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum ConfigCommand {
+    /// Write a default configuration file with every option commented
+    Init {
+        /// File to create
+        #[arg(default_value = ".ascfix.toml")]
+        path: PathBuf,
+
+        /// Overwrite the file if it exists
+        #[arg(long)]
+        force: bool,
+    },
+    /// Print the effective configuration for a file and where each value comes from
+    Show {
+        /// File (or directory) to resolve the configuration for
+        file: PathBuf,
+    },
+    /// Check configuration files for errors and unknown keys
+    Validate {
+        /// Files to check (default: the configuration files that apply to the current directory)
+        files: Vec<PathBuf>,
+    },
 }
 
-#[derive(ValueEnum, Clone, Debug, PartialEq, Eq)]
+#[derive(ValueEnum, Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Mode {
+    /// Tables and lists
     Safe,
+    /// Boxes and arrows in ASCII diagrams
     Diagram,
-    Check,
-}
-
-#[derive(Debug)]
-#[allow(clippy::struct_excessive_bools)] // Configuration flags are naturally boolean
-#[allow(dead_code)] // Used in main and tests, not in lib
-pub struct Config {
-    pub paths: Vec<PathBuf>,
-    pub mode: Mode,
-    pub in_place: bool,
-    pub check: bool,
-    pub max_size: Option<u64>,
-    pub fences: bool,
-    pub extensions: Vec<String>,
-    pub no_gitignore: bool,
 }
 
 /// Normalize extension list: deduplicate, trim, lowercase, ensure leading dot.
-#[allow(dead_code)] // Used in Config From impl, which is used in main and tests
-fn normalize_exts(exts: &[String]) -> Vec<String> {
+#[must_use]
+pub fn normalize_exts(exts: &[String]) -> Vec<String> {
     let mut normalized: Vec<String> = exts
         .iter()
         .map(|s| {
@@ -145,21 +253,26 @@ fn normalize_exts(exts: &[String]) -> Vec<String> {
     normalized
 }
 
-impl From<Args> for Config {
-    fn from(args: Args) -> Self {
-        let mode = if args.all { Mode::Diagram } else { args.mode };
-        let fences = if args.all { true } else { args.fences };
-
-        Self {
-            paths: args.paths,
-            mode,
-            in_place: args.in_place,
-            check: args.check,
-            max_size: args.max_size,
-            fences,
-            extensions: normalize_exts(&args.ext),
-            no_gitignore: args.no_gitignore,
+impl Args {
+    /// The fixers asked for with `--fix`, including the hidden `--all`
+    /// (`--fix all`) and `--fences` (`--fix +fences`) aliases.
+    #[must_use]
+    pub const fn fix_request(&self) -> Option<FixRequest> {
+        if self.all {
+            return Some(FixRequest::Exactly(FixSelection::ALL));
+        }
+        if !self.fences {
+            return self.fix;
         }
+        Some(match self.fix {
+            Some(FixRequest::Exactly(selection)) => {
+                FixRequest::Exactly(selection.with(Fix::Fences, true))
+            }
+            Some(FixRequest::Adding(selection)) => {
+                FixRequest::Adding(selection.with(Fix::Fences, true))
+            }
+            None => FixRequest::Adding(FixSelection::NONE.with(Fix::Fences, true)),
+        })
     }
 }
 
@@ -205,6 +318,14 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn parse_threshold_range() {
+        assert!((parse_threshold("0.5").unwrap() - 0.5).abs() < f64::EPSILON);
+        assert!(parse_threshold("0").is_ok());
+        assert!(parse_threshold("1.5").is_err());
+        assert!(parse_threshold("high").is_err());
+    }
+
     // ------------------------------------------------------------
     // Basic parsing tests
     // ------------------------------------------------------------
@@ -219,12 +340,32 @@ mod tests {
         );
     }
 
+    #[test]
+    fn parse_config_path_after_double_dash() {
+        let args = Args::try_parse_from(["ascfix", "-i", "--", "config"]).unwrap();
+        assert!(args.command.is_none());
+        assert!(args.in_place);
+        assert_eq!(args.paths, vec![PathBuf::from("config")]);
+        let args = Args::try_parse_from(["ascfix", "./config"]).unwrap();
+        assert_eq!(args.paths, vec![PathBuf::from("./config")]);
+
+        // Without `--`, `config` is always the subcommand
+        let args =
+            Args::try_parse_from(["ascfix", "config", "--no-config", "show", "a.md"]).unwrap();
+        assert!(matches!(
+            args.command,
+            Some(Command::Config(ConfigCommand::Show { .. }))
+        ));
+        assert!(Args::try_parse_from(["ascfix", "config"]).is_err());
+    }
+
     #[test]
     fn parse_single_file_defaults() {
         let args = Args::try_parse_from(["ascfix", "test.md"]).unwrap();
 
         assert_eq!(args.paths, vec![PathBuf::from("test.md")]);
-        assert_eq!(args.mode, Mode::Safe);
+        assert_eq!(args.mode, None);
+        assert_eq!(args.ext, None);
         assert!(!args.in_place);
         assert!(!args.check);
         assert!(!args.fences);
@@ -235,7 +376,7 @@ mod tests {
     fn parse_mode_diagram() {
         let args = Args::try_parse_from(["ascfix", "--mode", "diagram", "file.md"]).unwrap();
 
-        assert_eq!(args.mode, Mode::Diagram);
+        assert_eq!(args.mode, Some(Mode::Diagram));
     }
 
     #[test]
@@ -275,10 +416,15 @@ mod tests {
     // Extension normalization
     // ------------------------------------------------------------
 
+    /// The configuration the flags in `argv` produce over the defaults.
+    fn config_from(command_line: &[&str]) -> crate::config::Config {
+        let args = Args::try_parse_from(command_line).unwrap();
+        crate::processor::Processor::apply_args(crate::config::Config::default(), &args)
+    }
+
     #[test]
     fn default_extensions_include_txt() {
-        let args = Args::try_parse_from(["ascfix", "file.md"]).unwrap();
-        let config: Config = args.into();
+        let config = config_from(&["ascfix", "file.md"]);
 
         assert!(config.extensions.contains(&".md".to_string()));
         assert!(config.extensions.contains(&".mdx".to_string()));
@@ -287,10 +433,7 @@ mod tests {
 
     #[test]
     fn extension_normalization_trim_lowercase_dot() {
-        let args =
-            Args::try_parse_from(["ascfix", "--ext", "md,.MD, txt ,.txt", "file.md"]).unwrap();
-
-        let config: Config = args.into();
+        let config = config_from(&["ascfix", "--ext", "md,.MD, txt ,.txt", "file.md"]);
 
         assert_eq!(
             config.extensions,
@@ -300,9 +443,7 @@ mod tests {
 
     #[test]
     fn extension_order_preserved() {
-        let args = Args::try_parse_from(["ascfix", "--ext", "txt,md", "file.md"]).unwrap();
-
-        let config: Config = args.into();
+        let config = config_from(&["ascfix", "--ext", "txt,md", "file.md"]);
 
         assert_eq!(
             config.extensions,
@@ -311,27 +452,53 @@ mod tests {
     }
 
     // ------------------------------------------------------------
-    // --all semantic expansion
+    // Hidden --all and --fences aliases
     // ------------------------------------------------------------
 
     #[test]
-    fn all_expands_to_diagram_and_fences() {
-        let args = Args::try_parse_from(["ascfix", "--all", "file.md"]).unwrap();
-
-        let config: Config = args.into();
-
-        assert_eq!(config.mode, Mode::Diagram);
-        assert!(config.fences);
+    fn all_is_fix_all() {
+        let args = Args::try_parse_from(["ascfix", "--mode", "safe", "--all", "file.md"]).unwrap();
+        assert_eq!(
+            args.fix_request(),
+            Some(FixRequest::Exactly(FixSelection::ALL))
+        );
+        assert_eq!(
+            config_from(&["ascfix", "--all", "file.md"]).selected_fixes(),
+            FixSelection::ALL
+        );
     }
 
     #[test]
-    fn all_overrides_safe_mode() {
-        let args = Args::try_parse_from(["ascfix", "--mode", "safe", "--all", "file.md"]).unwrap();
+    fn fences_adds_fences_to_the_fix_request() {
+        let fences = FixSelection::NONE.with(Fix::Fences, true);
+        let request = |argv: &[&str]| Args::try_parse_from(argv).unwrap().fix_request();
 
-        let config: Config = args.into();
+        assert_eq!(
+            request(&["ascfix", "--fences", "file.md"]),
+            Some(FixRequest::Adding(fences))
+        );
+        assert_eq!(
+            request(&["ascfix", "--fix", "+fences", "file.md"]),
+            request(&["ascfix", "--fences", "file.md"])
+        );
+        assert_eq!(
+            request(&["ascfix", "--fix", "tables", "--fences", "file.md"]),
+            Some(FixRequest::Exactly(fences.with(Fix::Tables, true)))
+        );
+        assert_eq!(
+            config_from(&["ascfix", "--mode", "diagram", "--fences", "file.md"])
+                .selected_fixes()
+                .to_string(),
+            "fences,boxes,arrows"
+        );
+    }
 
-        assert_eq!(config.mode, Mode::Diagram);
-        assert!(config.fences);
+    #[test]
+    fn aliases_are_hidden_from_help() {
+        use clap::CommandFactory;
+        let help = Args::command().render_long_help().to_string();
+        assert!(!help.contains("--fences"), "{help}");
+        assert!(!help.contains("--all"), "{help}");
     }
 
     // ------------------------------------------------------------
@@ -340,7 +507,7 @@ mod tests {
 
     #[test]
     fn complex_configuration() {
-        let args = Args::try_parse_from([
+        let config = config_from(&[
             "ascfix",
             "--mode",
             "diagram",
@@ -350,10 +517,7 @@ mod tests {
             "md,txt",
             "--no-gitignore",
             "docs/",
-        ])
-        .unwrap();
-
-        let config: Config = args.into();
+        ]);
 
         assert_eq!(config.mode, Mode::Diagram);
         assert_eq!(config.max_size, Some(10 * 1024 * 1024));
@@ -361,7 +525,6 @@ mod tests {
             config.extensions,
             vec![".md".to_string(), ".txt".to_string()]
         );
-        assert!(config.no_gitignore);
-        assert_eq!(config.paths, vec![PathBuf::from("docs/")]);
+        assert!(!config.respect_gitignore);
     }
 }
@@ -1,25 +1,46 @@
 //! Configuration file support for ascfix
 //!
-//! Supports TOML configuration files for customizing behavior.
+//! Supports TOML configuration files for customizing behavior. Every table
+//! rejects unknown keys, so a typo is reported instead of silently ignored.
 
+use crate::cli::Mode;
+use crate::fixes::{Fix, FixSelection};
+use crate::primitives::BoxStyle;
+use crate::quality::QualityConfig;
+use crate::rules::RulesConfig;
 use serde::{Deserialize, Serialize};
-use std::path::Path;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
 
 /// Default configuration values
 pub const DEFAULT_MAX_LINE_LENGTH: usize = 120;
-pub const DEFAULT_BOX_PADDING: usize = 1;
+pub const DEFAULT_BOX_PADDING: usize = 0;
+pub const DEFAULT_EXTENSIONS: [&str; 3] = [".md", ".mdx", ".txt"];
+pub const DEFAULT_MAX_SIZE: u64 = 100 * 1024 * 1024;
+
+/// Files searched for settings in every directory, nearest first. A
+/// `pyproject.toml` or `Cargo.toml` only counts when it has an ascfix table.
+pub const CONFIG_FILE_NAMES: [&str; 3] = [".ascfix.toml", "pyproject.toml", "Cargo.toml"];
 
 /// Configuration for diagram formatting
 #[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct FormattingConfig {
     /// Maximum line length for wrapping
     pub max_line_length: usize,
-    /// Padding inside boxes
+    /// Minimum spaces between box text and the left/right borders (0 keeps text where it is)
     pub box_padding: usize,
     /// Whether to preserve Unicode characters
     pub preserve_unicode: bool,
     /// Whether to validate diagrams
     pub validate_diagrams: bool,
+    /// Redraw every box in this style (keep each box's own style when unset)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub box_style: Option<BoxStyle>,
+    /// Whether to balance the widths of side-by-side boxes
+    pub balance_boxes: bool,
 }
 
 impl Default for FormattingConfig {
@@ -29,72 +50,902 @@ impl Default for FormattingConfig {
             box_padding: DEFAULT_BOX_PADDING,
             preserve_unicode: true,
             validate_diagrams: false,
+            box_style: None,
+            balance_boxes: true,
+        }
+    }
+}
+
+/// Which non-Markdown blocks are protected from list, table and diagram processing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+#[allow(clippy::struct_excessive_bools)] // Configuration flags are naturally boolean
+pub struct ProtectConfig {
+    /// YAML (`---`) and TOML (`+++`) front matter
+    pub front_matter: bool,
+    /// `$$` display math blocks
+    pub math: bool,
+    /// Raw HTML blocks (`<pre>`, `<table>`, `<details>`, comments...)
+    pub html: bool,
+    /// MDX `import`/`export` statements and JSX elements
+    pub mdx: bool,
+    /// Repair diagrams inside `<pre>` blocks as if they were plain text
+    pub pre_as_text_fence: bool,
+}
+
+impl Default for ProtectConfig {
+    fn default() -> Self {
+        Self {
+            front_matter: true,
+            math: true,
+            html: true,
+            mdx: true,
+            pre_as_text_fence: false,
+        }
+    }
+}
+
+/// Fixers switched on or off on top of the mode preset (the `[fixes]` table).
+/// Unset keys keep what the mode selects.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+#[allow(clippy::struct_field_names)] // Field names are the documented `[fixes]` keys
+pub struct FixesConfig {
+    /// Repair code fence boundaries
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fix_fences: Option<bool>,
+    /// Normalize Markdown tables
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fix_tables: Option<bool>,
+    /// Normalize list indentation and bullets
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fix_lists: Option<bool>,
+    /// Both diagram fixers (`fix_boxes` and `fix_arrows` take precedence)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fix_diagrams: Option<bool>,
+    /// Resize, balance and pad boxes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fix_boxes: Option<bool>,
+    /// Align arrows to the boxes they connect
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fix_arrows: Option<bool>,
+}
+
+impl FixesConfig {
+    /// Settings selecting exactly `selection`, whatever the mode.
+    #[must_use]
+    pub const fn exactly(selection: FixSelection) -> Self {
+        Self {
+            fix_fences: Some(selection.contains(Fix::Fences)),
+            fix_tables: Some(selection.contains(Fix::Tables)),
+            fix_lists: Some(selection.contains(Fix::Lists)),
+            fix_diagrams: None,
+            fix_boxes: Some(selection.contains(Fix::Boxes)),
+            fix_arrows: Some(selection.contains(Fix::Arrows)),
         }
     }
+
+    /// Switch `fix` on or off.
+    pub const fn set(&mut self, fix: Fix, enabled: bool) {
+        let slot = match fix {
+            Fix::Fences => &mut self.fix_fences,
+            Fix::Tables => &mut self.fix_tables,
+            Fix::Lists => &mut self.fix_lists,
+            Fix::Boxes => &mut self.fix_boxes,
+            Fix::Arrows => &mut self.fix_arrows,
+        };
+        *slot = Some(enabled);
+    }
+
+    /// Check whether no key is set.
+    #[must_use]
+    #[allow(clippy::trivially_copy_pass_by_ref)] // Signature required by `skip_serializing_if`
+    pub const fn is_unset(&self) -> bool {
+        self.fix_fences.is_none()
+            && self.fix_tables.is_none()
+            && self.fix_lists.is_none()
+            && self.fix_diagrams.is_none()
+            && self.fix_boxes.is_none()
+            && self.fix_arrows.is_none()
+    }
+
+    /// Apply the keys that are set to a preset.
+    #[must_use]
+    pub fn apply(self, preset: FixSelection) -> FixSelection {
+        let mut selection = preset;
+        if let Some(enabled) = self.fix_diagrams {
+            selection = selection
+                .with(Fix::Boxes, enabled)
+                .with(Fix::Arrows, enabled);
+        }
+        for (fix, setting) in [
+            (Fix::Fences, self.fix_fences),
+            (Fix::Tables, self.fix_tables),
+            (Fix::Lists, self.fix_lists),
+            (Fix::Boxes, self.fix_boxes),
+            (Fix::Arrows, self.fix_arrows),
+        ] {
+            if let Some(enabled) = setting {
+                selection = selection.with(fix, enabled);
+            }
+        }
+        selection
+    }
+}
+
+/// Settings merged onto the base configuration for files matching `paths`
+/// (an `[[overrides]]` section).
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PathOverride {
+    /// Globs matched against paths relative to the config file's directory
+    pub paths: Vec<String>,
+    /// Top-level keys and tables to override, with the same schema as the file
+    #[serde(flatten)]
+    pub settings: toml::Table,
+    /// Directory the globs are relative to (the current directory when empty)
+    #[serde(skip)]
+    base_dir: PathBuf,
+    /// `paths` compiled when the file is loaded
+    #[serde(skip)]
+    globs: globset::GlobSet,
+}
+
+impl PathOverride {
+    /// Compile the globs of this section.
+    fn glob_set(&self) -> Result<globset::GlobSet, String> {
+        let mut builder = globset::GlobSetBuilder::new();
+        for pattern in &self.paths {
+            let glob = globset::GlobBuilder::new(pattern)
+                .literal_separator(true)
+                .build()
+                .map_err(|e| format!("invalid glob '{pattern}' in [[overrides]]: {e}"))?;
+            builder.add(glob);
+        }
+        builder
+            .build()
+            .map_err(|e| format!("invalid [[overrides]] paths: {e}"))
+    }
+
+    /// Check whether a file path matches one of the globs.
+    #[must_use]
+    pub fn matches(&self, path: &Path) -> bool {
+        let base_dir = if self.base_dir.as_os_str().is_empty() {
+            Path::new(".")
+        } else {
+            &self.base_dir
+        };
+        let (Ok(base_dir), Ok(path)) = (std::path::absolute(base_dir), std::path::absolute(path))
+        else {
+            return false;
+        };
+        path.strip_prefix(&base_dir)
+            .is_ok_and(|relative| self.globs.is_match(relative))
+    }
+}
+
+/// Path of the `[tool.ascfix]`-style table holding the settings in a shared
+/// project file, or `None` for a dedicated configuration file.
+fn embedded_table(path: &Path) -> Option<&'static [&'static str]> {
+    match path.file_name()?.to_str()? {
+        "pyproject.toml" => Some(&["tool", "ascfix"]),
+        "Cargo.toml" => Some(&["package", "metadata", "ascfix"]),
+        _ => None,
+    }
+}
+
+/// Merge `overlay` into `base`, recursing into tables present in both.
+fn merge_tables(base: &mut toml::Table, overlay: &toml::Table) {
+    for (key, value) in overlay {
+        match (base.get_mut(key), value) {
+            (Some(toml::Value::Table(base_table)), toml::Value::Table(overlay_table)) => {
+                merge_tables(base_table, overlay_table);
+            }
+            _ => {
+                base.insert(key.clone(), value.clone());
+            }
+        }
+    }
+}
+
+/// (De)serialize `max_size` as a size string (`"100MB"`) or a byte count.
+mod size {
+    use serde::{de, Deserializer, Serializer};
+    use std::fmt;
+
+    #[allow(clippy::ref_option)] // Reason: Signature required by serde's `with` attribute
+    pub fn serialize<S: Serializer>(value: &Option<u64>, serializer: S) -> Result<S::Ok, S::Error> {
+        let Some(bytes) = value else {
+            return serializer.serialize_none();
+        };
+        let (amount, unit) = [("GB", 1 << 30), ("MB", 1 << 20), ("KB", 1 << 10)]
+            .into_iter()
+            .find(|(_, factor)| *bytes >= *factor && bytes % factor == 0)
+            .map_or((*bytes, "B"), |(unit, factor)| (bytes / factor, unit));
+        serializer.serialize_str(&format!("{amount}{unit}"))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<u64>, D::Error> {
+        struct SizeVisitor;
+
+        impl de::Visitor<'_> for SizeVisitor {
+            type Value = Option<u64>;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a size such as \"100MB\" or a number of bytes")
+            }
+
+            fn visit_u64<E: de::Error>(self, value: u64) -> Result<Self::Value, E> {
+                Ok(Some(value))
+            }
+
+            fn visit_i64<E: de::Error>(self, value: i64) -> Result<Self::Value, E> {
+                u64::try_from(value)
+                    .map(Some)
+                    .map_err(|_| E::custom("size cannot be negative"))
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+                crate::cli::parse_size(value).map(Some).map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_any(SizeVisitor)
+    }
+}
+
+/// Read `diagram_threshold`, rejecting values outside 0.0-1.0 like
+/// `--diagram-threshold` does.
+fn threshold<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+    let value = f64::deserialize(deserializer)?;
+    if (0.0..=1.0).contains(&value) {
+        Ok(value)
+    } else {
+        Err(serde::de::Error::custom(format!(
+            "diagram_threshold must be between 0.0 and 1.0: {value}"
+        )))
+    }
 }
 
 /// Main configuration structure
-#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+///
+/// Fields mirror the `.ascfix.toml` schema documented in CONFIG.md. Command-line
+/// flags take precedence over these values (see `Processor::new`).
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+#[allow(clippy::struct_excessive_bools)] // Configuration flags are naturally boolean
 pub struct Config {
+    /// Processing mode used when `--mode` is not given
+    pub mode: Mode,
+    /// File extensions to process when `--ext` is not given
+    pub extensions: Vec<String>,
+    /// Files larger than this many bytes are skipped
+    #[serde(with = "size", skip_serializing_if = "Option::is_none")]
+    pub max_size: Option<u64>,
+    /// Whether to honor `.gitignore` when discovering files
+    pub respect_gitignore: bool,
+    /// Leave deeply nested diagrams (more than two levels of boxes) untouched
+    pub conservative: bool,
+    /// Fixers switched on or off on top of the `mode` preset
+    #[serde(skip_serializing_if = "FixesConfig::is_unset")]
+    pub fixes: FixesConfig,
     /// Formatting options
     pub formatting: FormattingConfig,
+    /// Checks a repaired diagram block must pass to replace the original
+    pub quality: QualityConfig,
+    /// Severity of each diagnostic rule, by rule ID (`ASC101 = "off"`)
+    #[serde(skip_serializing_if = "RulesConfig::is_empty")]
+    pub rules: RulesConfig,
     /// Whether to enable flowchart support
     pub enable_flowcharts: bool,
     /// Whether to enable sequence diagrams
     pub enable_sequence_diagrams: bool,
+    /// Whether to strip ANSI escape sequences outside code fences
+    pub strip_ansi: bool,
+    /// Whether to also strip ANSI escapes inside text/console fences
+    pub strip_ansi_in_fences: bool,
+    /// Minimum classifier score (0.0-1.0) for a block to be treated as a diagram
+    #[serde(deserialize_with = "threshold")]
+    pub diagram_threshold: f64,
+    /// Non-Markdown blocks left untouched
+    pub protect: ProtectConfig,
+    /// Leave matching files untouched (useful in `[[overrides]]`)
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub ignore: bool,
+    /// Per-path settings, applied in order on top of the rest of the file
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub overrides: Vec<PathOverride>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            mode: Mode::Safe,
+            extensions: DEFAULT_EXTENSIONS.iter().map(ToString::to_string).collect(),
+            max_size: Some(DEFAULT_MAX_SIZE),
+            respect_gitignore: true,
+            conservative: true,
+            fixes: FixesConfig::default(),
+            formatting: FormattingConfig::default(),
+            quality: QualityConfig::default(),
+            rules: RulesConfig::default(),
+            enable_flowcharts: false,
+            enable_sequence_diagrams: false,
+            strip_ansi: false,
+            strip_ansi_in_fences: false,
+            diagram_threshold: crate::classifier::DEFAULT_DIAGRAM_THRESHOLD,
+            protect: ProtectConfig::default(),
+            ignore: false,
+            overrides: Vec::new(),
+        }
+    }
 }
 
 impl Config {
+    /// Fixers to run: the `mode` preset with the `[fixes]` table applied.
+    #[must_use]
+    pub fn selected_fixes(&self) -> FixSelection {
+        self.fixes.apply(FixSelection::preset(&self.mode))
+    }
+
     /// Load configuration from a TOML file
     ///
     /// # Errors
     ///
-    /// Returns an error if the file cannot be read or if the TOML is invalid.
+    /// Returns an error if the file cannot be read, if the TOML is invalid, or if
+    /// it contains an unknown key. The message names the file, line and key.
+    ///
+    /// For a `pyproject.toml` or `Cargo.toml` the settings are read from its
+    /// `[tool.ascfix]` or `[package.metadata.ascfix]` table.
+    #[allow(dead_code)] // Reason: Part of public API for library usage
     pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn std::error::Error>> {
-        let contents = std::fs::read_to_string(path)?;
-        let config: Self = toml::from_str(&contents)?;
+        let path = path.as_ref();
+        let (config, _) = Self::read_settings(path)?.ok_or_else(|| {
+            format!(
+                "{}: no [{}] table",
+                path.display(),
+                embedded_table(path).unwrap_or_default().join(".")
+            )
+        })?;
         Ok(config)
     }
 
-    /// Load configuration from current directory or parents
+    /// Read and validate the settings of a configuration file, together with
+    /// the raw table they came from. `None` means the file is a
+    /// `pyproject.toml` or `Cargo.toml` without an ascfix table.
+    fn read_settings(path: &Path) -> Result<Option<(Self, toml::Table)>, String> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| format!("cannot read {}: {e}", path.display()))?;
+        let document: toml::Table =
+            toml::from_str(&contents).map_err(|e| format!("{}: {e}", path.display()))?;
+        if embedded_table(path).is_none() {
+            // Deserializing the text itself lets errors point at their line
+            toml::from_str::<Self>(&contents).map_err(|e| format!("{}: {e}", path.display()))?;
+        }
+        Self::from_document(path, &document)
+    }
+
+    /// Validate the settings of a parsed configuration file read from
+    /// `path`, together with the raw table they came from. `None` means the
+    /// file is a `pyproject.toml` or `Cargo.toml` without an ascfix table.
+    pub(crate) fn from_document(
+        path: &Path,
+        document: &toml::Table,
+    ) -> Result<Option<(Self, toml::Table)>, String> {
+        let keys = embedded_table(path).unwrap_or_default();
+        let Some(table) = keys
+            .iter()
+            .try_fold(document, |table, key| table.get(*key)?.as_table())
+        else {
+            return Ok(None);
+        };
+        let mut config: Self = table.clone().try_into().map_err(|e| {
+            if keys.is_empty() {
+                format!("{}: {e}", path.display())
+            } else {
+                format!("{}: [{}]: {e}", path.display(), keys.join("."))
+            }
+        })?;
+        let base_dir = path.parent().unwrap_or_else(|| Path::new("")).to_path_buf();
+        for (i, section) in config.overrides.iter_mut().enumerate() {
+            let context = |e: String| format!("{}: [[overrides]] #{}: {e}", path.display(), i + 1);
+            section.base_dir.clone_from(&base_dir);
+            section.globs = section.glob_set().map_err(context)?;
+            Self::default().merged(&[section]).map_err(context)?;
+        }
+        Ok(Some((config, table.clone())))
+    }
+
+    /// Merge configuration layers, outermost first, for one file: each
+    /// layer's top-level settings followed by its `[[overrides]]` sections
+    /// matching `path`, so nearer files win.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the merged settings are invalid.
+    pub fn from_layers(layers: &[Rc<ConfigLayer>], path: &Path) -> Result<Self, String> {
+        let mut table = toml::Table::try_from(Self::default()).map_err(|e| e.to_string())?;
+        for layer in layers {
+            for (_, settings) in layer.tables_for(path) {
+                merge_tables(&mut table, settings);
+            }
+        }
+        table
+            .try_into()
+            .map_err(|e: toml::de::Error| e.message().to_string())
+    }
+
+    /// Effective configuration for a file: this configuration with every
+    /// matching `[[overrides]]` section merged on top, in file order.
+    #[must_use]
+    #[allow(dead_code)] // Reason: Part of public API for library usage
+    pub fn for_path(&self, path: &Path) -> Self {
+        let matching: Vec<&PathOverride> = self
+            .overrides
+            .iter()
+            .filter(|section| section.matches(path))
+            .collect();
+        if matching.is_empty() {
+            return self.clone();
+        }
+        // Sections were validated when the file was loaded
+        self.merged(&matching).unwrap_or_else(|_| self.clone())
+    }
+
+    /// Merge override sections onto this configuration.
+    fn merged(&self, sections: &[&PathOverride]) -> Result<Self, String> {
+        let mut table = toml::Table::try_from(self).map_err(|e| e.to_string())?;
+        table.remove("overrides");
+        for section in sections {
+            if section.settings.contains_key("overrides") {
+                return Err("[[overrides]] sections cannot be nested".to_string());
+            }
+            merge_tables(&mut table, &section.settings);
+        }
+        let mut merged: Self = table
+            .try_into()
+            .map_err(|e: toml::de::Error| e.to_string())?;
+        merged.overrides = Vec::new();
+        Ok(merged)
+    }
+
+    /// Load configuration for the current directory: the user configuration
+    /// merged with every configuration file from the repository root down.
     ///
     /// # Errors
     ///
     /// Returns an error if there is an issue accessing the filesystem or parsing config files.
+    #[allow(dead_code)] // Reason: Part of public API for library usage
     pub fn load_from_cwd() -> Result<Self, Box<dyn std::error::Error>> {
-        let mut current = std::env::current_dir()?;
+        ConfigResolver::discover()?.resolve(&std::env::current_dir()?)
+    }
 
-        loop {
-            let config_path = current.join(".ascfix.toml");
-            if config_path.exists() {
-                return Self::from_file(config_path);
-            }
+    /// Set a single option from a `key=value` pair, as written in
+    /// `<!-- ascfix: key=value -->` block directives.
+    ///
+    /// Supported keys: `style`, `balance`, `padding`, `max_line_length`,
+    /// `preserve_unicode`, `validate` and `threshold`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a message naming the key if it is unknown or its value is invalid.
+    pub fn apply_override(&mut self, key: &str, value: &str) -> Result<(), String> {
+        fn parse<T: std::str::FromStr>(
+            key: &str,
+            value: &str,
+            expected: &str,
+        ) -> Result<T, String> {
+            value
+                .parse()
+                .map_err(|_| format!("invalid value '{value}' for '{key}' (expected {expected})"))
+        }
 
-            if !current.pop() {
-                break;
+        let formatting = &mut self.formatting;
+        match key {
+            "style" => {
+                formatting.box_style = Some(BoxStyle::from_name(value).ok_or_else(|| {
+                    format!("invalid value '{value}' for 'style' (expected single, double or rounded)")
+                })?);
+            }
+            "balance" => formatting.balance_boxes = parse(key, value, "true or false")?,
+            "padding" => formatting.box_padding = parse(key, value, "a number")?,
+            "max_line_length" => formatting.max_line_length = parse(key, value, "a number")?,
+            "preserve_unicode" => formatting.preserve_unicode = parse(key, value, "true or false")?,
+            "validate" => formatting.validate_diagrams = parse(key, value, "true or false")?,
+            "threshold" => {
+                let threshold: f64 = parse(key, value, "a number between 0 and 1")?;
+                if !(0.0..=1.0).contains(&threshold) {
+                    return Err(format!(
+                        "invalid value '{value}' for 'threshold' (expected a number between 0 and 1)"
+                    ));
+                }
+                self.diagram_threshold = threshold;
+            }
+            _ => {
+                return Err(format!(
+                    "unknown option '{key}' (expected style, balance, padding, max_line_length, preserve_unicode, validate or threshold)"
+                ))
             }
         }
-
-        // Return default if no config found
-        Ok(Self::default())
+        Ok(())
     }
 
-    /// Save configuration to a TOML file
+    /// Save configuration to a TOML file, with a comment above every key
     ///
     /// # Errors
     ///
     /// Returns an error if the file cannot be written or if serialization fails.
-    #[allow(dead_code)] // Reserved for future configuration editing features
     pub fn save_to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn std::error::Error>> {
-        let toml = toml::to_string_pretty(self)?;
-        std::fs::write(path, toml)?;
+        std::fs::write(path, self.to_commented_toml()?)?;
         Ok(())
     }
+
+    /// Serialize to TOML with the description of each key as a comment.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if serialization fails.
+    pub fn to_commented_toml(&self) -> Result<String, toml::ser::Error> {
+        let toml = toml::to_string_pretty(self)?;
+        let mut out = String::from("# ascfix configuration (see CONFIG.md for every option)\n\n");
+        let mut section = "";
+        for line in toml.lines() {
+            if let Some(header) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                section = header.trim_matches(['[', ']']);
+            } else if let Some((key, _)) = line.split_once(" = ") {
+                let path = if section.is_empty() {
+                    key.to_string()
+                } else {
+                    format!("{section}.{key}")
+                };
+                if let Some((_, doc)) = KEY_DOCS.iter().find(|(name, _)| *name == path) {
+                    out.push_str("# ");
+                    out.push_str(doc);
+                    out.push('\n');
+                }
+            }
+            out.push_str(line);
+            out.push('\n');
+        }
+        Ok(out)
+    }
+}
+
+/// One-line descriptions written above each key by [`Config::save_to_file`].
+const KEY_DOCS: &[(&str, &str)] = &[
+    (
+        "mode",
+        "Preset of fixers: \"safe\" (tables, lists) or \"diagram\" (boxes, arrows)",
+    ),
+    ("extensions", "File extensions to process"),
+    ("max_size", "Skip files larger than this (B, KB, MB or GB)"),
+    ("respect_gitignore", "Skip files ignored by .gitignore"),
+    ("conservative", "Leave deeply nested diagrams untouched"),
+    ("enable_flowcharts", "Experimental flowchart support"),
+    (
+        "enable_sequence_diagrams",
+        "Experimental sequence diagram support",
+    ),
+    (
+        "strip_ansi",
+        "Remove ANSI escape sequences outside code fences",
+    ),
+    (
+        "strip_ansi_in_fences",
+        "Also remove them inside text/console fences",
+    ),
+    (
+        "diagram_threshold",
+        "Minimum diagram-vs-prose score (0.0-1.0) for a block to be repaired",
+    ),
+    ("fixes.fix_fences", "Repair code fence boundaries"),
+    ("fixes.fix_tables", "Normalize Markdown tables"),
+    ("fixes.fix_lists", "Normalize list indentation and bullets"),
+    (
+        "fixes.fix_diagrams",
+        "Both diagram fixers (boxes and arrows)",
+    ),
+    ("fixes.fix_boxes", "Resize, balance and pad boxes"),
+    ("fixes.fix_arrows", "Align arrows to the boxes they connect"),
+    (
+        "formatting.max_line_length",
+        "Leave diagrams unchanged rather than widen lines past this",
+    ),
+    (
+        "formatting.box_padding",
+        "Minimum spaces between a box border and its text",
+    ),
+    (
+        "formatting.preserve_unicode",
+        "Keep Unicode box drawing (false draws repaired diagrams in ASCII)",
+    ),
+    (
+        "formatting.validate_diagrams",
+        "Run quality checks on every changed diagram",
+    ),
+    (
+        "formatting.box_style",
+        "Redraw boxes as \"single\", \"double\" or \"rounded\"",
+    ),
+    (
+        "formatting.balance_boxes",
+        "Give side-by-side boxes the same width",
+    ),
+    (
+        "quality.enabled",
+        "Leave a repaired diagram unchanged when it fails these checks",
+    ),
+    (
+        "quality.min_score",
+        "Minimum overall quality score (0.0-1.0)",
+    ),
+    (
+        "quality.min_text_preservation",
+        "Minimum share of the original text kept (0.0-1.0)",
+    ),
+    (
+        "quality.min_structure_preservation",
+        "Minimum share of the original structure kept (0.0-1.0)",
+    ),
+    (
+        "quality.max_line_count_delta",
+        "Maximum number of lines a repair may add or remove",
+    ),
+    (
+        "quality.allow_text_corruption",
+        "Accept repairs that put arrows or borders inside text",
+    ),
+    (
+        "quality.allow_data_loss",
+        "Accept repairs that drop content",
+    ),
+    (
+        "protect.front_matter",
+        "Leave YAML/TOML front matter untouched",
+    ),
+    ("protect.math", "Leave $$ math blocks untouched"),
+    ("protect.html", "Leave raw HTML blocks untouched"),
+    (
+        "protect.mdx",
+        "Leave MDX import/export lines and JSX untouched",
+    ),
+    (
+        "protect.pre_as_text_fence",
+        "Repair diagrams inside <pre> blocks like plain text",
+    ),
+];
+
+/// The settings of one configuration file taking part in a merge.
+#[derive(Debug, Clone)]
+pub struct ConfigLayer {
+    /// File the settings were read from
+    pub source: PathBuf,
+    /// Keys and tables set in the file, as written
+    table: toml::Table,
+    /// `[[overrides]]` sections, relative to the file's directory
+    overrides: Vec<PathOverride>,
+}
+
+impl ConfigLayer {
+    /// Read a configuration file. Returns `Ok(None)` for a `pyproject.toml`
+    /// or `Cargo.toml` without an ascfix table.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or its settings are invalid.
+    pub fn load(path: &Path) -> Result<Option<Self>, String> {
+        Ok(Config::read_settings(path)?.map(|(config, mut table)| {
+            table.remove("overrides");
+            Self {
+                source: path.to_path_buf(),
+                table,
+                overrides: config.overrides,
+            }
+        }))
+    }
+
+    /// Settings this layer applies to `path`, in order, each with a label
+    /// for display: the file's own keys, then every matching `[[overrides]]`
+    /// section.
+    #[must_use]
+    pub fn tables_for(&self, path: &Path) -> Vec<(String, &toml::Table)> {
+        let source = self.source.display();
+        let mut tables = vec![(source.to_string(), &self.table)];
+        for (i, section) in self.overrides.iter().enumerate() {
+            if section.matches(path) {
+                tables.push((
+                    format!("{source} [[overrides]] #{}", i + 1),
+                    &section.settings,
+                ));
+            }
+        }
+        tables
+    }
+}
+
+/// Location of the user configuration: `$XDG_CONFIG_HOME/ascfix/config.toml`,
+/// falling back to `~/.config/ascfix/config.toml`.
+#[must_use]
+pub fn user_config_path() -> Option<PathBuf> {
+    std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+        .map(|dir| dir.join("ascfix").join("config.toml"))
+}
+
+/// Directories searched for configuration files for `path`, nearest first:
+/// its own directory and every parent up to the repository root.
+fn search_dirs(path: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let path = std::path::absolute(path)?;
+    let mut dir = if path.is_dir() {
+        path
+    } else {
+        path.parent().map(Path::to_path_buf).unwrap_or(path)
+    };
+    let mut dirs = Vec::new();
+    loop {
+        dirs.push(dir.clone());
+        if dir.join(".git").exists() || !dir.pop() {
+            break;
+        }
+    }
+    Ok(dirs)
+}
+
+/// Every existing configuration file that may apply to `path`, outermost
+/// first, starting with the user configuration. Project files are listed
+/// whether or not they contain ascfix settings.
+///
+/// # Errors
+///
+/// Returns an error if the current directory cannot be determined.
+pub fn candidate_files(path: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for dir in search_dirs(path)? {
+        for name in CONFIG_FILE_NAMES.iter().rev() {
+            let candidate = dir.join(name);
+            if candidate.is_file() {
+                files.push(candidate);
+            }
+        }
+    }
+    files.extend(user_config_path().filter(|path| path.is_file()));
+    files.reverse();
+    Ok(files)
+}
+
+/// Where the configuration of a run comes from.
+#[derive(Debug)]
+enum Sources {
+    /// The user configuration, then the configuration files found from the
+    /// repository root down to each processed file
+    Discover { user: Option<Rc<ConfigLayer>> },
+    /// A single file given with `--config`
+    Explicit(Rc<ConfigLayer>),
+    /// `--no-config`: built-in defaults only
+    Defaults,
+}
+
+/// Finds and merges the configuration that applies to each processed file.
+///
+/// Every directory from the file up to the repository root (the first
+/// directory containing `.git`) contributes at most one file, the first of
+/// [`CONFIG_FILE_NAMES`] holding ascfix settings. Nearer files win.
+#[derive(Debug)]
+pub struct ConfigResolver {
+    sources: Sources,
+    /// Layer found in each directory visited so far
+    found: RefCell<HashMap<PathBuf, Option<Rc<ConfigLayer>>>>,
+}
+
+impl ConfigResolver {
+    fn with_sources(sources: Sources) -> Self {
+        Self {
+            sources,
+            found: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Discover configuration files next to the processed files, on top of
+    /// the user configuration if one exists.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the user configuration is invalid.
+    pub fn discover() -> Result<Self, Box<dyn std::error::Error>> {
+        let user = match user_config_path().filter(|path| path.is_file()) {
+            Some(path) => ConfigLayer::load(&path)?.map(Rc::new),
+            None => None,
+        };
+        Ok(Self::with_sources(Sources::Discover { user }))
+    }
+
+    /// Use a single configuration file for every processed file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read, is invalid or has no
+    /// ascfix settings.
+    pub fn explicit(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let layer = ConfigLayer::load(path)?.ok_or_else(|| {
+            format!(
+                "{}: no [{}] table",
+                path.display(),
+                embedded_table(path).unwrap_or_default().join(".")
+            )
+        })?;
+        Ok(Self::with_sources(Sources::Explicit(Rc::new(layer))))
+    }
+
+    /// Ignore every configuration file.
+    #[must_use]
+    pub fn defaults() -> Self {
+        Self::with_sources(Sources::Defaults)
+    }
+
+    /// Configuration layers that apply to a file or directory, outermost first.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a configuration file on the way is invalid.
+    pub fn layers_for(
+        &self,
+        path: &Path,
+    ) -> Result<Vec<Rc<ConfigLayer>>, Box<dyn std::error::Error>> {
+        let user = match &self.sources {
+            Sources::Discover { user } => user,
+            Sources::Explicit(layer) => return Ok(vec![Rc::clone(layer)]),
+            Sources::Defaults => return Ok(Vec::new()),
+        };
+
+        let mut layers = Vec::new();
+        for dir in search_dirs(path)? {
+            if let Some(layer) = self.layer_in(&dir)? {
+                layers.push(layer);
+            }
+        }
+        layers.extend(user.clone());
+        layers.reverse();
+        Ok(layers)
+    }
+
+    /// Effective configuration for a file or directory.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a configuration file on the way is invalid.
+    pub fn resolve(&self, path: &Path) -> Result<Config, Box<dyn std::error::Error>> {
+        Ok(Config::from_layers(&self.layers_for(path)?, path)?)
+    }
+
+    /// The configuration file of one directory, if any.
+    fn layer_in(&self, dir: &Path) -> Result<Option<Rc<ConfigLayer>>, String> {
+        if let Some(layer) = self.found.borrow().get(dir) {
+            return Ok(layer.clone());
+        }
+        let mut layer = None;
+        for name in CONFIG_FILE_NAMES {
+            let candidate = dir.join(name);
+            if candidate.is_file() {
+                if let Some(found) = ConfigLayer::load(&candidate)? {
+                    layer = Some(Rc::new(found));
+                    break;
+                }
+            }
+        }
+        self.found
+            .borrow_mut()
+            .insert(dir.to_path_buf(), layer.clone());
+        Ok(layer)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::rules::{Rule, Severity};
     use std::io::Write;
     use tempfile::NamedTempFile;
 
@@ -110,7 +961,7 @@ mod tests {
     #[test]
     fn test_load_config_from_file() {
         let mut temp_file = NamedTempFile::new().unwrap();
-        let config_content = r"enable_flowcharts = true
+        let config_content = r#"enable_flowcharts = true
 enable_sequence_diagrams = false
 
 [formatting]
@@ -118,7 +969,15 @@ max_line_length = 100
 box_padding = 2
 preserve_unicode = false
 validate_diagrams = true
-";
+
+[quality]
+max_line_count_delta = 2
+allow_data_loss = true
+
+[rules]
+ASC304 = "error"
+ASC101 = "off"
+"#;
 
         temp_file.write_all(config_content.as_bytes()).unwrap();
         temp_file.flush().unwrap();
@@ -131,5 +990,268 @@ validate_diagrams = true
         assert!(config.formatting.validate_diagrams);
         assert!(config.enable_flowcharts);
         assert!(!config.enable_sequence_diagrams);
+        assert_eq!(config.quality.max_line_count_delta, 2);
+        assert!(config.quality.allow_data_loss);
+        assert!(config.quality.enabled);
+        assert!((config.quality.min_text_preservation - 0.95).abs() < f64::EPSILON);
+        let rules = &config.rules;
+        assert_eq!(rules.severity(Rule::MixedBoxStyles), Severity::Error);
+        assert_eq!(rules.severity(Rule::RaggedTableRow), Severity::Off);
+        assert_eq!(rules.severity(Rule::TextChanged), Severity::Error);
+    }
+
+    #[test]
+    fn test_documented_schema() {
+        let config: Config = toml::from_str(
+            r#"mode = "diagram"
+extensions = [".md", ".mdx", ".txt"]
+max_size = "100MB"
+respect_gitignore = false
+conservative = false
+
+[fixes]
+fix_tables = true
+fix_arrows = false
+fix_boxes = true
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(config.mode, Mode::Diagram);
+        assert_eq!(config.extensions, vec![".md", ".mdx", ".txt"]);
+        assert_eq!(config.max_size, Some(100 * 1024 * 1024));
+        assert!(!config.respect_gitignore);
+        assert!(!config.conservative);
+        assert_eq!(config.fixes.fix_arrows, Some(false));
+        assert_eq!(config.fixes.fix_boxes, Some(true));
+        assert_eq!(config.fixes.fix_lists, None);
+        // Unset tables keep their defaults
+        assert_eq!(config.formatting.box_padding, DEFAULT_BOX_PADDING);
+    }
+
+    #[test]
+    fn test_example_config_parses() {
+        let config: Config = toml::from_str(include_str!("../.ascfix.example.toml")).unwrap();
+        assert!(!config.enable_flowcharts);
+    }
+
+    #[test]
+    fn test_unknown_key_is_an_error() {
+        let mut temp_file = NamedTempFile::new().unwrap();
+        temp_file
+            .write_all(b"mode = \"safe\"\n\n[fixes]\nfix_links = true\n")
+            .unwrap();
+        temp_file.flush().unwrap();
+
+        let err = Config::from_file(temp_file.path()).unwrap_err().to_string();
+        assert!(err.contains("fix_links"), "got: {err}");
+        assert!(err.contains("line 4"), "got: {err}");
+    }
+
+    #[test]
+    fn test_max_size_accepts_bytes_and_round_trips() {
+        let config: Config = toml::from_str("max_size = 2048").unwrap();
+        assert_eq!(config.max_size, Some(2048));
+        assert!(toml::from_str::<Config>("max_size = \"lots\"").is_err());
+
+        let saved = toml::to_string(&Config::default()).unwrap();
+        assert!(saved.contains("max_size = \"100MB\""));
+        let reloaded: Config = toml::from_str(&saved).unwrap();
+        assert_eq!(reloaded.max_size, Some(DEFAULT_MAX_SIZE));
+    }
+
+    #[test]
+    fn test_diagram_threshold_out_of_range_is_an_error() {
+        let config: Config = toml::from_str("diagram_threshold = 1.0").unwrap();
+        assert!((config.diagram_threshold - 1.0).abs() < f64::EPSILON);
+
+        let err = toml::from_str::<Config>("diagram_threshold = 7.5")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("between 0.0 and 1.0"), "got: {err}");
+        assert!(toml::from_str::<Config>("diagram_threshold = -0.1").is_err());
+
+        let mut temp_file = NamedTempFile::new().unwrap();
+        temp_file
+            .write_all(b"[[overrides]]\npaths = [\"docs/**\"]\ndiagram_threshold = 2.0\n")
+            .unwrap();
+        temp_file.flush().unwrap();
+        let err = Config::from_file(temp_file.path()).unwrap_err().to_string();
+        assert!(err.contains("[[overrides]] #1"), "got: {err}");
+    }
+
+    #[test]
+    fn test_path_overrides_merge_per_file() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join(".ascfix.toml");
+        std::fs::write(
+            &path,
+            r#"mode = "diagram"
+
+[formatting]
+box_padding = 1
+
+[[overrides]]
+paths = ["docs/legacy/**"]
+mode = "safe"
+
+[[overrides]]
+paths = ["design/**"]
+formatting = { box_style = "rounded" }
+
+[[overrides]]
+paths = ["CHANGELOG.md"]
+ignore = true
+"#,
+        )
+        .unwrap();
+        let config = Config::from_file(&path).unwrap();
+
+        let legacy = config.for_path(&dir.path().join("docs/legacy/old.md"));
+        assert_eq!(legacy.mode, Mode::Safe);
+
+        let design = config.for_path(&dir.path().join("design/arch.md"));
+        assert_eq!(design.mode, Mode::Diagram);
+        assert_eq!(design.formatting.box_style, Some(BoxStyle::Rounded));
+        // Tables are merged key by key, not replaced
+        assert_eq!(design.formatting.box_padding, 1);
+
+        assert!(config.for_path(&dir.path().join("CHANGELOG.md")).ignore);
+        assert!(
+            !config
+                .for_path(&dir.path().join("docs/CHANGELOG.md"))
+                .ignore
+        );
+
+        let other = config.for_path(&dir.path().join("README.md"));
+        assert_eq!(other.mode, Mode::Diagram);
+        assert!(!other.ignore);
+    }
+
+    #[test]
+    fn test_path_override_errors_name_the_section() {
+        let mut temp_file = NamedTempFile::new().unwrap();
+        temp_file
+            .write_all(b"[[overrides]]\npaths = [\"docs/**\"]\nmodee = \"safe\"\n")
+            .unwrap();
+        temp_file.flush().unwrap();
+
+        let err = Config::from_file(temp_file.path()).unwrap_err().to_string();
+        assert!(err.contains("[[overrides]] #1"), "got: {err}");
+        assert!(err.contains("modee"), "got: {err}");
+
+        std::fs::write(
+            temp_file.path(),
+            "[[overrides]]\npaths = [\"docs/[\"]\nmode = \"safe\"\n",
+        )
+        .unwrap();
+        let err = Config::from_file(temp_file.path()).unwrap_err().to_string();
+        assert!(
+            err.contains("[[overrides]] #1: invalid glob 'docs/['"),
+            "got: {err}"
+        );
+    }
+
+    #[test]
+    fn test_nearest_config_wins_up_to_repo_root() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let root = dir.path().join("repo");
+        let sub = root.join("packages/web");
+        std::fs::create_dir_all(&sub).unwrap();
+        std::fs::create_dir(root.join(".git")).unwrap();
+        // Above the repository root: never read
+        std::fs::write(dir.path().join(".ascfix.toml"), "strip_ansi = true\n").unwrap();
+        std::fs::write(
+            root.join(".ascfix.toml"),
+            "mode = \"diagram\"\n\n[formatting]\nbox_padding = 2\n\n[[overrides]]\npaths = [\"packages/**\"]\ndiagram_threshold = 0.5\n",
+        )
+        .unwrap();
+        std::fs::write(
+            sub.join(".ascfix.toml"),
+            "mode = \"safe\"\n\n[formatting]\nmax_line_length = 80\n",
+        )
+        .unwrap();
+
+        let resolver = ConfigResolver::with_sources(Sources::Discover { user: None });
+        let config = resolver.resolve(&sub.join("README.md")).unwrap();
+        assert_eq!(config.mode, Mode::Safe);
+        assert_eq!(config.formatting.max_line_length, 80);
+        assert_eq!(config.formatting.box_padding, 2);
+        assert!((config.diagram_threshold - 0.5).abs() < f64::EPSILON);
+        assert!(!config.strip_ansi);
+
+        let config = resolver.resolve(&root.join("README.md")).unwrap();
+        assert_eq!(config.mode, Mode::Diagram);
+        assert_eq!(config.formatting.max_line_length, DEFAULT_MAX_LINE_LENGTH);
+
+        let layers = resolver.layers_for(&sub.join("README.md")).unwrap();
+        let sources: Vec<_> = layers.iter().map(|layer| layer.source.clone()).collect();
+        assert_eq!(
+            sources,
+            vec![root.join(".ascfix.toml"), sub.join(".ascfix.toml")]
+        );
+    }
+
+    #[test]
+    fn test_user_config_and_project_files() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let root = dir.path().join("repo");
+        let python = root.join("python");
+        let rust = root.join("rust");
+        std::fs::create_dir_all(root.join(".git")).unwrap();
+        std::fs::create_dir_all(&python).unwrap();
+        std::fs::create_dir_all(&rust).unwrap();
+        std::fs::write(
+            python.join("pyproject.toml"),
+            "[project]\nname = \"x\"\n\n[tool.ascfix]\nmode = \"diagram\"\n",
+        )
+        .unwrap();
+        std::fs::write(
+            rust.join("Cargo.toml"),
+            "[package]\nname = \"x\"\n\n[package.metadata.ascfix.formatting]\nbox_padding = 3\n",
+        )
+        .unwrap();
+        // Without an ascfix table a project file is not a config file
+        std::fs::write(root.join("pyproject.toml"), "[project]\nname = \"y\"\n").unwrap();
+        let user_path = dir.path().join("user.toml");
+        std::fs::write(&user_path, "strip_ansi = true\nmode = \"diagram\"\n").unwrap();
+        let user = ConfigLayer::load(&user_path).unwrap().map(Rc::new);
+
+        let resolver = ConfigResolver::with_sources(Sources::Discover { user });
+        let config = resolver.resolve(&python.join("a.md")).unwrap();
+        assert_eq!(config.mode, Mode::Diagram);
+        assert!(config.strip_ansi);
+        let config = resolver.resolve(&rust.join("a.md")).unwrap();
+        assert_eq!(config.formatting.box_padding, 3);
+        assert_eq!(config.mode, Mode::Diagram);
+        assert_eq!(resolver.layers_for(&root.join("a.md")).unwrap().len(), 1);
+
+        std::fs::write(
+            python.join("pyproject.toml"),
+            "[tool.ascfix]\nmodee = \"diagram\"\n",
+        )
+        .unwrap();
+        let err = ConfigResolver::explicit(&python.join("pyproject.toml"))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("[tool.ascfix]"), "got: {err}");
+        assert!(err.contains("modee"), "got: {err}");
+        assert!(ConfigResolver::explicit(&root.join("pyproject.toml")).is_err());
+    }
+
+    #[test]
+    fn test_apply_override() {
+        let mut config = Config::default();
+        config.apply_override("style", "rounded").unwrap();
+        config.apply_override("balance", "false").unwrap();
+        config.apply_override("padding", "2").unwrap();
+        assert_eq!(config.formatting.box_style, Some(BoxStyle::Rounded));
+        assert!(!config.formatting.balance_boxes);
+        assert_eq!(config.formatting.box_padding, 2);
+
+        assert!(config.apply_override("style", "wavy").is_err());
+        assert!(config.apply_override("threshold", "1.5").is_err());
+        let err = config.apply_override("colour", "red").unwrap_err();
+        assert!(err.contains("colour"));
     }
 }
@@ -0,0 +1,735 @@
+//! `ascfix config` subcommands: create, inspect and check configuration files.
+
+use crate::cli::{Args, ConfigCommand};
+use crate::config::{Config, ConfigLayer};
+use crate::fixes::{Fix, FixRequest};
+use crate::processor::Processor;
+use anyhow::{anyhow, bail, Result};
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
+
+/// Exit code when `config validate` finds a problem.
+pub const INVALID_CONFIG_EXIT_CODE: i32 = 1;
+
+/// Run a `config` subcommand and return the exit code.
+///
+/// # Errors
+///
+/// Returns an error if a file cannot be read or written, or if the
+/// configuration needed by `show` is invalid.
+pub fn run(command: &ConfigCommand, args: &Args) -> Result<i32> {
+    match command {
+        ConfigCommand::Init { path, force } => init(path, *force),
+        ConfigCommand::Show { file } => show(file, args),
+        ConfigCommand::Validate { files } => validate(files, args),
+    }
+}
+
+/// Write the default configuration, with every key commented.
+fn init(path: &Path, force: bool) -> Result<i32> {
+    if path.exists() && !force {
+        bail!(
+            "{} already exists (use --force to overwrite)",
+            path.display()
+        );
+    }
+    Config::default()
+        .save_to_file(path)
+        .map_err(|e| anyhow!("Failed to write {}: {e}", path.display()))?;
+    println!("Wrote {}", path.display());
+    Ok(0)
+}
+
+/// Print the effective configuration for `file` as TOML, with the source of
+/// every value as a trailing comment.
+fn show(file: &Path, args: &Args) -> Result<i32> {
+    let resolver = Processor::config_resolver(args)?;
+    let layers = resolver
+        .layers_for(file)
+        .map_err(|e| anyhow!("Failed to load config: {e}"))?;
+    let merged = Config::from_layers(&layers, file).map_err(|e| anyhow!("{e}"))?;
+    let effective = Processor::apply_args(merged.clone(), args);
+
+    let mut sources: HashMap<String, String> = HashMap::new();
+    for layer in &layers {
+        for (label, table) in layer.tables_for(file) {
+            let label = relative_label(&label);
+            for (key, _) in leaves(table) {
+                sources.insert(key, label.clone());
+            }
+        }
+    }
+    let file_values = file_values(merged)?;
+    let source =
+        |key: &str, value: &toml::Value| source_of(key, value, args, &file_values, &sources);
+
+    let mut out = format!("# Effective configuration for {}\n", file.display());
+    let table = to_table(&effective)?;
+    let mut sections: Vec<(&str, &toml::Table)> = Vec::new();
+    let mut top: Vec<(String, &toml::Value)> = Vec::new();
+    for (key, value) in &table {
+        match value {
+            toml::Value::Table(sub) => sections.push((key, sub)),
+            _ => top.push((key.clone(), value)),
+        }
+    }
+    write_section(&mut out, None, &top, &source);
+    let mode = table
+        .get("mode")
+        .and_then(toml::Value::as_str)
+        .unwrap_or_default();
+    write_lines(
+        &mut out,
+        Some("fixes"),
+        resolved_fixes(&effective, args, &sources, mode),
+    );
+    for (name, sub) in sections {
+        if name == "fixes" {
+            continue;
+        }
+        let entries: Vec<(String, &toml::Value)> = sub
+            .iter()
+            .map(|(key, value)| (key.clone(), value))
+            .collect();
+        write_section(&mut out, Some(name), &entries, &source);
+    }
+
+    // A reader such as `head` may close the pipe early; that is not an error
+    match std::io::stdout().write_all(out.as_bytes()) {
+        Err(e) if e.kind() != std::io::ErrorKind::BrokenPipe => Err(e.into()),
+        _ => Ok(0),
+    }
+}
+
+/// Values as the configuration files leave them, normalized the way the
+/// command line normalizes them, so a rewrite such as `.MD` to `.md` is not
+/// mistaken for a command-line value.
+fn file_values(mut merged: Config) -> Result<HashMap<String, toml::Value>> {
+    merged.extensions = crate::cli::normalize_exts(&merged.extensions);
+    Ok(leaves(&to_table(&merged)?).collect())
+}
+
+/// Where an effective value comes from: the flag that changed it, the file
+/// setting it, or the default.
+fn source_of(
+    key: &str,
+    value: &toml::Value,
+    args: &Args,
+    file_values: &HashMap<String, toml::Value>,
+    sources: &HashMap<String, String>,
+) -> String {
+    match flag_for(key, args) {
+        Some(flag) if file_values.get(key) != Some(value) => flag.to_string(),
+        _ => sources
+            .get(key)
+            .cloned()
+            .unwrap_or_else(|| "default".to_string()),
+    }
+}
+
+/// The flag the user passed that sets `key`, if any.
+fn flag_for(key: &str, args: &Args) -> Option<&'static str> {
+    match key {
+        "mode" => args.mode.is_some().then_some("--mode"),
+        "extensions" => args.ext.is_some().then_some("--ext"),
+        "max_size" => args.max_size.is_some().then_some("--max-size"),
+        "respect_gitignore" => args.no_gitignore.then_some("--no-gitignore"),
+        "strip_ansi" => args.strip_ansi.then_some("--strip-ansi"),
+        "strip_ansi_in_fences" => args.strip_ansi_fences.then_some("--strip-ansi-fences"),
+        "diagram_threshold" => args
+            .diagram_threshold
+            .is_some()
+            .then_some("--diagram-threshold"),
+        _ => None,
+    }
+}
+
+/// Write `key = value` lines with aligned `# source` comments.
+fn write_section(
+    out: &mut String,
+    name: Option<&str>,
+    entries: &[(String, &toml::Value)],
+    source_of: &impl Fn(&str, &toml::Value) -> String,
+) {
+    let lines: Vec<(String, String)> = entries
+        .iter()
+        .map(|(key, value)| {
+            let path = name.map_or_else(|| key.clone(), |name| format!("{name}.{key}"));
+            (format!("{key} = {value}"), source_of(&path, value))
+        })
+        .collect();
+    write_lines(out, name, lines);
+}
+
+/// Write `(line, source)` pairs under an optional section header.
+fn write_lines(out: &mut String, name: Option<&str>, lines: Vec<(String, String)>) {
+    if let Some(name) = name {
+        let _ = write!(out, "\n[{name}]\n");
+    }
+    let width = lines.iter().map(|(line, _)| line.len()).max().unwrap_or(0);
+    for (line, source) in lines {
+        let _ = writeln!(out, "{line:width$}  # {source}");
+    }
+}
+
+/// The on/off state of every fixer as `fix_<name> = bool` lines, each with
+/// what decided it: a command-line flag, the file setting its `[fixes]` key,
+/// or the `mode` preset.
+fn resolved_fixes(
+    effective: &Config,
+    args: &Args,
+    sources: &HashMap<String, String>,
+    mode: &str,
+) -> Vec<(String, String)> {
+    let selected = effective.selected_fixes();
+    Fix::ALL
+        .iter()
+        .map(|&fix| {
+            let key = format!("fix_{}", fix.name());
+            let from_file = || {
+                sources.get(&format!("fixes.{key}")).or_else(|| {
+                    matches!(fix, Fix::Boxes | Fix::Arrows)
+                        .then(|| sources.get("fixes.fix_diagrams"))
+                        .flatten()
+                })
+            };
+            let from_flag = match args.fix_request() {
+                Some(FixRequest::Exactly(_)) => true,
+                Some(request) => request.selection().contains(fix),
+                None => false,
+            };
+            let source = if args.skip.is_some_and(|skip| skip.contains(fix)) {
+                "--skip".to_string()
+            } else if from_flag {
+                "--fix".to_string()
+            } else if let Some(label) = from_file() {
+                label.clone()
+            } else {
+                format!("{mode} mode preset")
+            };
+            (format!("{key} = {}", selected.contains(fix)), source)
+        })
+        .collect()
+}
+
+/// Check configuration files and print every problem found.
+fn validate(files: &[PathBuf], args: &Args) -> Result<i32> {
+    let files = if !files.is_empty() {
+        files.to_vec()
+    } else if let Some(path) = &args.config {
+        vec![path.clone()]
+    } else if args.no_config {
+        Vec::new()
+    } else {
+        crate::config::candidate_files(&std::env::current_dir()?)?
+    };
+    if files.is_empty() {
+        println!("No configuration files found");
+        return Ok(0);
+    }
+
+    let mut exit_code = 0;
+    for file in &files {
+        let Some(problems) = check_file(file) else {
+            continue;
+        };
+        if problems.is_empty() {
+            println!("{}: ok", file.display());
+        }
+        for problem in problems {
+            exit_code = INVALID_CONFIG_EXIT_CODE;
+            match problem.line {
+                Some(line) => println!("{}:{line}: {}", file.display(), problem.message),
+                None => println!("{}: {}", file.display(), problem.message),
+            }
+        }
+    }
+    Ok(exit_code)
+}
+
+/// A problem found by `config validate`.
+#[derive(Debug, PartialEq, Eq)]
+struct Problem {
+    line: Option<usize>,
+    message: String,
+}
+
+/// Problems in one configuration file, or `None` for a `pyproject.toml` or
+/// `Cargo.toml` without ascfix settings.
+///
+/// Every unknown key is reported with its line, followed by the first
+/// invalid value among the remaining keys.
+fn check_file(path: &Path) -> Option<Vec<Problem>> {
+    let text = match std::fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) => {
+            return Some(vec![Problem {
+                line: None,
+                message: format!("cannot read: {e}"),
+            }])
+        }
+    };
+    let document: toml::Table = match toml::from_str(&text) {
+        Ok(document) => document,
+        Err(e) => {
+            return Some(vec![Problem {
+                line: e.span().map(|span| line_of(&text, span.start)),
+                message: e.message().to_string(),
+            }])
+        }
+    };
+    let prefix: &[&str] = match path.file_name().and_then(|name| name.to_str()) {
+        Some("pyproject.toml") => &["tool", "ascfix"],
+        Some("Cargo.toml") => &["package", "metadata", "ascfix"],
+        _ => &[],
+    };
+    let table = prefix
+        .iter()
+        .try_fold(&document, |table, key| table.get(*key)?.as_table())?;
+
+    let schema_config = Config {
+        fixes: crate::config::FixesConfig {
+            fix_diagrams: Some(true),
+            ..crate::config::FixesConfig::exactly(crate::fixes::FixSelection::ALL)
+        },
+        rules: crate::rules::RulesConfig::defaults(),
+        ..Config::default()
+    };
+    let schema = to_table(&schema_config).ok()?;
+    let mut unknown = Vec::new();
+    unknown_keys(table, &schema, &prefix.join("."), &mut unknown);
+    let lines = key_lines(&text);
+    let mut problems: Vec<Problem> = unknown
+        .iter()
+        .map(|(path, display)| Problem {
+            line: find_line(&lines, path),
+            message: format!("unknown key `{display}`"),
+        })
+        .collect();
+
+    // Check the values of the known keys as well, so one run reports everything
+    let mut known = document.clone();
+    for (path, _) in &unknown {
+        remove_key(&mut known, path);
+    }
+    let result = if unknown.is_empty() {
+        // The text itself gives errors with their line
+        ConfigLayer::load(path).map(drop)
+    } else {
+        Config::from_document(path, &known).map(drop)
+    };
+    if let Err(message) = result {
+        // The loader names the file itself
+        let message = message
+            .strip_prefix(&format!("{}: ", path.display()))
+            .unwrap_or(&message);
+        let (message, line) = match message.split_once("\nin `") {
+            Some((message, key)) => {
+                let key = key.trim_end().trim_end_matches('`');
+                let key = prefix.iter().copied().chain([key]).collect::<Vec<_>>();
+                (message, find_line(&lines, &key.join(".")))
+            }
+            None => (message, None),
+        };
+        problems.push(Problem {
+            line,
+            message: message.trim_end().to_string(),
+        });
+    }
+    Some(problems)
+}
+
+/// Remove the value at a dotted path (`overrides.0.mode`) from a table.
+fn remove_key(table: &mut toml::Table, path: &str) {
+    let Some((head, rest)) = path.split_once('.') else {
+        table.remove(path);
+        return;
+    };
+    match table.get_mut(head) {
+        Some(toml::Value::Table(sub)) => remove_key(sub, rest),
+        Some(toml::Value::Array(items)) => {
+            let Some((index, rest)) = rest.split_once('.') else {
+                return;
+            };
+            let item = index.parse().ok().and_then(|i: usize| items.get_mut(i));
+            if let Some(toml::Value::Table(sub)) = item {
+                remove_key(sub, rest);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Collect keys of `table` missing from `schema` as (dotted path in the
+/// file, name for display). `[[overrides]]` sections accept every top-level
+/// key plus `paths`.
+fn unknown_keys(
+    table: &toml::Table,
+    schema: &toml::Table,
+    path: &str,
+    unknown: &mut Vec<(String, String)>,
+) {
+    let join = |key: &str| {
+        if path.is_empty() {
+            key.to_string()
+        } else {
+            format!("{path}.{key}")
+        }
+    };
+    for (key, value) in table {
+        match (key.as_str(), schema.get(key), value) {
+            ("overrides", _, toml::Value::Array(sections)) if !path.contains("overrides") => {
+                let mut section_schema = schema.clone();
+                section_schema.insert("paths".to_string(), toml::Value::Array(Vec::new()));
+                section_schema.remove("overrides");
+                for (i, section) in sections.iter().enumerate() {
+                    if let toml::Value::Table(section) = section {
+                        let mut found = Vec::new();
+                        unknown_keys(
+                            section,
+                            &section_schema,
+                            &join(&format!("overrides.{i}")),
+                            &mut found,
+                        );
+                        unknown.extend(found.into_iter().map(|(path, display)| {
+                            (path, format!("[[overrides]] #{}: {display}", i + 1))
+                        }));
+                    }
+                }
+            }
+            (_, Some(toml::Value::Table(sub_schema)), toml::Value::Table(sub)) => {
+                let mut found = Vec::new();
+                unknown_keys(sub, sub_schema, &join(key), &mut found);
+                unknown.extend(
+                    found
+                        .into_iter()
+                        .map(|(path, display)| (path, format!("{key}.{display}"))),
+                );
+            }
+            (_, Some(_), _) => {}
+            _ if OPTIONAL_KEYS.contains(&key.as_str()) => {}
+            _ => unknown.push((join(key), key.clone())),
+        }
+    }
+}
+
+/// Keys that are valid but absent from a serialized default configuration.
+const OPTIONAL_KEYS: [&str; 3] = ["box_style", "ignore", "overrides"];
+
+/// Line of the first definition of each dotted key, as written in the file.
+/// Entries of `[[array]]` tables are numbered (`overrides.0.mode`).
+fn key_lines(text: &str) -> HashMap<String, usize> {
+    let mut lines = HashMap::new();
+    let mut section = String::new();
+    let mut array_counts: HashMap<String, usize> = HashMap::new();
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if let Some(name) = line.strip_prefix("[[") {
+            let name = name
+                .split("]]")
+                .next()
+                .unwrap_or_default()
+                .trim()
+                .to_string();
+            let count = array_counts.entry(name.clone()).or_insert(0);
+            section = format!("{name}.{count}");
+            *count += 1;
+            lines.entry(section.clone()).or_insert(i + 1);
+        } else if let Some(name) = line.strip_prefix('[') {
+            section = name
+                .split(']')
+                .next()
+                .unwrap_or_default()
+                .trim()
+                .to_string();
+            lines.entry(section.clone()).or_insert(i + 1);
+        } else if let Some((key, _)) = line.split_once('=') {
+            if line.starts_with('#') {
+                continue;
+            }
+            let key: String = key
+                .trim()
+                .split('.')
+                .map(|part| part.trim().trim_matches('"'))
+                .collect::<Vec<_>>()
+                .join(".");
+            let path = if section.is_empty() {
+                key
+            } else {
+                format!("{section}.{key}")
+            };
+            lines.entry(path).or_insert(i + 1);
+        }
+    }
+    lines
+}
+
+/// Line of a dotted key, falling back to its nearest parent (for keys inside
+/// inline tables).
+fn find_line(lines: &HashMap<String, usize>, path: &str) -> Option<usize> {
+    let mut path = path;
+    loop {
+        if let Some(line) = lines.get(path) {
+            return Some(*line);
+        }
+        path = &path[..path.rfind('.')?];
+    }
+}
+
+/// 1-based line containing byte `offset`.
+fn line_of(text: &str, offset: usize) -> usize {
+    text[..offset.min(text.len())].matches('\n').count() + 1
+}
+
+fn to_table(config: &Config) -> Result<toml::Table> {
+    toml::Table::try_from(config).map_err(|e| anyhow!("{e}"))
+}
+
+/// Every non-table value in `table`, keyed by dotted path.
+fn leaves(table: &toml::Table) -> impl Iterator<Item = (String, toml::Value)> {
+    let mut out = Vec::new();
+    let mut stack: Vec<(String, &toml::Table)> = vec![(String::new(), table)];
+    while let Some((prefix, table)) = stack.pop() {
+        for (key, value) in table {
+            let path = if prefix.is_empty() {
+                key.clone()
+            } else {
+                format!("{prefix}.{key}")
+            };
+            match value {
+                toml::Value::Table(sub) => stack.push((path, sub)),
+                _ => out.push((path, value.clone())),
+            }
+        }
+    }
+    out.into_iter()
+}
+
+/// Show sources below the current directory as relative paths.
+fn relative_label(label: &str) -> String {
+    std::env::current_dir()
+        .ok()
+        .and_then(|cwd| {
+            let cwd = format!("{}{}", cwd.display(), std::path::MAIN_SEPARATOR);
+            label.strip_prefix(&cwd).map(ToString::to_string)
+        })
+        .unwrap_or_else(|| label.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[test]
+    fn test_unknown_keys_reported_with_lines() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join(".ascfix.toml");
+        std::fs::write(
+            &path,
+            "mode = \"diagram\"\ncolour = true\n\n[fixes]\nfix_links = true\n\n[[overrides]]\npaths = [\"docs/**\"]\nmodee = \"safe\"\n",
+        )
+        .unwrap();
+
+        let mut problems = check_file(&path).unwrap();
+        problems.sort_by_key(|problem| problem.line);
+        assert_eq!(
+            problems,
+            vec![
+                Problem {
+                    line: Some(2),
+                    message: "unknown key `colour`".to_string()
+                },
+                Problem {
+                    line: Some(5),
+                    message: "unknown key `fixes.fix_links`".to_string()
+                },
+                Problem {
+                    line: Some(9),
+                    message: "unknown key `[[overrides]] #1: modee`".to_string()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unknown_keys_and_invalid_values_reported_together() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join(".ascfix.toml");
+        std::fs::write(
+            &path,
+            "[fixes]\nbogus = 1\n\n[quality]\nmin_score = \"x\"\n",
+        )
+        .unwrap();
+
+        let problems = check_file(&path).unwrap();
+        assert_eq!(problems.len(), 2, "{problems:?}");
+        assert_eq!(
+            problems[0],
+            Problem {
+                line: Some(2),
+                message: "unknown key `fixes.bogus`".to_string()
+            }
+        );
+        assert_eq!(problems[1].line, Some(5), "{problems:?}");
+        assert!(problems[1].message.contains("invalid type"), "{problems:?}");
+
+        let path = dir.path().join("pyproject.toml");
+        std::fs::write(
+            &path,
+            "[tool.ascfix]\ncolour = true\n\n[tool.ascfix.quality]\nmin_score = \"x\"\n",
+        )
+        .unwrap();
+        let lines: Vec<_> = check_file(&path)
+            .unwrap()
+            .iter()
+            .map(|problem| problem.line)
+            .collect();
+        assert_eq!(lines, vec![Some(2), Some(5)]);
+    }
+
+    #[test]
+    fn test_schema_and_syntax_errors() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join(".ascfix.toml");
+
+        std::fs::write(&path, "mode = \"fast\"\n").unwrap();
+        let problems = check_file(&path).unwrap();
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].message.contains("fast"), "{problems:?}");
+
+        std::fs::write(&path, "mode = \n").unwrap();
+        let problems = check_file(&path).unwrap();
+        assert_eq!(problems[0].line, Some(1));
+
+        std::fs::write(&path, "[formatting]\nbox_style = \"rounded\"\n").unwrap();
+        assert_eq!(check_file(&path).unwrap(), Vec::new());
+    }
+
+    #[test]
+    fn test_project_files_checked_in_their_table() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("pyproject.toml");
+        std::fs::write(&path, "[project]\nname = \"x\"\n").unwrap();
+        assert_eq!(check_file(&path), None);
+
+        std::fs::write(&path, "[tool.ascfix]\nmode = \"safe\"\nfences = true\n").unwrap();
+        assert_eq!(
+            check_file(&path).unwrap(),
+            vec![Problem {
+                line: Some(3),
+                message: "unknown key `fences`".to_string()
+            }]
+        );
+    }
+
+    #[test]
+    fn test_show_resolves_every_fixer() {
+        let config: Config =
+            toml::from_str("mode = \"diagram\"\n[fixes]\nfix_diagrams = false\n").unwrap();
+        let sources =
+            HashMap::from([("fixes.fix_diagrams".to_string(), ".ascfix.toml".to_string())]);
+        let resolve = |argv: &[&str]| {
+            let args = Args::try_parse_from(argv).unwrap();
+            let effective = Processor::apply_args(config.clone(), &args);
+            resolved_fixes(&effective, &args, &sources, "diagram")
+        };
+        let line = |line: &str, source: &str| (line.to_string(), source.to_string());
+
+        assert_eq!(
+            resolve(&["ascfix", "a.md"]),
+            vec![
+                line("fix_fences = false", "diagram mode preset"),
+                line("fix_tables = false", "diagram mode preset"),
+                line("fix_lists = false", "diagram mode preset"),
+                line("fix_boxes = false", ".ascfix.toml"),
+                line("fix_arrows = false", ".ascfix.toml"),
+            ]
+        );
+        assert_eq!(
+            resolve(&[
+                "ascfix",
+                "--fix",
+                "tables,boxes",
+                "--skip",
+                "boxes",
+                "--fences",
+                "a.md"
+            ]),
+            vec![
+                // `--fences` is an alias for `--fix +fences`
+                line("fix_fences = true", "--fix"),
+                line("fix_tables = true", "--fix"),
+                line("fix_lists = false", "--fix"),
+                line("fix_boxes = false", "--skip"),
+                line("fix_arrows = false", "--fix"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_show_attributes_values_to_passed_flags() {
+        let merged: Config = toml::from_str("extensions = [\".MD\"]\nstrip_ansi = true\n").unwrap();
+        let file_values = file_values(merged.clone()).unwrap();
+        let sources = HashMap::from([
+            ("extensions".to_string(), ".ascfix.toml".to_string()),
+            ("strip_ansi".to_string(), ".ascfix.toml".to_string()),
+        ]);
+        let sources_for = |argv: &[&str]| {
+            let args = Args::try_parse_from(argv).unwrap();
+            let effective = to_table(&Processor::apply_args(merged.clone(), &args)).unwrap();
+            ["extensions", "strip_ansi", "mode", "diagram_threshold"]
+                .map(|key| source_of(key, &effective[key], &args, &file_values, &sources))
+        };
+
+        assert_eq!(
+            sources_for(&["ascfix", "a.md"]),
+            [".ascfix.toml", ".ascfix.toml", "default", "default"]
+        );
+        assert_eq!(
+            sources_for(&[
+                "ascfix",
+                "--strip-ansi",
+                "--ext",
+                "txt",
+                "--mode",
+                "diagram",
+                "a.md"
+            ]),
+            ["--ext", ".ascfix.toml", "--mode", "default"]
+        );
+        assert_eq!(
+            sources_for(&[
+                "ascfix",
+                "--ext",
+                ".md",
+                "--diagram-threshold",
+                "0.9",
+                "a.md"
+            ]),
+            [
+                ".ascfix.toml",
+                ".ascfix.toml",
+                "default",
+                "--diagram-threshold"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_init_writes_commented_defaults() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join(".ascfix.toml");
+        assert_eq!(init(&path, false).unwrap(), 0);
+
+        let text = std::fs::read_to_string(&path).unwrap();
+        assert!(
+            text.contains("# Minimum spaces between a box border and its text\nbox_padding = 0")
+        );
+        assert_eq!(check_file(&path).unwrap(), Vec::new());
+        assert!(init(&path, false).is_err());
+        assert_eq!(init(&path, true).unwrap(), 0);
+    }
+}
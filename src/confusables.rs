@@ -0,0 +1,255 @@
+//! Canonicalization of look-alike and invisible characters in diagrams.
+//!
+//! Pasted or generated diagrams often use characters that look like box-drawing
+//! glyphs but are not (em dashes as horizontal lines, fullwidth bars as walls),
+//! or contain invisible spacing (non-breaking and zero-width spaces). These break
+//! column alignment and defeat box detection, so diagram blocks are mapped to
+//! their canonical equivalents before detection runs.
+
+/// A single character replaced during canonicalization.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Substitution {
+    /// Line index (relative to the lines passed in, or to the file once rebased)
+    pub line: usize,
+    /// Column (character index) within the line
+    pub col: usize,
+    /// The character found in the input
+    pub original: char,
+    /// The canonical character written in its place
+    pub replacement: char,
+}
+
+impl Substitution {
+    /// Return a copy of this substitution with its line shifted by `offset`
+    /// and its column shifted by `col_offset` (e.g. a stripped container prefix).
+    #[must_use]
+    pub const fn rebased(self, offset: usize, col_offset: usize) -> Self {
+        Self {
+            line: self.line + offset,
+            col: self.col + col_offset,
+            ..self
+        }
+    }
+}
+
+/// Check if a character is a dash that is commonly pasted in place of `─`.
+const fn is_dash_confusable(ch: char) -> bool {
+    matches!(
+        ch,
+        '\u{2012}' // figure dash
+            | '\u{2013}' // en dash
+            | '\u{2014}' // em dash
+            | '\u{2015}' // horizontal bar
+            | '\u{2212}' // minus sign
+            | '\u{FF0D}' // fullwidth hyphen-minus
+    )
+}
+
+/// Check if a character is a bar that is commonly pasted in place of `│`.
+const fn is_bar_confusable(ch: char) -> bool {
+    matches!(
+        ch,
+        '\u{00A6}' // broken bar
+            | '\u{2223}' // divides
+            | '\u{FF5C}' // fullwidth vertical line
+    )
+}
+
+/// Check if a character is a look-alike that diagram repair may canonicalize.
+#[must_use]
+pub const fn is_confusable(ch: char) -> bool {
+    is_dash_confusable(ch) || is_bar_confusable(ch) || is_space_confusable(ch)
+}
+
+/// Check if a character is invisible or non-standard spacing.
+const fn is_space_confusable(ch: char) -> bool {
+    matches!(
+        ch,
+        '\u{00A0}' // no-break space
+            | '\u{2007}' // figure space
+            | '\u{200B}' // zero-width space
+            | '\u{202F}' // narrow no-break space
+            | '\u{2060}' // word joiner
+            | '\u{FEFF}' // zero-width no-break space
+    )
+}
+
+/// Characters that continue a horizontal line on the left or right.
+const fn joins_horizontally(ch: char) -> bool {
+    matches!(
+        ch,
+        '─' | '═'
+            | '┌'
+            | '┐'
+            | '└'
+            | '┘'
+            | '├'
+            | '┤'
+            | '┬'
+            | '┴'
+            | '┼'
+            | '╔'
+            | '╗'
+            | '╚'
+            | '╝'
+            | '╭'
+            | '╮'
+            | '╰'
+            | '╯'
+            | '→'
+            | '←'
+    ) || is_dash_confusable(ch)
+}
+
+/// Characters that continue a vertical line above or below.
+const fn joins_vertically(ch: char) -> bool {
+    matches!(
+        ch,
+        '│' | '║'
+            | '┃'
+            | '┌'
+            | '┐'
+            | '└'
+            | '┘'
+            | '├'
+            | '┤'
+            | '┬'
+            | '┴'
+            | '┼'
+            | '╔'
+            | '╗'
+            | '╚'
+            | '╝'
+            | '╭'
+            | '╮'
+            | '╰'
+            | '╯'
+            | '↓'
+            | '↑'
+    ) || is_bar_confusable(ch)
+}
+
+/// Map a look-alike character to its canonical form, using its neighbours as context.
+///
+/// Dashes and bars are only replaced when they continue a line drawn with
+/// box characters, so that prose such as "A — B" inside a box keeps its dash.
+/// Invisible spacing is always replaced with a plain space.
+fn canonical_char(rows: &[Vec<char>], row: usize, col: usize) -> Option<char> {
+    let ch = rows[row][col];
+
+    if is_space_confusable(ch) {
+        return Some(' ');
+    }
+
+    if is_dash_confusable(ch) {
+        let left = col.checked_sub(1).and_then(|c| rows[row].get(c)).copied();
+        let right = rows[row].get(col + 1).copied();
+        if left.is_some_and(joins_horizontally) || right.is_some_and(joins_horizontally) {
+            return Some('─');
+        }
+        return None;
+    }
+
+    if is_bar_confusable(ch) {
+        let above = row
+            .checked_sub(1)
+            .and_then(|r| rows.get(r))
+            .and_then(|r| r.get(col))
+            .copied();
+        let below = rows.get(row + 1).and_then(|r| r.get(col)).copied();
+        if above.is_some_and(joins_vertically) || below.is_some_and(joins_vertically) {
+            return Some('│');
+        }
+        return None;
+    }
+
+    None
+}
+
+/// Canonicalize look-alike characters in the lines of a diagram block.
+///
+/// Returns the rewritten lines and one [`Substitution`] per replaced character,
+/// with line indices relative to `lines`. Lines without confusables are
+/// returned unchanged.
+#[must_use]
+pub fn canonicalize_lines(lines: &[String]) -> (Vec<String>, Vec<Substitution>) {
+    let rows: Vec<Vec<char>> = lines.iter().map(|line| line.chars().collect()).collect();
+    let mut substitutions = Vec::new();
+    let mut output = rows.clone();
+
+    for (row, chars) in rows.iter().enumerate() {
+        for col in 0..chars.len() {
+            if let Some(replacement) = canonical_char(&rows, row, col) {
+                substitutions.push(Substitution {
+                    line: row,
+                    col,
+                    original: chars[col],
+                    replacement,
+                });
+                output[row][col] = replacement;
+            }
+        }
+    }
+
+    let lines = output
+        .into_iter()
+        .map(|chars| chars.into_iter().collect())
+        .collect();
+    (lines, substitutions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(text: &str) -> Vec<String> {
+        text.lines().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_em_dash_border_canonicalized() {
+        let (out, subs) = canonicalize_lines(&lines("┌——┐\n│ab│\n└──┘"));
+        assert_eq!(out[0], "┌──┐");
+        assert_eq!(subs.len(), 2);
+        assert_eq!(subs[0].line, 0);
+        assert_eq!(subs[0].col, 1);
+        assert_eq!(subs[0].original, '—');
+        assert_eq!(subs[0].replacement, '─');
+    }
+
+    #[test]
+    fn test_fullwidth_bar_wall_canonicalized() {
+        let (out, subs) = canonicalize_lines(&lines("┌──┐\n｜ab¦\n└──┘"));
+        assert_eq!(out[1], "│ab│");
+        assert_eq!(subs.len(), 2);
+    }
+
+    #[test]
+    fn test_invisible_spaces_replaced() {
+        let (out, subs) = canonicalize_lines(&lines("│a\u{00A0}b\u{200B}│"));
+        assert_eq!(out[0], "│a b │");
+        assert_eq!(subs.len(), 2);
+        assert_eq!(subs[1].col, 4);
+    }
+
+    #[test]
+    fn test_prose_dash_inside_box_preserved() {
+        let input = lines("┌─────────┐\n│ A — B   │\n└─────────┘");
+        let (out, subs) = canonicalize_lines(&input);
+        assert_eq!(out, input);
+        assert!(subs.is_empty());
+    }
+
+    #[test]
+    fn test_rebased_substitution() {
+        let sub = Substitution {
+            line: 2,
+            col: 3,
+            original: '–',
+            replacement: '─',
+        };
+        assert_eq!(sub.rebased(10, 0).line, 12);
+        assert_eq!(sub.rebased(10, 0).col, 3);
+        assert_eq!(sub.rebased(10, 2).col, 5);
+    }
+}
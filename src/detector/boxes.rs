@@ -1,7 +1,7 @@
 //! Box detection functionality.
 
 use crate::grid::Grid;
-use crate::primitives::{Box, BoxStyle};
+use crate::primitives::{continues_down, continues_up, Box, BoxStyle};
 use std::collections::{HashSet, VecDeque};
 
 /// Box character set for detection.
@@ -40,6 +40,11 @@ const fn is_any_box_corner(ch: char) -> bool {
     )
 }
 
+/// Check if a character is a vertical wall without horizontal joins.
+const fn is_plain_wall(ch: char) -> bool {
+    matches!(ch, '│' | '║' | '┃')
+}
+
 /// Detects rectangular boxes in ASCII diagrams.
 pub struct BoxDetector<'a> {
     grid: &'a Grid,
@@ -82,6 +87,42 @@ impl<'a> BoxDetector<'a> {
         boxes
     }
 
+    /// Whether every cell of `row` is blank.
+    fn is_blank_row(&self, row: usize) -> bool {
+        (0..self.grid.width()).all(|col| self.grid.get(row, col).is_none_or(char::is_whitespace))
+    }
+
+    /// Cells that continue the vertical line through `(row, col)` on the other
+    /// side of a run of blank rows, above and below.
+    fn across_blank_rows(&self, row: usize, col: usize, ch: char) -> Vec<(usize, usize)> {
+        let mut cells = Vec::new();
+        if continues_down(ch) {
+            let below = (row + 1..self.grid.height()).find(|&r| !self.is_blank_row(r));
+            if let Some(below) = below.filter(|&below| below > row + 1) {
+                if self
+                    .grid
+                    .get(below, col)
+                    .is_some_and(|c| is_box_char(c) && continues_up(c))
+                {
+                    cells.push((below, col));
+                }
+            }
+        }
+        if continues_up(ch) {
+            let above = (0..row).rev().find(|&r| !self.is_blank_row(r));
+            if let Some(above) = above.filter(|&above| above + 1 < row) {
+                if self
+                    .grid
+                    .get(above, col)
+                    .is_some_and(|c| is_box_char(c) && continues_down(c))
+                {
+                    cells.push((above, col));
+                }
+            }
+        }
+        cells
+    }
+
     /// Try to extract a rectangular box starting at (row, col).
     fn extract_box_at(&mut self, start_row: usize, start_col: usize) -> Option<Box> {
         let mut boundary = HashSet::new();
@@ -99,13 +140,17 @@ impl<'a> BoxDetector<'a> {
                     self.visited.insert((row, col));
                     boundary.insert((row, col));
 
-                    // Check 4-neighbors
-                    let neighbors = [
-                        (row.saturating_sub(1), col),
-                        (row.saturating_add(1), col),
-                        (row, col.saturating_sub(1)),
-                        (row, col.saturating_add(1)),
-                    ];
+                    // Check 4-neighbors; a plain wall has no horizontal
+                    // neighbors, so a line leaving its side is not part of the box
+                    let mut neighbors =
+                        vec![(row.saturating_sub(1), col), (row.saturating_add(1), col)];
+                    if !is_plain_wall(ch) {
+                        neighbors.extend(
+                            [(row, col.saturating_sub(1)), (row, col.saturating_add(1))]
+                                .into_iter()
+                                .filter(|&(r, c)| !self.grid.get(r, c).is_some_and(is_plain_wall)),
+                        );
+                    }
                     for (nr, nc) in neighbors {
                         if nr < self.grid.height()
                             && nc < self.grid.width()
@@ -114,6 +159,8 @@ impl<'a> BoxDetector<'a> {
                             queue.push_back((nr, nc));
                         }
                     }
+                    // A wall continues across a blank interior row
+                    queue.extend(self.across_blank_rows(row, col, ch));
                 }
             }
         }
@@ -29,40 +29,37 @@ pub fn detect_all_primitives(grid: &crate::grid::Grid) -> crate::primitives::Pri
     };
     let labels = detect_labels(grid, &temp_inventory);
 
-    // Extract text rows from inside boxes
+    // Extract text rows from inside boxes. Each row belongs to its innermost
+    // enclosing box: regions covered by nested boxes are cut out of the parent
+    // interior, leaving the parent only the text segments around its children.
     let mut text_rows = Vec::new();
-
-    // Check if we have nested boxes (complex case)
-    let has_nested_boxes = boxes.len() > 1
-        && boxes.iter().any(|b1| {
-            boxes.iter().any(|b2| {
-                b1 != b2
-                    && b1.top_left.0 < b2.top_left.0
-                    && b1.bottom_right.0 > b2.bottom_right.0
-                    && b1.top_left.1 < b2.top_left.1
-                    && b1.bottom_right.1 > b2.bottom_right.1
-            })
-        });
-
-    if has_nested_boxes {
-        // Conservative approach for nested boxes: skip text extraction entirely
-        // to avoid corrupting content. Complex nested layouts need manual handling.
-        // This preserves the original content structure.
-    } else {
-        // Simple case: no nested boxes, use original logic
-        for b in &boxes {
-            for (line_idx, line) in extract_box_content(grid, b).iter().enumerate() {
-                if !line.trim().is_empty() {
-                    let interior_row = b.top_left.0 + 1 + line_idx;
-                    // Clean the content by removing trailing border characters
-                    let clean_content = line.trim_end_matches(|c| ['║', '│', '┃'].contains(&c));
-                    text_rows.push(crate::primitives::TextRow {
-                        row: interior_row,
-                        start_col: b.top_left.1 + 1,
-                        end_col: b.bottom_right.1 - 1,
-                        content: clean_content.to_string(),
-                    });
+    for b in &boxes {
+        let nested: Vec<&crate::primitives::Box> = boxes
+            .iter()
+            .filter(|other| contains_box(b, other))
+            .collect();
+
+        for (line_idx, line) in extract_box_content(grid, b).iter().enumerate() {
+            let interior_row = b.top_left.0 + 1 + line_idx;
+            let chars: Vec<char> = line.chars().collect();
+
+            for (seg_start, seg_end) in interior_segments(b, &nested, interior_row) {
+                let segment: String = chars
+                    .iter()
+                    .skip(seg_start - (b.top_left.1 + 1))
+                    .take(seg_end + 1 - seg_start)
+                    .collect();
+                if segment.trim().is_empty() {
+                    continue;
                 }
+                // Clean the content by removing trailing border characters
+                let clean_content = segment.trim_end_matches(|c| ['║', '│', '┃'].contains(&c));
+                text_rows.push(crate::primitives::TextRow {
+                    row: interior_row,
+                    start_col: seg_start,
+                    end_col: seg_end,
+                    content: clean_content.to_string(),
+                });
             }
         }
     }
@@ -80,31 +77,63 @@ pub fn detect_all_primitives(grid: &crate::grid::Grid) -> crate::primitives::Pri
     }
 }
 
+/// Check if `outer` strictly contains `inner` (borders included).
+const fn contains_box(outer: &crate::primitives::Box, inner: &crate::primitives::Box) -> bool {
+    inner.top_left.0 > outer.top_left.0
+        && inner.bottom_right.0 < outer.bottom_right.0
+        && inner.top_left.1 > outer.top_left.1
+        && inner.bottom_right.1 < outer.bottom_right.1
+}
+
+/// Split a box's interior row into the column ranges not covered by nested boxes.
+///
+/// Returns inclusive `(start_col, end_col)` ranges. Without nested boxes on the
+/// row this is the whole interior.
+fn interior_segments(
+    b: &crate::primitives::Box,
+    nested: &[&crate::primitives::Box],
+    row: usize,
+) -> Vec<(usize, usize)> {
+    let mut covered: Vec<(usize, usize)> = nested
+        .iter()
+        .filter(|child| row >= child.top_left.0 && row <= child.bottom_right.0)
+        .map(|child| (child.top_left.1, child.bottom_right.1))
+        .collect();
+    covered.sort_unstable();
+
+    let mut segments = Vec::new();
+    let mut col = b.top_left.1 + 1;
+    for (left, right) in covered {
+        if left > col {
+            segments.push((col, left - 1));
+        }
+        col = col.max(right + 1);
+    }
+    if col < b.bottom_right.1 {
+        segments.push((col, b.bottom_right.1 - 1));
+    }
+    segments
+}
+
 /// Establish parent-child relationships between boxes based on containment.
+///
+/// A box's parent is the smallest box containing it, so `parent_idx` and
+/// `child_indices` describe direct nesting only.
 fn establish_parent_child_relationships(
     mut boxes: Vec<crate::primitives::Box>,
 ) -> Vec<crate::primitives::Box> {
-    // For each pair of boxes, check containment
-    for i in 0..boxes.len() {
-        for j in 0..boxes.len() {
-            if i != j {
-                let parent = &boxes[i];
-                let child = &boxes[j];
-
-                // Check if parent completely contains child (with some margin for text)
-                let contains = child.top_left.0 > parent.top_left.0  // Child starts after parent top
-                    && child.bottom_right.0 < parent.bottom_right.0  // Child ends before parent bottom
-                    && child.top_left.1 > parent.top_left.1  // Child starts after parent left
-                    && child.bottom_right.1 < parent.bottom_right.1; // Child ends before parent right
-
-                if contains {
-                    // Mark child as having this parent
-                    boxes[j].parent_idx = Some(i);
-                    // Add child to parent's children list (if not already there)
-                    if !boxes[i].child_indices.contains(&j) {
-                        boxes[i].child_indices.push(j);
-                    }
-                }
+    for j in 0..boxes.len() {
+        // Find the innermost box completely containing this one
+        let parent = (0..boxes.len())
+            .filter(|&i| i != j && contains_box(&boxes[i], &boxes[j]))
+            .min_by_key(|&i| boxes[i].width() * boxes[i].height());
+
+        if let Some(i) = parent {
+            // Mark child as having this parent
+            boxes[j].parent_idx = Some(i);
+            // Add child to parent's children list (if not already there)
+            if !boxes[i].child_indices.contains(&j) {
+                boxes[i].child_indices.push(j);
             }
         }
     }
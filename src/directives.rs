@@ -0,0 +1,250 @@
+//! Inline `<!-- ascfix:... -->` directives that control processing.
+//!
+//! Supported directives:
+//! - `<!-- ascfix:ignore -->` / `<!-- /ascfix:ignore -->`: leave a region untouched
+//! - `<!-- ascfix:ignore-next -->`: leave the next block untouched
+//! - `<!-- ascfix:disable tables,lists -->` / `<!-- ascfix:enable -->`: switch
+//!   fixers off (any name `--fix` accepts; all of them when none are listed)
+//!   until re-enabled
+//! - `<!-- ascfix:off -->`: leave the whole file untouched
+//! - `<!-- ascfix: style=rounded balance=false -->`: override configuration
+//!   values for the next block only
+
+use crate::fixes::FixSelection;
+use std::fmt;
+
+/// Configuration overrides attached to a single block.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct BlockOverrides {
+    entries: Vec<(String, String)>,
+}
+
+impl BlockOverrides {
+    /// The `(key, value)` pairs in the order they were written.
+    #[must_use]
+    #[allow(dead_code)] // Reason: Public API for library usage
+    pub fn entries(&self) -> &[(String, String)] {
+        &self.entries
+    }
+
+    /// Return a copy of `config` with these overrides applied.
+    #[must_use]
+    pub fn apply(&self, config: &crate::config::Config) -> crate::config::Config {
+        let mut config = config.clone();
+        for (key, value) in &self.entries {
+            // Entries were validated when the directive was parsed
+            let _ = config.apply_override(key, value);
+        }
+        config
+    }
+}
+
+impl fmt::Display for BlockOverrides {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, (key, value)) in self.entries.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" ")?;
+            }
+            write!(f, "{key}={value}")?;
+        }
+        Ok(())
+    }
+}
+
+/// A parsed ascfix directive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Directive {
+    /// Start of an ignore region
+    IgnoreStart,
+    /// End of an ignore region
+    IgnoreEnd,
+    /// Ignore the next block
+    IgnoreNext,
+    /// Switch off the given fixers
+    Disable(FixSelection),
+    /// Switch the given fixers back on
+    Enable(FixSelection),
+    /// Leave the whole file untouched
+    Off,
+    /// Override configuration values for the next block
+    Overrides(BlockOverrides),
+}
+
+/// A problem found while reading a directive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirectiveWarning {
+    /// Line of the directive (0-indexed)
+    pub line: usize,
+    /// Description of the problem
+    pub message: String,
+}
+
+/// Return the body of an ascfix comment (`ascfix:ignore-next`, `/ascfix:ignore`...).
+fn directive_body(line: &str) -> Option<&str> {
+    let inner = line
+        .trim()
+        .strip_prefix("<!--")?
+        .strip_suffix("-->")?
+        .trim();
+    (inner.starts_with("ascfix") || inner.starts_with("/ascfix")).then_some(inner)
+}
+
+/// Check whether a line is an ascfix directive comment.
+#[must_use]
+pub fn is_directive(line: &str) -> bool {
+    directive_body(line).is_some()
+}
+
+/// Parse a list of fixer names, as accepted by `--fix`; an empty list means every fixer.
+fn parse_fixers(args: &str) -> Result<FixSelection, String> {
+    let selection = FixSelection::parse(&args.replace(' ', ","))?;
+    Ok(if selection == FixSelection::NONE {
+        FixSelection::ALL
+    } else {
+        selection
+    })
+}
+
+/// Parse whitespace-separated `key=value` pairs, checking each against the config schema.
+fn parse_overrides(args: &str) -> Result<BlockOverrides, String> {
+    let mut probe = crate::config::Config::default();
+    let mut entries = Vec::new();
+    for pair in args.split_whitespace() {
+        let (key, value) = pair
+            .split_once('=')
+            .ok_or_else(|| format!("expected key=value, found '{pair}'"))?;
+        probe.apply_override(key, value)?;
+        entries.push((key.to_string(), value.to_string()));
+    }
+    Ok(BlockOverrides { entries })
+}
+
+/// Parse a directive line.
+///
+/// Returns `None` if the line is not an ascfix comment, and an error message
+/// for ascfix comments that use an unknown directive or fixer.
+///
+/// # Examples
+///
+/// ```
+/// use ascfix::directives::{parse_directive, Directive};
+///
+/// assert_eq!(parse_directive("<!-- ascfix:ignore-next -->"), Some(Ok(Directive::IgnoreNext)));
+/// assert!(parse_directive("<!-- ascfix:ignroe -->").unwrap().is_err());
+/// assert_eq!(parse_directive("plain text"), None);
+/// ```
+#[must_use]
+pub fn parse_directive(line: &str) -> Option<Result<Directive, String>> {
+    let body = directive_body(line)?;
+
+    // Legacy spellings of the region markers
+    match body {
+        "ascfix-ignore-start" => return Some(Ok(Directive::IgnoreStart)),
+        "ascfix-ignore-end" | "/ascfix:ignore" => return Some(Ok(Directive::IgnoreEnd)),
+        _ => {}
+    }
+
+    let Some(rest) = body.strip_prefix("ascfix:") else {
+        return Some(Err(format!("unknown directive '{body}'")));
+    };
+    let rest = rest.trim();
+    if rest
+        .split_whitespace()
+        .next()
+        .is_some_and(|word| word.contains('='))
+    {
+        return Some(parse_overrides(rest).map(Directive::Overrides));
+    }
+    let (name, args) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+
+    Some(match name {
+        "ignore" => Ok(Directive::IgnoreStart),
+        "ignore-next" => Ok(Directive::IgnoreNext),
+        "off" => Ok(Directive::Off),
+        "disable" => parse_fixers(args).map(Directive::Disable),
+        "enable" => parse_fixers(args).map(Directive::Enable),
+        _ => Err(format!(
+            "unknown directive '{name}' (expected ignore, ignore-next, disable, enable or off)"
+        )),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_region_markers() {
+        for line in ["<!-- ascfix:ignore -->", "<!-- ascfix-ignore-start -->"] {
+            assert_eq!(parse_directive(line), Some(Ok(Directive::IgnoreStart)));
+        }
+        for line in ["<!-- /ascfix:ignore -->", "<!-- ascfix-ignore-end -->"] {
+            assert_eq!(parse_directive(line), Some(Ok(Directive::IgnoreEnd)));
+        }
+    }
+
+    #[test]
+    fn test_parse_disable_fixers() {
+        use crate::fixes::Fix;
+
+        let Some(Ok(Directive::Disable(set))) =
+            parse_directive("<!-- ascfix:disable tables,lists -->")
+        else {
+            panic!("expected a disable directive");
+        };
+        assert!(set.contains(Fix::Tables));
+        assert!(set.contains(Fix::Lists));
+        assert!(!set.has_diagrams());
+
+        // The names `--fix` accepts, separated by commas or spaces
+        assert_eq!(
+            parse_directive("<!-- ascfix:disable fences boxes -->"),
+            Some(Ok(Directive::Disable(
+                FixSelection::NONE
+                    .with(Fix::Fences, true)
+                    .with(Fix::Boxes, true)
+            )))
+        );
+        assert_eq!(
+            parse_directive("<!-- ascfix:disable diagrams -->"),
+            Some(Ok(Directive::Disable(FixSelection::DIAGRAMS)))
+        );
+        assert_eq!(
+            parse_directive("<!-- ascfix:enable -->"),
+            Some(Ok(Directive::Enable(FixSelection::ALL)))
+        );
+    }
+
+    #[test]
+    fn test_unknown_names_are_errors() {
+        assert!(parse_directive("<!-- ascfix:skip -->").unwrap().is_err());
+        assert!(parse_directive("<!-- ascfix:disable charts -->")
+            .unwrap()
+            .is_err());
+        assert_eq!(parse_directive("<!-- regular comment -->"), None);
+    }
+
+    #[test]
+    fn test_parse_block_overrides() {
+        let Some(Ok(Directive::Overrides(overrides))) =
+            parse_directive("<!-- ascfix: style=rounded balance=false padding=2 -->")
+        else {
+            panic!("expected an overrides directive");
+        };
+        assert_eq!(
+            overrides.to_string(),
+            "style=rounded balance=false padding=2"
+        );
+
+        let config = overrides.apply(&crate::config::Config::default());
+        assert!(!config.formatting.balance_boxes);
+        assert_eq!(config.formatting.box_padding, 2);
+
+        assert!(parse_directive("<!-- ascfix: style=wavy -->")
+            .unwrap()
+            .is_err());
+        assert!(parse_directive("<!-- ascfix: colour=red -->")
+            .unwrap()
+            .is_err());
+    }
+}
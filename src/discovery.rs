@@ -45,20 +45,40 @@ pub fn parse_extensions(s: &str) -> Result<Vec<String>> {
 
 /// File discovery configuration and operations.
 pub struct FileDiscovery {
-    extensions: Vec<String>,
+    /// Extensions to match, or `None` for every file
+    extensions: Option<Vec<String>>,
     respect_gitignore: bool,
 }
 
 impl FileDiscovery {
     /// Create a new file discovery with given extensions and gitignore setting.
     #[must_use]
+    #[allow(dead_code)] // Reason: Part of public API for library usage
     pub const fn new(extensions: Vec<String>, respect_gitignore: bool) -> Self {
         Self {
-            extensions,
+            extensions: Some(extensions),
             respect_gitignore,
         }
     }
 
+    /// Create a file discovery that finds files of every extension, for
+    /// callers that filter them with per-file settings.
+    #[must_use]
+    pub const fn all_files(respect_gitignore: bool) -> Self {
+        Self {
+            extensions: None,
+            respect_gitignore,
+        }
+    }
+
+    /// Check if a file's extension is one of `extensions` (with leading dots).
+    #[must_use]
+    pub fn has_extension(path: &Path, extensions: &[String]) -> bool {
+        path.extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| extensions.iter().any(|e| e.strip_prefix('.') == Some(ext)))
+    }
+
     /// Discover files matching the configured criteria.
     ///
     /// For each path in `paths`:
@@ -93,16 +113,9 @@ impl FileDiscovery {
 
     /// Check if a file's extension matches configured extensions.
     fn matches_extension(&self, path: &Path) -> bool {
-        path.extension()
-            .and_then(|ext| ext.to_str())
-            .is_some_and(|ext| {
-                let ext_with_dot = if ext.starts_with('.') {
-                    ext.to_string()
-                } else {
-                    format!(".{ext}")
-                };
-                self.extensions.contains(&ext_with_dot)
-            })
+        self.extensions
+            .as_ref()
+            .is_none_or(|extensions| Self::has_extension(path, extensions))
     }
 
     /// Recursively walk a directory and collect matching files.
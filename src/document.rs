@@ -0,0 +1,501 @@
+//! Block-level document model shared by all processing passes.
+//!
+//! A file is parsed once: the parser gives every line a [`LineContext`], and
+//! consecutive lines are grouped into typed blocks (fences, HTML, tables,
+//! lists, paragraphs, diagram candidates...). List, table, link and diagram
+//! passes all consume the same model, so a line is either code for every pass
+//! or for none of them.
+
+use crate::changes::Fixer;
+use crate::config::ProtectConfig;
+use crate::directives::{BlockOverrides, Directive, DirectiveWarning};
+use crate::fixes::FixSelection;
+use crate::parser::LineContext;
+
+/// Kind of a block-level element.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockKind {
+    /// Fenced code block, including its fence markers
+    Fence,
+    /// Indented code block
+    IndentedCode,
+    /// Region between `ascfix:ignore` markers, including the markers
+    Ignored,
+    /// YAML or TOML front matter
+    FrontMatter,
+    /// `$$` display math
+    Math,
+    /// Raw HTML block (including the interior of `<pre>`)
+    Html,
+    /// MDX `import`/`export` statements or JSX element
+    Mdx,
+    /// Markdown table (header, separator and rows)
+    Table,
+    /// Run of lines starting with a list item
+    List,
+    /// Run of prose lines
+    Paragraph,
+    /// Run of lines containing drawing glyphs
+    DiagramCandidate,
+    /// Blank lines between blocks
+    Blank,
+}
+
+impl BlockKind {
+    /// Whether the block holds literal content that no pass may rewrite.
+    #[must_use]
+    pub const fn is_protected(self) -> bool {
+        !matches!(
+            self,
+            Self::Table | Self::List | Self::Paragraph | Self::DiagramCandidate | Self::Blank
+        )
+    }
+
+    /// Block kind for a protected line context, or `None` for normal lines.
+    const fn from_context(context: LineContext) -> Option<Self> {
+        match context {
+            LineContext::Normal => None,
+            LineContext::InCodeFence => Some(Self::Fence),
+            LineContext::InIndentedCode => Some(Self::IndentedCode),
+            LineContext::InIgnoreBlock | LineContext::Directive => Some(Self::Ignored),
+            LineContext::InFrontMatter => Some(Self::FrontMatter),
+            LineContext::InMathBlock => Some(Self::Math),
+            LineContext::InHtmlBlock | LineContext::InPreBlock => Some(Self::Html),
+            LineContext::InMdx => Some(Self::Mdx),
+        }
+    }
+}
+
+/// A block-level element spanning a range of lines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Block {
+    /// Kind of the block
+    pub kind: BlockKind,
+    /// First line of the block (0-indexed)
+    pub start_line: usize,
+    /// Last line of the block (0-indexed, inclusive)
+    pub end_line: usize,
+}
+
+/// A Markdown document parsed into line contexts and blocks.
+#[derive(Debug, Clone)]
+pub struct Document<'a> {
+    text: &'a str,
+    lines: Vec<&'a str>,
+    contexts: Vec<LineContext>,
+    blocks: Vec<Block>,
+    protect: ProtectConfig,
+    /// Fixers switched off by `ascfix:disable` at each line
+    disabled: Vec<FixSelection>,
+    /// Whether an `ascfix:off` directive switched off the whole file
+    off: bool,
+    warnings: Vec<DirectiveWarning>,
+    /// `ascfix: key=value` overrides and the block each one applies to
+    overrides: Vec<(Block, BlockOverrides)>,
+}
+
+impl<'a> Document<'a> {
+    /// Parse a document, recognizing the protected block kinds enabled in `protect`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ascfix::config::ProtectConfig;
+    /// use ascfix::document::{BlockKind, Document};
+    ///
+    /// let doc = Document::parse("```\ncode\n```\n\n| a |\n|---|", ProtectConfig::default());
+    /// let kinds: Vec<BlockKind> = doc.blocks().iter().map(|b| b.kind).collect();
+    /// assert_eq!(kinds, vec![BlockKind::Fence, BlockKind::Blank, BlockKind::Table]);
+    /// ```
+    #[must_use]
+    pub fn parse(text: &'a str, protect: ProtectConfig) -> Self {
+        let lines: Vec<&str> = text.lines().collect();
+        let mut contexts = crate::parser::line_contexts(text, protect);
+        let mut blocks = group_blocks(&lines, &contexts);
+
+        let mut disabled = Vec::with_capacity(lines.len());
+        let mut current = FixSelection::NONE;
+        let mut off = false;
+        let mut warnings = Vec::new();
+        let mut ignore_next = Vec::new();
+        let mut pending_overrides = Vec::new();
+
+        for (i, line) in lines.iter().enumerate() {
+            if contexts[i] == LineContext::Directive {
+                match crate::directives::parse_directive(line) {
+                    Some(Ok(Directive::Disable(set))) => current = current.union(set),
+                    Some(Ok(Directive::Enable(set))) => current = current.difference(set),
+                    Some(Ok(Directive::IgnoreNext)) => ignore_next.push(i),
+                    Some(Ok(Directive::Off)) => off = true,
+                    Some(Ok(Directive::Overrides(overrides))) => {
+                        pending_overrides.push((i, overrides));
+                    }
+                    Some(Ok(Directive::IgnoreStart | Directive::IgnoreEnd)) | None => {}
+                    Some(Err(message)) => warnings.push(DirectiveWarning { line: i, message }),
+                }
+            }
+            disabled.push(current);
+        }
+
+        // `ignore-next` hides the first non-blank block after the directive
+        if !ignore_next.is_empty() {
+            for directive_line in ignore_next {
+                if let Some(block) = next_block(&blocks, &contexts, directive_line) {
+                    for context in &mut contexts[block.start_line..=block.end_line] {
+                        *context = LineContext::InIgnoreBlock;
+                    }
+                }
+            }
+            blocks = group_blocks(&lines, &contexts);
+        }
+
+        // Overrides attach to the next block, like `ignore-next`
+        let overrides = pending_overrides
+            .into_iter()
+            .filter_map(|(directive_line, overrides)| {
+                next_block(&blocks, &contexts, directive_line).map(|block| (*block, overrides))
+            })
+            .collect();
+
+        Self {
+            text,
+            lines,
+            contexts,
+            blocks,
+            protect,
+            disabled,
+            off,
+            warnings,
+            overrides,
+        }
+    }
+
+    /// Whether the file is switched off with `<!-- ascfix:off -->`.
+    #[must_use]
+    pub const fn is_off(&self) -> bool {
+        self.off
+    }
+
+    /// Problems found in ascfix directives (unknown names or categories).
+    #[must_use]
+    pub fn warnings(&self) -> &[DirectiveWarning] {
+        &self.warnings
+    }
+
+    /// Overrides attached to the first block overlapping the given line range.
+    #[must_use]
+    pub fn overrides_for(&self, start_line: usize, end_line: usize) -> Option<&BlockOverrides> {
+        self.overrides
+            .iter()
+            .find(|(block, _)| block.start_line <= end_line && block.end_line >= start_line)
+            .map(|(_, overrides)| overrides)
+    }
+
+    /// Fixers switched off at a line by `ascfix:disable` (all of them under `ascfix:off`).
+    #[must_use]
+    pub fn disabled_fixes(&self, line: usize) -> FixSelection {
+        if self.off {
+            return FixSelection::ALL;
+        }
+        self.disabled.get(line).copied().unwrap_or_default()
+    }
+
+    /// Check whether every part of a fixer is switched off at a line by `ascfix:disable`.
+    #[must_use]
+    pub fn is_disabled(&self, line: usize, fixer: Fixer) -> bool {
+        let parts = fixer.fixes();
+        self.disabled_fixes(line).intersect(parts) == parts
+    }
+
+    /// The source text of the document.
+    #[must_use]
+    pub const fn text(&self) -> &'a str {
+        self.text
+    }
+
+    /// The lines of the document.
+    #[must_use]
+    pub fn lines(&self) -> &[&'a str] {
+        &self.lines
+    }
+
+    /// The context of every line.
+    #[must_use]
+    #[allow(dead_code)] // Reason: Public API for library usage
+    pub fn contexts(&self) -> &[LineContext] {
+        &self.contexts
+    }
+
+    /// The blocks of the document, in order and covering every line.
+    #[must_use]
+    pub fn blocks(&self) -> &[Block] {
+        &self.blocks
+    }
+
+    /// Line ranges (inclusive) of all protected blocks.
+    #[must_use]
+    pub fn protected_line_ranges(&self) -> Vec<(usize, usize)> {
+        self.blocks
+            .iter()
+            .filter(|block| block.kind.is_protected())
+            .map(|block| (block.start_line, block.end_line))
+            .collect()
+    }
+
+    /// Byte ranges (end exclusive) of all protected blocks in the source text.
+    #[must_use]
+    pub fn protected_byte_ranges(&self) -> Vec<(usize, usize)> {
+        self.protected_line_ranges()
+            .into_iter()
+            .map(|(start, end)| {
+                let end_offset = self.line_offset(end) + self.lines[end].len();
+                (self.line_offset(start), end_offset)
+            })
+            .collect()
+    }
+
+    /// Lines eligible for diagram repair, with their line numbers.
+    ///
+    /// These are the normal lines, plus the interior of `<pre>` blocks when
+    /// `pre_as_text_fence` is enabled, minus lines where diagrams are disabled.
+    #[must_use]
+    pub fn diagram_lines(&self) -> Vec<(usize, String)> {
+        self.lines
+            .iter()
+            .zip(&self.contexts)
+            .enumerate()
+            .filter(|(i, (_, context))| {
+                let eligible = match context {
+                    LineContext::Normal => true,
+                    LineContext::InPreBlock => self.protect.pre_as_text_fence,
+                    _ => false,
+                };
+                eligible && !self.is_disabled(*i, Fixer::Diagram)
+            })
+            .map(|(i, (line, _))| (i, (*line).to_string()))
+            .collect()
+    }
+
+    /// Byte offset of the start of a line in the source text.
+    fn line_offset(&self, line: usize) -> usize {
+        self.lines[line].as_ptr() as usize - self.text.as_ptr() as usize
+    }
+}
+
+/// First non-blank block after a directive line, skipping further directives.
+fn next_block<'b>(
+    blocks: &'b [Block],
+    contexts: &[LineContext],
+    directive_line: usize,
+) -> Option<&'b Block> {
+    blocks.iter().find(|block| {
+        block.start_line > directive_line
+            && block.kind != BlockKind::Blank
+            && contexts[block.start_line] != LineContext::Directive
+    })
+}
+
+/// Group lines into blocks: runs of the same protected context, runs of blank
+/// lines, and runs of normal lines split into tables and other content.
+fn group_blocks(lines: &[&str], contexts: &[LineContext]) -> Vec<Block> {
+    let mut blocks = Vec::new();
+    let mut i = 0;
+
+    while i < lines.len() {
+        let protected = BlockKind::from_context(contexts[i]);
+        let blank = protected.is_none() && lines[i].trim().is_empty();
+        let mut end = i;
+        while end + 1 < lines.len() {
+            let next_protected = BlockKind::from_context(contexts[end + 1]);
+            let next_blank = next_protected.is_none() && lines[end + 1].trim().is_empty();
+            if next_protected != protected || next_blank != blank {
+                break;
+            }
+            end += 1;
+        }
+
+        match protected {
+            Some(kind) => blocks.push(Block {
+                kind,
+                start_line: i,
+                end_line: end,
+            }),
+            None if blank => blocks.push(Block {
+                kind: BlockKind::Blank,
+                start_line: i,
+                end_line: end,
+            }),
+            None => split_content_run(lines, i, end, &mut blocks),
+        }
+        i = end + 1;
+    }
+
+    blocks
+}
+
+/// Split a run of non-blank normal lines into tables and the content between them.
+fn split_content_run(lines: &[&str], start: usize, end: usize, blocks: &mut Vec<Block>) {
+    use crate::tables::{is_table_row, is_table_separator};
+
+    let table_starts_at =
+        |i: usize| i < end && is_table_row(lines[i]) && is_table_separator(lines[i + 1]);
+
+    let mut i = start;
+    while i <= end {
+        if table_starts_at(i) {
+            let mut table_end = i + 1;
+            while table_end < end && is_table_row(lines[table_end + 1]) {
+                table_end += 1;
+            }
+            blocks.push(Block {
+                kind: BlockKind::Table,
+                start_line: i,
+                end_line: table_end,
+            });
+            i = table_end + 1;
+            continue;
+        }
+
+        let mut segment_end = i;
+        while segment_end < end && !table_starts_at(segment_end + 1) {
+            segment_end += 1;
+        }
+        let segment = &lines[i..=segment_end];
+        let kind = if crate::parser::list_content_column(segment[0]).is_some() {
+            BlockKind::List
+        } else if segment
+            .iter()
+            .any(|line| line.chars().any(crate::classifier::is_diagram_glyph))
+        {
+            BlockKind::DiagramCandidate
+        } else {
+            BlockKind::Paragraph
+        };
+        blocks.push(Block {
+            kind,
+            start_line: i,
+            end_line: segment_end,
+        });
+        i = segment_end + 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(text: &str) -> Vec<(BlockKind, usize, usize)> {
+        Document::parse(text, ProtectConfig::default())
+            .blocks()
+            .iter()
+            .map(|b| (b.kind, b.start_line, b.end_line))
+            .collect()
+    }
+
+    #[test]
+    fn test_blocks_cover_document() {
+        let text = "# Title\n\n- a\n- b\n\n```\n| x |\n```\n\n┌─┐\n└─┘\n\n    code";
+        assert_eq!(
+            kinds(text),
+            vec![
+                (BlockKind::Paragraph, 0, 0),
+                (BlockKind::Blank, 1, 1),
+                (BlockKind::List, 2, 3),
+                (BlockKind::Blank, 4, 4),
+                (BlockKind::Fence, 5, 7),
+                (BlockKind::Blank, 8, 8),
+                (BlockKind::DiagramCandidate, 9, 10),
+                (BlockKind::Blank, 11, 11),
+                (BlockKind::IndentedCode, 12, 12),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_table_split_from_surrounding_text() {
+        let text = "Intro\n| a | b |\n|---|---|\n| 1 | 2 |\nAfter";
+        assert_eq!(
+            kinds(text),
+            vec![
+                (BlockKind::Paragraph, 0, 0),
+                (BlockKind::Table, 1, 3),
+                (BlockKind::Paragraph, 4, 4),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_protected_byte_ranges() {
+        let text = "a\n```\nx\n```\nb";
+        let doc = Document::parse(text, ProtectConfig::default());
+        let ranges = doc.protected_byte_ranges();
+        assert_eq!(ranges, vec![(2, 11)]);
+        assert_eq!(&text[2..11], "```\nx\n```");
+    }
+
+    #[test]
+    fn test_pre_interior_kept_as_text_fence() {
+        let text = "<pre>\n┌─┐\n└─┘\n</pre>";
+        let doc = Document::parse(text, ProtectConfig::default());
+        assert!(doc.diagram_lines().is_empty());
+
+        let protect = ProtectConfig {
+            pre_as_text_fence: true,
+            ..ProtectConfig::default()
+        };
+        let doc = Document::parse(text, protect);
+        let lines: Vec<usize> = doc.diagram_lines().iter().map(|(n, _)| *n).collect();
+        assert_eq!(lines, vec![1, 2]);
+    }
+
+    #[test]
+    fn test_ignore_next_hides_following_block() {
+        let text = "<!-- ascfix:ignore-next -->\n\n┌─┐\n└─┘\n\n┌─┐\n└─┘";
+        let doc = Document::parse(text, ProtectConfig::default());
+        let lines: Vec<usize> = doc.diagram_lines().iter().map(|(n, _)| *n).collect();
+        assert_eq!(lines, vec![1, 4, 5, 6]);
+    }
+
+    #[test]
+    fn test_overrides_attach_to_next_block() {
+        let text = "┌─┐\n└─┘\n\n<!-- ascfix: style=rounded -->\n\n┌─┐\n└─┘";
+        let doc = Document::parse(text, ProtectConfig::default());
+        assert!(doc.overrides_for(0, 1).is_none());
+        let overrides = doc
+            .overrides_for(5, 6)
+            .expect("overrides on the second block");
+        assert_eq!(overrides.to_string(), "style=rounded");
+    }
+
+    #[test]
+    fn test_disable_and_enable_ranges() {
+        let text = "a\n<!-- ascfix:disable tables -->\nb\n<!-- ascfix:enable tables -->\nc";
+        let doc = Document::parse(text, ProtectConfig::default());
+        assert!(!doc.is_disabled(0, Fixer::Table));
+        assert!(doc.is_disabled(2, Fixer::Table));
+        assert!(!doc.is_disabled(2, Fixer::List));
+        assert!(!doc.is_disabled(4, Fixer::Table));
+    }
+
+    #[test]
+    fn test_off_and_unknown_directives() {
+        let doc = Document::parse("text\n<!-- ascfix:off -->", ProtectConfig::default());
+        assert!(doc.is_off());
+        assert!(doc.is_disabled(0, Fixer::Diagram));
+
+        let doc = Document::parse("text\n<!-- ascfix:ignroe -->", ProtectConfig::default());
+        assert!(!doc.is_off());
+        assert_eq!(doc.warnings().len(), 1);
+        assert_eq!(doc.warnings()[0].line, 1);
+    }
+
+    #[test]
+    fn test_directives_in_fences_are_examples() {
+        let doc = Document::parse(
+            "```\n<!-- ascfix:off -->\n<!-- ascfix:nope -->\n```",
+            ProtectConfig::default(),
+        );
+        assert!(!doc.is_off());
+        assert!(doc.warnings().is_empty());
+    }
+}
@@ -4,6 +4,9 @@
 //! It handles both backtick and tilde fences, detects issues like
 //! mismatched lengths and unclosed blocks, and repairs them conservatively.
 
+use crate::changes::Fixer;
+use crate::document::Document;
+use crate::parser::LineContext;
 use std::collections::VecDeque;
 
 /// Type of fence marker (backtick or tilde).
@@ -58,68 +61,59 @@ pub enum FenceIssue {
     OrphanedClosing { marker: FenceMarker },
 }
 
-/// Detect all fence markers in content.
+impl FenceIssue {
+    /// Rule reporting this issue.
+    #[must_use]
+    pub const fn rule(&self) -> crate::rules::Rule {
+        match self {
+            Self::Unclosed { .. } => crate::rules::Rule::UnclosedFence,
+            Self::LengthMismatch { .. } => crate::rules::Rule::FenceLengthMismatch,
+            Self::TypeMismatch { .. } => crate::rules::Rule::FenceTypeMismatch,
+            Self::OrphanedClosing { .. } => crate::rules::Rule::OrphanedFence,
+        }
+    }
+
+    /// Lines (0-indexed) of the fence markers involved.
+    #[must_use]
+    pub const fn lines(&self) -> (usize, Option<usize>) {
+        match self {
+            Self::Unclosed { opening } => (opening.line_num, None),
+            Self::LengthMismatch { opening, closing } | Self::TypeMismatch { opening, closing } => {
+                (opening.line_num, Some(closing.line_num))
+            }
+            Self::OrphanedClosing { marker } => (marker.line_num, None),
+        }
+    }
+}
+
+/// Detect all fence markers in a document.
 ///
 /// Returns a vector of fence markers with their metadata.
-/// Fences must be on a line by themselves (possibly with indentation).
+/// Fences must be on a line by themselves (possibly with indentation), and
+/// lines in other protected blocks (HTML, front matter, ignore regions...) are
+/// skipped.
 #[must_use]
-pub fn detect_fence_markers(content: &str) -> Vec<FenceMarker> {
+pub fn detect_fence_markers(document: &Document) -> Vec<FenceMarker> {
     let mut markers = Vec::new();
 
-    for (line_num, line) in content.lines().enumerate() {
-        let trimmed = line.trim();
-
-        // Check for backtick fence (3+ backticks)
-        if let Some(pos) = trimmed.find('`') {
-            if trimmed[pos..].starts_with("```") {
-                let fence_str = &trimmed[pos..];
-                let fence_end = fence_str.find(|c| c != '`').unwrap_or(fence_str.len());
-                let length = fence_end;
-
-                if length >= 3 {
-                    let rest = &fence_str[fence_end..].trim_start();
-                    let language = if rest.is_empty() {
-                        None
-                    } else {
-                        Some(rest.to_string())
-                    };
-
-                    markers.push(FenceMarker {
-                        line_num,
-                        fence_type: FenceType::Backtick,
-                        length,
-                        language,
-                        is_opening: false, // Will be determined during pairing
-                    });
-                }
-                continue;
-            }
+    let lines = document.lines().iter().zip(document.contexts());
+    for (line_num, (line, context)) in lines.enumerate() {
+        if !matches!(context, LineContext::Normal | LineContext::InCodeFence) {
+            continue;
         }
-
-        // Check for tilde fence (3+ tildes)
-        if let Some(pos) = trimmed.find('~') {
-            if trimmed[pos..].starts_with("~~~") {
-                let fence_str = &trimmed[pos..];
-                let fence_end = fence_str.find(|c| c != '~').unwrap_or(fence_str.len());
-                let length = fence_end;
-
-                if length >= 3 {
-                    let rest = &fence_str[fence_end..].trim_start();
-                    let language = if rest.is_empty() {
-                        None
-                    } else {
-                        Some(rest.to_string())
-                    };
-
-                    markers.push(FenceMarker {
-                        line_num,
-                        fence_type: FenceType::Tilde,
-                        length,
-                        language,
-                        is_opening: false, // Will be determined during pairing
-                    });
-                }
-            }
+        // Same marker rule as the document parser, so repair and processing agree
+        if let Some(marker) = crate::parser::parse_fence_marker(line) {
+            markers.push(FenceMarker {
+                line_num,
+                fence_type: if marker.fence_char == '`' {
+                    FenceType::Backtick
+                } else {
+                    FenceType::Tilde
+                },
+                length: marker.length,
+                language: (!marker.info.is_empty()).then(|| marker.info.to_string()),
+                is_opening: false, // Will be determined during pairing
+            });
         }
     }
 
@@ -277,7 +271,16 @@ pub fn validate_fences(blocks: &[CodeBlock]) -> Vec<FenceIssue> {
     issues
 }
 
-/// Normalize fences in content, repairing common issues.
+/// Find every fence issue in a document, except where `ascfix:disable fences`
+/// switched fence repair off at the issue's first marker.
+#[must_use]
+pub fn fence_issues(document: &Document) -> Vec<FenceIssue> {
+    let mut issues = validate_fences(&pair_fences(detect_fence_markers(document)));
+    issues.retain(|issue| !document.is_disabled(issue.lines().0, Fixer::Fence));
+    issues
+}
+
+/// Normalize fences in a document, repairing common issues.
 ///
 /// This is the main entry point for fence repair. It:
 /// 1. Detects all fence markers
@@ -286,28 +289,27 @@ pub fn validate_fences(blocks: &[CodeBlock]) -> Vec<FenceIssue> {
 /// 4. Repairs issues conservatively
 /// 5. Returns normalized content
 #[must_use]
-pub fn normalize_fences(content: &str) -> String {
-    let markers = detect_fence_markers(content);
+pub fn normalize_fences(document: &Document) -> String {
+    let content = document.text();
+    let markers = detect_fence_markers(document);
 
     // If no fences found, return unchanged
     if markers.is_empty() {
         return content.to_string();
     }
 
-    let blocks = pair_fences(markers);
-
     // If no validation issues, return unchanged
-    let issues = validate_fences(&blocks);
+    let issues = fence_issues(document);
     if issues.is_empty() {
         return content.to_string();
     }
 
     // Repair issues
-    repair_fences(content, &blocks, &issues)
+    repair_fences(content, &issues)
 }
 
 /// Repair fence issues in content.
-fn repair_fences(content: &str, _blocks: &[CodeBlock], issues: &[FenceIssue]) -> String {
+fn repair_fences(content: &str, issues: &[FenceIssue]) -> String {
     let lines: Vec<&str> = content.lines().collect();
     let mut result_lines: Vec<String> =
         lines.iter().map(std::string::ToString::to_string).collect();
@@ -329,8 +331,7 @@ fn repair_fences(content: &str, _blocks: &[CodeBlock], issues: &[FenceIssue]) ->
         match issue {
             FenceIssue::Unclosed { opening } => {
                 // Add closing fence after the last line
-                let closing_fence =
-                    format!("{}{}", fence_chars(opening.fence_type), opening.length);
+                let closing_fence = fence_chars(opening.fence_type).repeat(opening.length);
                 result_lines.push(closing_fence);
             }
             FenceIssue::LengthMismatch { opening, closing } => {
@@ -406,11 +407,20 @@ const fn lines_are_close(line1: usize, line2: usize, max_distance: usize) -> boo
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::ProtectConfig;
+
+    fn fence_markers(content: &str) -> Vec<FenceMarker> {
+        detect_fence_markers(&Document::parse(content, ProtectConfig::default()))
+    }
+
+    fn normalize(content: &str) -> String {
+        normalize_fences(&Document::parse(content, ProtectConfig::default()))
+    }
 
     #[test]
     fn test_detect_backtick_fence() {
         let content = "```python\ncode\n```";
-        let markers = detect_fence_markers(content);
+        let markers = fence_markers(content);
         assert_eq!(markers.len(), 2);
         assert_eq!(markers[0].fence_type, FenceType::Backtick);
         assert_eq!(markers[0].length, 3);
@@ -423,7 +433,7 @@ mod tests {
     #[test]
     fn test_detect_tilde_fence() {
         let content = "~~~ruby\ncode\n~~~";
-        let markers = detect_fence_markers(content);
+        let markers = fence_markers(content);
         assert_eq!(markers.len(), 2);
         assert_eq!(markers[0].fence_type, FenceType::Tilde);
         assert_eq!(markers[0].length, 3);
@@ -433,7 +443,7 @@ mod tests {
     #[test]
     fn test_detect_long_fence() {
         let content = "`````\ncode\n`````";
-        let markers = detect_fence_markers(content);
+        let markers = fence_markers(content);
         assert_eq!(markers.len(), 2);
         assert_eq!(markers[0].length, 5);
         assert_eq!(markers[1].length, 5);
@@ -442,7 +452,7 @@ mod tests {
     #[test]
     fn test_detect_fence_with_whitespace() {
         let content = "  ```python\n  code\n  ```";
-        let markers = detect_fence_markers(content);
+        let markers = fence_markers(content);
         assert_eq!(markers.len(), 2);
         assert_eq!(markers[0].fence_type, FenceType::Backtick);
     }
@@ -450,7 +460,7 @@ mod tests {
     #[test]
     fn test_pair_simple_fences() {
         let content = "```\ncode\n```";
-        let markers = detect_fence_markers(content);
+        let markers = fence_markers(content);
         let blocks = pair_fences(markers);
         assert_eq!(blocks.len(), 1);
         assert!(blocks[0].closing.is_some());
@@ -461,7 +471,7 @@ mod tests {
     #[test]
     fn test_pair_nested_fences() {
         let content = "`````\n```\ncode\n```\n`````";
-        let markers = detect_fence_markers(content);
+        let markers = fence_markers(content);
         let blocks = pair_fences(markers);
         // Should have 2 blocks: outer 5-backtick and inner 3-backtick
         assert_eq!(blocks.len(), 2);
@@ -470,7 +480,7 @@ mod tests {
     #[test]
     fn test_pair_unclosed_fence() {
         let content = "```\ncode";
-        let markers = detect_fence_markers(content);
+        let markers = fence_markers(content);
         let blocks = pair_fences(markers);
         assert_eq!(blocks.len(), 1);
         assert!(blocks[0].closing.is_none());
@@ -479,7 +489,7 @@ mod tests {
     #[test]
     fn test_validate_length_mismatch() {
         let content = "```\ncode\n`````";
-        let markers = detect_fence_markers(content);
+        let markers = fence_markers(content);
         let blocks = pair_fences(markers);
         let issues = validate_fences(&blocks);
         assert_eq!(issues.len(), 1);
@@ -492,7 +502,7 @@ mod tests {
     #[test]
     fn test_validate_type_mismatch() {
         let content = "```\ncode\n~~~";
-        let markers = detect_fence_markers(content);
+        let markers = fence_markers(content);
         let blocks = pair_fences(markers);
         // When types don't match, they don't pair, so we get 2 unclosed fences
         // (not a type mismatch issue, since they're not paired)
@@ -504,7 +514,7 @@ mod tests {
     #[test]
     fn test_validate_unclosed() {
         let content = "```\ncode";
-        let markers = detect_fence_markers(content);
+        let markers = fence_markers(content);
         let blocks = pair_fences(markers);
         let issues = validate_fences(&blocks);
         assert_eq!(issues.len(), 1);
@@ -517,7 +527,7 @@ mod tests {
     #[test]
     fn test_normalize_length_mismatch() {
         let content = "```python\ncode\n`````";
-        let normalized = normalize_fences(content);
+        let normalized = normalize(content);
         // Both fences should now have 5 backticks
         assert!(normalized.contains("`````python") || normalized.contains("`````"));
         assert!(normalized.contains("`````"));
@@ -526,7 +536,7 @@ mod tests {
     #[test]
     fn test_normalize_unclosed_fence() {
         let content = "```python\ncode";
-        let normalized = normalize_fences(content);
+        let normalized = normalize(content);
         // Should have added closing fence
         assert!(normalized.contains("```"));
         // Check that we have both opening (with language) and closing
@@ -540,29 +550,29 @@ mod tests {
     #[test]
     fn test_normalize_already_correct() {
         let content = "```python\ncode\n```";
-        let normalized = normalize_fences(content);
+        let normalized = normalize(content);
         assert_eq!(normalized, content);
     }
 
     #[test]
     fn test_normalize_idempotent() {
         let content = "```python\ncode\n`````";
-        let first = normalize_fences(content);
-        let second = normalize_fences(&first);
+        let first = normalize(content);
+        let second = normalize(&first);
         assert_eq!(first, second);
     }
 
     #[test]
     fn test_normalize_no_fences() {
         let content = "# Title\n\nNo fences here";
-        let normalized = normalize_fences(content);
+        let normalized = normalize(content);
         assert_eq!(normalized, content);
     }
 
     #[test]
     fn test_normalize_multiple_blocks() {
         let content = "```\ncode1\n```\n\n```\ncode2\n`````";
-        let normalized = normalize_fences(content);
+        let normalized = normalize(content);
         // Should have fixed the second block
         assert!(!normalized.is_empty());
     }
@@ -570,45 +580,45 @@ mod tests {
     #[test]
     fn test_fence_type_detection_backtick() {
         let content = "```\ncode\n```";
-        let markers = detect_fence_markers(content);
+        let markers = fence_markers(content);
         assert!(markers.iter().all(|m| m.fence_type == FenceType::Backtick));
     }
 
     #[test]
     fn test_fence_type_detection_tilde() {
         let content = "~~~\ncode\n~~~";
-        let markers = detect_fence_markers(content);
+        let markers = fence_markers(content);
         assert!(markers.iter().all(|m| m.fence_type == FenceType::Tilde));
     }
 
     #[test]
     fn test_language_specifier_preserved() {
         let content = "```javascript\ncode\n```";
-        let normalized = normalize_fences(content);
+        let normalized = normalize(content);
         assert!(normalized.contains("javascript"));
     }
 
     #[test]
     fn test_skip_type_mismatch() {
         let content = "```\ncode\n~~~";
-        let normalized = normalize_fences(content);
+        let normalized = normalize(content);
         // When types don't match, they don't pair - so we get 2 unclosed fences instead of a type mismatch
         // normalize_fences will add closing fences for both
-        let markers = detect_fence_markers(&normalized);
+        let markers = fence_markers(&normalized);
         assert!(markers.len() >= 2); // At least opening backtick and tilde
     }
 
     #[test]
     fn test_empty_fence_block() {
         let content = "```\n```";
-        let normalized = normalize_fences(content);
+        let normalized = normalize(content);
         assert_eq!(normalized, content);
     }
 
     #[test]
     fn test_fence_at_end_of_file() {
         let content = "```\ncode";
-        let normalized = normalize_fences(content);
+        let normalized = normalize(content);
         assert!(normalized.contains("```"));
         assert!(normalized.lines().count() >= 2);
     }
@@ -616,7 +626,7 @@ mod tests {
     #[test]
     fn test_indented_fences() {
         let content = "  ```python\n  code\n  ```";
-        let normalized = normalize_fences(content);
+        let normalized = normalize(content);
         // Should preserve indentation
         for line in normalized.lines() {
             if line.contains('`') {
@@ -628,7 +638,7 @@ mod tests {
     #[test]
     fn test_duplicate_closing_fence_basic_internal() {
         let content = "Code:\n\n```python\ncode\n```\n```\n\nAfter";
-        let normalized = normalize_fences(content);
+        let normalized = normalize(content);
         // Should remove the duplicate closing fence
         assert!(
             !normalized.contains("```\n```"),
@@ -0,0 +1,255 @@
+//! Selection of the individual repair passes ("fixers").
+//!
+//! Every fixer can be switched on or off on its own with `--fix` / `--skip`
+//! or the `[fixes]` configuration table; `--fix +fences` adds fixers to the
+//! mode preset instead of replacing it. The processing modes are named
+//! presets of this selection:
+//! - `safe`: `tables,lists`
+//! - `diagram`: `boxes,arrows` (also written `diagrams`)
+
+use crate::cli::Mode;
+use std::fmt;
+
+/// One independent repair pass.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fix {
+    /// Code fence boundary repair
+    Fences,
+    /// Markdown table normalization
+    Tables,
+    /// List indentation and bullet normalization
+    Lists,
+    /// Box resizing, balancing and padding in diagrams
+    Boxes,
+    /// Arrow alignment in diagrams
+    Arrows,
+}
+
+impl Fix {
+    /// Every fixer, in the order the passes run.
+    pub const ALL: [Self; 5] = [
+        Self::Fences,
+        Self::Tables,
+        Self::Lists,
+        Self::Boxes,
+        Self::Arrows,
+    ];
+
+    /// Name used on the command line.
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Fences => "fences",
+            Self::Tables => "tables",
+            Self::Lists => "lists",
+            Self::Boxes => "boxes",
+            Self::Arrows => "arrows",
+        }
+    }
+
+    /// Bit used for this fixer in a [`FixSelection`].
+    const fn bit(self) -> u8 {
+        match self {
+            Self::Fences => 1,
+            Self::Tables => 1 << 1,
+            Self::Lists => 1 << 2,
+            Self::Boxes => 1 << 3,
+            Self::Arrows => 1 << 4,
+        }
+    }
+}
+
+/// A set of enabled fixers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FixSelection(u8);
+
+impl FixSelection {
+    /// No fixer.
+    pub const NONE: Self = Self(0);
+    /// Every fixer.
+    pub const ALL: Self = Self(0b1_1111);
+    /// Diagram repair: boxes and arrows.
+    pub const DIAGRAMS: Self = Self(Fix::Boxes.bit() | Fix::Arrows.bit());
+
+    /// The fixers a processing mode stands for.
+    #[must_use]
+    pub const fn preset(mode: &Mode) -> Self {
+        match mode {
+            Mode::Safe => Self(Fix::Tables.bit() | Fix::Lists.bit()),
+            Mode::Diagram => Self::DIAGRAMS,
+        }
+    }
+
+    /// Check whether a fixer is enabled.
+    #[must_use]
+    pub const fn contains(self, fix: Fix) -> bool {
+        self.0 & fix.bit() != 0
+    }
+
+    /// Check whether any diagram fixer is enabled.
+    #[must_use]
+    pub const fn has_diagrams(self) -> bool {
+        self.0 & Self::DIAGRAMS.0 != 0
+    }
+
+    /// Return this selection with `fix` switched on or off.
+    #[must_use]
+    pub const fn with(self, fix: Fix, enabled: bool) -> Self {
+        if enabled {
+            Self(self.0 | fix.bit())
+        } else {
+            Self(self.0 & !fix.bit())
+        }
+    }
+
+    /// Return the union of two selections.
+    #[must_use]
+    pub const fn union(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+
+    /// Return this selection without the fixers of `other`.
+    #[must_use]
+    pub const fn difference(self, other: Self) -> Self {
+        Self(self.0 & !other.0)
+    }
+
+    /// Return the fixers enabled in both selections.
+    #[must_use]
+    pub const fn intersect(self, other: Self) -> Self {
+        Self(self.0 & other.0)
+    }
+
+    /// Enabled fixers, in the order the passes run.
+    pub fn iter(self) -> impl Iterator<Item = Fix> {
+        Fix::ALL.into_iter().filter(move |fix| self.contains(*fix))
+    }
+
+    /// Parse a comma-separated list such as `tables,lists`. `diagrams` stands
+    /// for `boxes,arrows` and `all` for every fixer.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error naming the first unknown fixer. `links` gets its own
+    /// message: links are kept intact by the other fixers but never rewritten.
+    pub fn parse(list: &str) -> Result<Self, String> {
+        let mut selection = Self::NONE;
+        for name in list
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+        {
+            selection = selection.union(match name {
+                "all" => Self::ALL,
+                "diagrams" => Self::DIAGRAMS,
+                "links" => {
+                    return Err(
+                        "there is no 'links' fixer: links are preserved by the other fixers but never rewritten"
+                            .to_string(),
+                    )
+                }
+                _ => Fix::ALL
+                    .into_iter()
+                    .find(|fix| fix.name() == name)
+                    .map(|fix| Self::NONE.with(fix, true))
+                    .ok_or_else(|| {
+                        format!(
+                            "unknown fixer '{name}' (expected fences, tables, lists, boxes, arrows, diagrams or all)"
+                        )
+                    })?,
+            });
+        }
+        Ok(selection)
+    }
+}
+
+impl fmt::Display for FixSelection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names: Vec<&str> = self.iter().map(Fix::name).collect();
+        if names.is_empty() {
+            f.write_str("none")
+        } else {
+            f.write_str(&names.join(","))
+        }
+    }
+}
+
+/// A `--fix` value: exactly the listed fixers, or with a leading `+`
+/// (`+fences,tables`) the listed fixers on top of the mode preset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FixRequest {
+    /// Run exactly these fixers
+    Exactly(FixSelection),
+    /// Run these fixers as well as the preset
+    Adding(FixSelection),
+}
+
+impl FixRequest {
+    /// Parse a `--fix` value.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error naming the first unknown fixer.
+    pub fn parse(value: &str) -> Result<Self, String> {
+        value.trim().strip_prefix('+').map_or_else(
+            || FixSelection::parse(value).map(Self::Exactly),
+            |list| FixSelection::parse(list).map(Self::Adding),
+        )
+    }
+
+    /// The fixers named by this request.
+    #[must_use]
+    pub const fn selection(self) -> FixSelection {
+        match self {
+            Self::Exactly(selection) | Self::Adding(selection) => selection,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_fix_lists() {
+        let selection = FixSelection::parse("tables, diagrams").unwrap();
+        assert!(selection.contains(Fix::Tables));
+        assert!(selection.contains(Fix::Boxes));
+        assert!(selection.contains(Fix::Arrows));
+        assert!(!selection.contains(Fix::Lists));
+        assert_eq!(selection.to_string(), "tables,boxes,arrows");
+
+        assert_eq!(FixSelection::parse("all").unwrap(), FixSelection::ALL);
+        let err = FixSelection::parse("tables,lnks").unwrap_err();
+        assert!(err.contains("unknown fixer 'lnks'"), "got: {err}");
+        let err = FixSelection::parse("tables,links").unwrap_err();
+        assert!(err.contains("no 'links' fixer"), "got: {err}");
+    }
+
+    #[test]
+    fn test_mode_presets() {
+        assert_eq!(
+            FixSelection::preset(&Mode::Safe).to_string(),
+            "tables,lists"
+        );
+        assert_eq!(FixSelection::preset(&Mode::Diagram), FixSelection::DIAGRAMS);
+        assert!(!FixSelection::preset(&Mode::Safe).has_diagrams());
+    }
+
+    #[test]
+    fn test_parse_fix_requests() {
+        assert_eq!(
+            FixRequest::parse("tables"),
+            Ok(FixRequest::Exactly(
+                FixSelection::NONE.with(Fix::Tables, true)
+            ))
+        );
+        assert_eq!(
+            FixRequest::parse(" +fences,lists"),
+            Ok(FixRequest::Adding(
+                FixSelection::parse("fences,lists").unwrap()
+            ))
+        );
+        assert!(FixRequest::parse("+links").is_err());
+    }
+}
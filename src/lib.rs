@@ -3,10 +3,19 @@
 //! This library exposes the core functionality for processing and normalizing
 //! ASCII diagrams in Markdown content.
 
+pub mod ansi;
+pub mod baseline;
+pub mod changes;
+pub mod classifier;
 pub mod cli;
 pub mod config;
+pub mod config_command;
+pub mod confusables;
+pub mod directives;
 pub mod discovery;
+pub mod document;
 pub mod fences;
+pub mod fixes;
 pub mod links;
 pub mod lists;
 pub mod modes;
@@ -23,5 +32,8 @@ pub mod primitives;
 pub mod processor;
 pub mod quality;
 pub mod renderer;
+pub mod report;
+pub mod rules;
 pub mod scanner;
 pub mod transformation_analysis;
+pub mod walls;
@@ -3,6 +3,8 @@
 //! This module detects and normalizes Markdown lists, fixing inconsistent
 //! indentation and bullet styles.
 
+use crate::document::Document;
+
 /// Represents a detected list item.
 #[derive(Debug, Clone, PartialEq, Eq)]
 #[allow(dead_code)] // Reason: Part of public API for library usage
@@ -38,26 +40,28 @@ pub struct List {
 /// Detect all lists in the given content.
 ///
 /// Identifies both bullet lists (-, *, +) and ordered lists (1., 2), etc.).
-/// Lists inside code blocks are ignored.
+/// Lists inside protected blocks of the document (code, HTML...) are ignored.
 ///
 /// # Examples
 ///
 /// ```
+/// use ascfix::config::ProtectConfig;
+/// use ascfix::document::Document;
 /// use ascfix::lists::detect_lists;
 ///
-/// let content = "- Item 1\n- Item 2\n- Item 3";
-/// let lists = detect_lists(content);
+/// let document = Document::parse("- Item 1\n- Item 2\n- Item 3", ProtectConfig::default());
+/// let lists = detect_lists(&document);
 /// assert_eq!(lists.len(), 1);
 /// assert_eq!(lists[0].items.len(), 3);
 /// ```
 #[must_use]
 #[allow(dead_code)] // Reason: Public API for list detection, used in tests
-pub fn detect_lists(content: &str) -> Vec<List> {
+pub fn detect_lists(document: &Document) -> Vec<List> {
     let mut lists = Vec::new();
-    let lines: Vec<&str> = content.lines().collect();
+    let lines = document.lines();
 
     // Get code block line ranges to skip
-    let code_line_ranges = get_code_block_line_ranges(content);
+    let code_line_ranges = document.protected_line_ranges();
 
     let mut i = 0;
     while i < lines.len() {
@@ -139,43 +143,6 @@ pub fn detect_lists(content: &str) -> Vec<List> {
     lists
 }
 
-/// Get code block regions as line number ranges (`start_line`, `end_line`).
-fn get_code_block_line_ranges(content: &str) -> Vec<(usize, usize)> {
-    let mut ranges = Vec::new();
-    let lines: Vec<&str> = content.lines().collect();
-    let mut in_code_block = false;
-    let mut block_start = 0;
-
-    for (i, line) in lines.iter().enumerate() {
-        let trimmed = line.trim();
-
-        // Check for fence markers
-        if is_fence_line(trimmed) {
-            if in_code_block {
-                // End of code block
-                ranges.push((block_start, i));
-                in_code_block = false;
-            } else {
-                // Start of code block
-                block_start = i;
-                in_code_block = true;
-            }
-        }
-    }
-
-    // Handle unclosed code block
-    if in_code_block {
-        ranges.push((block_start, lines.len() - 1));
-    }
-
-    ranges
-}
-
-/// Check if a line is a fence marker (starts with backticks or tildes).
-fn is_fence_line(line: &str) -> bool {
-    line.starts_with("```") || line.starts_with("~~~")
-}
-
 /// Parse a single line to see if it's a list item.
 fn parse_list_item(line: &str, line_number: usize) -> Option<ListItem> {
     let trimmed = line.trim_start();
@@ -354,22 +321,24 @@ fn is_in_code_region(line_num: usize, regions: &[(usize, usize)]) -> bool {
 /// # Examples
 ///
 /// ```
+/// use ascfix::config::ProtectConfig;
+/// use ascfix::document::Document;
 /// use ascfix::lists::normalize_list_indentation;
 ///
 /// let content = "- Item 1\n    - Nested item\n- Item 2";
-/// let normalized = normalize_list_indentation(content);
+/// let normalized = normalize_list_indentation(&Document::parse(content, ProtectConfig::default()));
 /// assert!(normalized.contains("  - Nested item")); // 2 spaces, not 4
 /// ```
 #[must_use]
 #[allow(dead_code)] // Reason: Public API for list normalization, used in tests
-pub fn normalize_list_indentation(content: &str) -> String {
-    let lines: Vec<&str> = content.lines().collect();
+pub fn normalize_list_indentation(document: &Document) -> String {
+    let lines = document.lines();
     if lines.is_empty() {
         return String::new();
     }
 
     // Get code block regions to skip
-    let code_ranges = get_code_block_line_ranges(content);
+    let code_ranges = document.protected_line_ranges();
 
     let mut result = Vec::new();
     let mut list_stack: Vec<usize> = Vec::new(); // Stack of indentation levels for each list level
@@ -434,24 +403,26 @@ pub fn normalize_list_indentation(content: &str) -> String {
 ///
 /// # Arguments
 ///
-/// * `content` - The content containing lists to normalize
+/// * `document` - The parsed document containing lists to normalize
 /// * `target_bullet` - The bullet character to use (`-`, `*`, or `+`)
 ///
 /// # Examples
 ///
 /// ```
+/// use ascfix::config::ProtectConfig;
+/// use ascfix::document::Document;
 /// use ascfix::lists::normalize_bullet_styles;
 ///
-/// let content = "- Item 1\n* Item 2\n+ Item 3";
-/// let normalized = normalize_bullet_styles(content, '-');
+/// let document = Document::parse("- Item 1\n* Item 2\n+ Item 3", ProtectConfig::default());
+/// let normalized = normalize_bullet_styles(&document, '-');
 /// assert!(normalized.contains("- Item 1"));
 /// assert!(normalized.contains("- Item 2"));
 /// assert!(normalized.contains("- Item 3"));
 /// ```
 #[must_use]
 #[allow(dead_code)] // Reason: Public API for list normalization, used in tests
-pub fn normalize_bullet_styles(content: &str, target_bullet: char) -> String {
-    let lines: Vec<&str> = content.lines().collect();
+pub fn normalize_bullet_styles(document: &Document, target_bullet: char) -> String {
+    let lines = document.lines();
     if lines.is_empty() {
         return String::new();
     }
@@ -463,7 +434,7 @@ pub fn normalize_bullet_styles(content: &str, target_bullet: char) -> String {
     };
 
     // Get code block regions to skip
-    let code_ranges = get_code_block_line_ranges(content);
+    let code_ranges = document.protected_line_ranges();
 
     let mut result = Vec::new();
 
@@ -510,31 +481,34 @@ pub fn normalize_bullet_styles(content: &str, target_bullet: char) -> String {
     result.join("\n")
 }
 
-/// Normalize lists in content with both indentation and bullet style fixes.
+/// Normalize lists in a document with both indentation and bullet style fixes.
 ///
 /// Combines indentation normalization (2-space increments) and bullet style
-/// normalization (consistent `-` style) in a single pass.
+/// normalization (consistent `-` style) in a single pass. Protected blocks of
+/// the document, and lines where lists are disabled, are left untouched.
 ///
 /// # Examples
 ///
 /// ```
+/// use ascfix::config::ProtectConfig;
+/// use ascfix::document::Document;
 /// use ascfix::lists::normalize_lists;
 ///
 /// let content = "- Item 1\n    * Nested with 4 spaces\n+ Item 2";
-/// let normalized = normalize_lists(content);
+/// let normalized = normalize_lists(&Document::parse(content, ProtectConfig::default()));
 /// assert!(normalized.contains("- Item 1"));
 /// assert!(normalized.contains("  - Nested with 4 spaces")); // 2 spaces, normalized
 /// assert!(normalized.contains("- Item 2")); // + changed to -
 /// ```
 #[must_use]
-pub fn normalize_lists(content: &str) -> String {
-    let lines: Vec<&str> = content.lines().collect();
+pub fn normalize_lists(document: &Document) -> String {
+    let lines = document.lines();
     if lines.is_empty() {
         return String::new();
     }
 
     // Get code block regions to skip
-    let code_ranges = get_code_block_line_ranges(content);
+    let code_ranges = document.protected_line_ranges();
 
     let mut result = Vec::new();
     let mut list_stack: Vec<usize> = Vec::new(); // Stack of indentation levels for each list level
@@ -542,7 +516,9 @@ pub fn normalize_lists(content: &str) -> String {
 
     for (i, line) in lines.iter().enumerate() {
         // Skip lines inside code blocks
-        if is_in_code_region(i, &code_ranges) {
+        if is_in_code_region(i, &code_ranges)
+            || document.is_disabled(i, crate::changes::Fixer::List)
+        {
             result.push(line.to_string());
             // Reset stack when entering code block
             list_stack.clear();
@@ -683,11 +659,16 @@ pub fn normalize_loose_lists(content: &str) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::ProtectConfig;
+
+    fn parse(content: &str) -> Document<'_> {
+        Document::parse(content, ProtectConfig::default())
+    }
 
     #[test]
     fn detect_simple_bullet_list() {
         let content = "- Item 1\n- Item 2\n- Item 3";
-        let lists = detect_lists(content);
+        let lists = detect_lists(&parse(content));
         assert_eq!(lists.len(), 1);
         assert_eq!(lists[0].items.len(), 3);
         assert_eq!(lists[0].items[0].content, "Item 1");
@@ -698,7 +679,7 @@ mod tests {
     #[test]
     fn detect_list_with_mixed_bullets() {
         let content = "- Item 1\n* Item 2\n+ Item 3";
-        let lists = detect_lists(content);
+        let lists = detect_lists(&parse(content));
         assert_eq!(lists.len(), 1);
         assert_eq!(lists[0].items.len(), 3);
     }
@@ -706,7 +687,7 @@ mod tests {
     #[test]
     fn detect_ordered_list() {
         let content = "1. First item\n2. Second item\n3. Third item";
-        let lists = detect_lists(content);
+        let lists = detect_lists(&parse(content));
         assert_eq!(lists.len(), 1);
         assert_eq!(lists[0].items.len(), 3);
         assert!(lists[0].is_ordered);
@@ -715,7 +696,7 @@ mod tests {
     #[test]
     fn detect_task_list() {
         let content = "- [ ] Todo item\n- [x] Done item\n- [X] Also done";
-        let lists = detect_lists(content);
+        let lists = detect_lists(&parse(content));
         assert_eq!(lists.len(), 1);
         assert_eq!(lists[0].items.len(), 3);
         assert!(lists[0].items[0].is_task);
@@ -728,16 +709,25 @@ mod tests {
     fn ignore_lists_in_code_blocks() {
         let content =
             "```markdown\n- Item in code block\n- Another item\n```\n\n- Real item outside";
-        let lists = detect_lists(content);
+        let lists = detect_lists(&parse(content));
         assert_eq!(lists.len(), 1);
         assert_eq!(lists[0].items.len(), 1);
         assert_eq!(lists[0].items[0].content, "Real item outside");
     }
 
+    #[test]
+    fn ignore_lists_in_indented_code_blocks() {
+        let content = "Example:\n\n    * Item in code\n    * Another\n\n- Real item";
+        let lists = detect_lists(&parse(content));
+        assert_eq!(lists.len(), 1);
+        assert_eq!(lists[0].items[0].content, "Real item");
+        assert_eq!(normalize_lists(&parse(content)), content);
+    }
+
     #[test]
     fn detect_multiple_lists() {
         let content = "- First list item 1\n- First list item 2\n\nSome text\n\n* Second list item 1\n* Second list item 2";
-        let lists = detect_lists(content);
+        let lists = detect_lists(&parse(content));
         assert_eq!(lists.len(), 2);
         assert_eq!(lists[0].items.len(), 2);
         assert_eq!(lists[1].items.len(), 2);
@@ -746,7 +736,7 @@ mod tests {
     #[test]
     fn no_lists_in_plain_text() {
         let content = "This is just a paragraph.\nNo lists here.\nJust text.";
-        let lists = detect_lists(content);
+        let lists = detect_lists(&parse(content));
         assert_eq!(lists.len(), 0);
     }
 
@@ -754,7 +744,7 @@ mod tests {
     fn normalize_indentation_to_two_spaces() {
         // 4-space indentation should become 2-space
         let content = "- Item 1\n    - Nested item\n- Item 2";
-        let normalized = normalize_list_indentation(content);
+        let normalized = normalize_list_indentation(&parse(content));
         assert!(normalized.contains("- Item 1"));
         assert!(normalized.contains("  - Nested item")); // 2 spaces
         assert!(!normalized.contains("    - Nested")); // No 4 spaces
@@ -764,7 +754,7 @@ mod tests {
     fn normalize_deeply_nested_list() {
         // Mixed indentation should be normalized
         let content = "- Level 1\n    - Level 2\n        - Level 3\n- Back to 1";
-        let normalized = normalize_list_indentation(content);
+        let normalized = normalize_list_indentation(&parse(content));
         assert!(normalized.contains("- Level 1"));
         assert!(normalized.contains("  - Level 2")); // 2 spaces
         assert!(normalized.contains("    - Level 3")); // 4 spaces (2 per level)
@@ -774,7 +764,7 @@ mod tests {
     fn preserve_content_when_normalizing() {
         // Content should remain unchanged, only indentation fixed
         let content = "- First item with text\n    - Second item with more text";
-        let normalized = normalize_list_indentation(content);
+        let normalized = normalize_list_indentation(&parse(content));
         assert!(normalized.contains("First item with text"));
         assert!(normalized.contains("Second item with more text"));
     }
@@ -783,7 +773,7 @@ mod tests {
     fn no_change_to_already_normalized() {
         // Already 2-space indented lists should remain unchanged
         let content = "- Item 1\n  - Nested\n  - Another nested\n- Item 2";
-        let normalized = normalize_list_indentation(content);
+        let normalized = normalize_list_indentation(&parse(content));
         assert_eq!(normalized, content);
     }
 
@@ -791,7 +781,7 @@ mod tests {
     fn normalize_mixed_indentation_styles() {
         // Mixed 2-space and 4-space should become all 2-space relative
         let content = "- Item 1\n  - Two space\n    - Four space (should be 4)\n- Item 2";
-        let normalized = normalize_list_indentation(content);
+        let normalized = normalize_list_indentation(&parse(content));
         assert!(normalized.contains("- Item 1"));
         assert!(normalized.contains("  - Two space"));
         assert!(normalized.contains("    - Four space")); // 4 spaces is correct for 2nd level
@@ -801,7 +791,7 @@ mod tests {
     fn normalize_bullet_styles_to_dash() {
         // Mixed bullet styles should become dashes
         let content = "- Item 1\n* Item 2\n+ Item 3";
-        let normalized = normalize_bullet_styles(content, '-');
+        let normalized = normalize_bullet_styles(&parse(content), '-');
         assert!(normalized.contains("- Item 1"));
         assert!(normalized.contains("- Item 2"));
         assert!(normalized.contains("- Item 3"));
@@ -813,7 +803,7 @@ mod tests {
     fn normalize_bullet_styles_to_asterisk() {
         // Can normalize to any bullet style
         let content = "- Item 1\n* Item 2\n+ Item 3";
-        let normalized = normalize_bullet_styles(content, '*');
+        let normalized = normalize_bullet_styles(&parse(content), '*');
         assert!(normalized.contains("* Item 1"));
         assert!(normalized.contains("* Item 2"));
         assert!(normalized.contains("* Item 3"));
@@ -823,7 +813,7 @@ mod tests {
     fn bullet_normalization_preserves_indentation() {
         // Bullet style change should not affect indentation
         let content = "- Item 1\n  * Nested\n    + Deep";
-        let normalized = normalize_bullet_styles(content, '-');
+        let normalized = normalize_bullet_styles(&parse(content), '-');
         assert!(normalized.contains("- Item 1"));
         assert!(normalized.contains("  - Nested"));
         assert!(normalized.contains("    - Deep"));
@@ -833,7 +823,7 @@ mod tests {
     fn bullet_normalization_preserves_task_lists() {
         // Task list syntax should be preserved
         let content = "- [ ] Todo\n* [x] Done\n+ [ ] Another";
-        let normalized = normalize_bullet_styles(content, '-');
+        let normalized = normalize_bullet_styles(&parse(content), '-');
         assert!(normalized.contains("- [ ] Todo"));
         assert!(normalized.contains("- [x] Done"));
         assert!(normalized.contains("- [ ] Another"));
@@ -843,7 +833,7 @@ mod tests {
     fn bullet_normalization_preserves_ordered_lists() {
         // Ordered lists should not be affected
         let content = "1. First\n2. Second\n- Unordered";
-        let normalized = normalize_bullet_styles(content, '-');
+        let normalized = normalize_bullet_styles(&parse(content), '-');
         assert!(normalized.contains("1. First"));
         assert!(normalized.contains("2. Second"));
         assert!(normalized.contains("- Unordered"));
@@ -853,7 +843,7 @@ mod tests {
     fn normalize_complex_nested_list() {
         // Mixed nesting with different bullets should normalize correctly
         let content = "- Level 1\n  * Level 2\n    + Level 3\n- Back to 1";
-        let normalized = normalize_lists(content);
+        let normalized = normalize_lists(&parse(content));
         // All should use consistent bullet and indentation
         assert!(normalized.contains("- Level 1"));
         assert!(normalized.contains("  - Level 2"));
@@ -865,7 +855,7 @@ mod tests {
     fn normalize_deeply_nested_structure() {
         // 4 levels deep
         let content = "- A\n  - B\n    - C\n      - D\n- E";
-        let normalized = normalize_lists(content);
+        let normalized = normalize_lists(&parse(content));
         assert!(normalized.contains("- A"));
         assert!(normalized.contains("  - B"));
         assert!(normalized.contains("    - C"));
@@ -877,7 +867,7 @@ mod tests {
     fn normalize_nested_with_inconsistent_indentation() {
         // Mix of 2-space and 4-space nesting should become consistent 2-space
         let content = "- Item 1\n    - Nested with 4\n      - Deeper\n- Item 2";
-        let normalized = normalize_lists(content);
+        let normalized = normalize_lists(&parse(content));
         assert!(normalized.contains("- Item 1"));
         assert!(normalized.contains("  - Nested with 4")); // Now 2-space
         assert!(normalized.contains("    - Deeper")); // 4-space (2nd level)
@@ -889,7 +879,7 @@ mod tests {
         // Two separate lists should not affect each other
         let content =
             "- First list A\n- First list B\n\nSome text\n\n* Second list A\n* Second list B";
-        let normalized = normalize_lists(content);
+        let normalized = normalize_lists(&parse(content));
         assert!(normalized.contains("- First list A"));
         assert!(normalized.contains("- First list B"));
         assert!(normalized.contains("- Second list A")); // Normalized to -
@@ -901,7 +891,7 @@ mod tests {
         // Task list checkboxes should be preserved during normalization
         // Note: We normalize both [x] and [X] to [x] for consistency
         let content = "- [ ] Unchecked todo\n- [x] Checked todo\n- [X] Also checked";
-        let normalized = normalize_lists(content);
+        let normalized = normalize_lists(&parse(content));
         assert!(normalized.contains("- [ ] Unchecked todo"));
         assert!(normalized.contains("- [x] Checked todo"));
         assert!(normalized.contains("- [x] Also checked")); // [X] normalized to [x]
@@ -911,7 +901,7 @@ mod tests {
     fn normalize_mixed_task_and_regular() {
         // Mix of task and regular list items
         let content = "- [ ] Todo item\n- Regular item\n- [x] Done item";
-        let normalized = normalize_lists(content);
+        let normalized = normalize_lists(&parse(content));
         assert!(normalized.contains("- [ ] Todo item"));
         assert!(normalized.contains("- Regular item"));
         assert!(normalized.contains("- [x] Done item"));
@@ -922,7 +912,7 @@ mod tests {
         // Task lists can be nested
         let content =
             "- [ ] Parent task\n  - [ ] Subtask 1\n  - [x] Subtask 2\n- [ ] Another parent";
-        let normalized = normalize_lists(content);
+        let normalized = normalize_lists(&parse(content));
         assert!(normalized.contains("- [ ] Parent task"));
         assert!(normalized.contains("  - [ ] Subtask 1"));
         assert!(normalized.contains("  - [x] Subtask 2"));
@@ -933,7 +923,7 @@ mod tests {
     fn task_list_with_bullet_normalization() {
         // Task lists should normalize bullet style too
         let content = "- [ ] Todo 1\n* [ ] Todo 2\n+ [x] Done";
-        let normalized = normalize_lists(content);
+        let normalized = normalize_lists(&parse(content));
         assert!(normalized.contains("- [ ] Todo 1"));
         assert!(normalized.contains("- [ ] Todo 2")); // * changed to -
         assert!(normalized.contains("- [x] Done")); // + changed to -
@@ -944,7 +934,7 @@ mod tests {
         // Task lists in code blocks should not be normalized
         let content =
             "```markdown\n- [ ] In code block\n- [x] Also in block\n```\n\n- [ ] Real task outside";
-        let normalized = normalize_lists(content);
+        let normalized = normalize_lists(&parse(content));
         assert!(normalized.contains("- [ ] In code block")); // Preserved as-is
         assert!(normalized.contains("- [ ] Real task outside"));
     }
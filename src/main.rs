@@ -5,11 +5,20 @@
 //! - ASCII boxes and arrows
 //! - Text row formatting
 
+mod ansi;
+mod baseline;
+mod changes;
+mod classifier;
 mod cli;
 mod config;
+mod config_command;
+mod confusables;
 mod detector;
+mod directives;
 mod discovery;
+mod document;
 mod fences;
+mod fixes;
 pub mod grid;
 mod io;
 mod links;
@@ -22,9 +31,12 @@ mod primitives;
 mod processor;
 mod quality;
 mod renderer;
+mod report;
+mod rules;
 mod scanner;
 mod tables;
 mod transformation_analysis;
+mod walls;
 
 use anyhow::Result;
 use clap::Parser;
@@ -33,8 +45,12 @@ use processor::Processor;
 
 fn main() -> Result<()> {
     let args = Args::parse();
-    let processor = Processor::new(args)?;
-    let exit_code = processor.process_all()?;
+    args.color.apply();
+    let exit_code = if let Some(cli::Command::Config(command)) = &args.command {
+        config_command::run(command, &args)?
+    } else {
+        Processor::new(args)?.process_all()?
+    };
 
     // Exit with appropriate code (0 for success, 1 for check mode failures)
     std::process::exit(exit_code);
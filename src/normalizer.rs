@@ -142,8 +142,8 @@ pub fn align_horizontal_arrows(inventory: &PrimitiveInventory) -> PrimitiveInven
 
     // For each row, sort arrows by start position and collect preserving row order
     normalized.horizontal_arrows = arrows_by_row
-        .into_iter()
-        .flat_map(|(_row, mut arrows)| {
+        .into_values()
+        .flat_map(|mut arrows| {
             // Sort arrows by start position within row
             arrows.sort_by_key(|a| a.start_col);
             arrows
@@ -182,8 +182,19 @@ pub fn normalize_padding(inventory: &PrimitiveInventory) -> PrimitiveInventory {
             .iter()
             .min_by_key(|box_| box_.width() * box_.height())
         {
+            // Text to the right of a nested box keeps its column
+            let follows_child = normalized.boxes.iter().any(|child| {
+                child != *b
+                    && child.top_left.1 > b.top_left.1
+                    && child.bottom_right.1 < row.start_col
+                    && row.row >= child.top_left.0
+                    && row.row <= child.bottom_right.0
+            });
+
             // Enforce 1-space padding: start at left+1, end at right-1
-            row.start_col = b.top_left.1 + 1;
+            if !follows_child {
+                row.start_col = b.top_left.1 + 1;
+            }
             row.end_col = b.bottom_right.1 - 1;
         }
     }
@@ -191,6 +202,39 @@ pub fn normalize_padding(inventory: &PrimitiveInventory) -> PrimitiveInventory {
     normalized
 }
 
+/// Give text rows at least `padding` spaces after the left border of their box.
+///
+/// Rows that follow a nested box keep their position; rows already indented
+/// further keep their indentation. Pair with [`normalize_box_widths_padded`]
+/// for the padding before the right border.
+#[must_use]
+pub fn pad_text_rows(inventory: &PrimitiveInventory, padding: usize) -> PrimitiveInventory {
+    let mut normalized = inventory.clone();
+    for row in &mut normalized.text_rows {
+        let Some(owner) = owning_box(&normalized.boxes, row) else {
+            continue;
+        };
+        if row.start_col != normalized.boxes[owner].top_left.1 + 1 {
+            continue;
+        }
+        let leading = row.content.chars().take_while(|c| *c == ' ').count();
+        if leading < padding {
+            row.content.insert_str(0, &" ".repeat(padding - leading));
+        }
+    }
+    normalized
+}
+
+/// Redraw every box in the given style.
+#[must_use]
+pub fn restyle_boxes(inventory: &PrimitiveInventory, style: BoxStyle) -> PrimitiveInventory {
+    let mut normalized = inventory.clone();
+    for b in &mut normalized.boxes {
+        b.style = style;
+    }
+    normalized
+}
+
 /// Align vertical arrows to box column positions.
 ///
 /// Algorithm:
@@ -246,47 +290,184 @@ fn find_alignment_column(boxes: &[crate::primitives::Box], arrow_col: usize) ->
     best_box.map(|b| usize::midpoint(b.top_left.1, b.bottom_right.1))
 }
 
+/// Find the innermost box owning a text row (smallest box containing its start).
+fn owning_box(boxes: &[DiagramBox], row: &crate::primitives::TextRow) -> Option<usize> {
+    boxes
+        .iter()
+        .enumerate()
+        .filter(|(_, b)| {
+            row.row > b.top_left.0
+                && row.row < b.bottom_right.0
+                && row.start_col >= b.top_left.1
+                && row.start_col <= b.bottom_right.1
+        })
+        .min_by_key(|(_, b)| b.width() * b.height())
+        .map(|(idx, _)| idx)
+}
+
+/// Check if box `ancestor` is reachable from box `idx` through `parent_idx` links.
+fn is_ancestor(boxes: &[DiagramBox], ancestor: usize, idx: usize) -> bool {
+    let mut current = boxes[idx].parent_idx;
+    // Bound the walk so a malformed inventory cannot loop forever
+    for _ in 0..boxes.len() {
+        match current {
+            Some(p) if p == ancestor => return true,
+            Some(p) => current = boxes[p].parent_idx,
+            None => return false,
+        }
+    }
+    false
+}
+
+/// Nesting depth of a box (0 for top-level boxes).
+fn nesting_depth(boxes: &[DiagramBox], idx: usize) -> usize {
+    (0..boxes.len())
+        .filter(|&other| other != idx && is_ancestor(boxes, other, idx))
+        .count()
+}
+
+/// Shift a box, its descendants and the text rows they own to the right.
+fn shift_box_right(
+    normalized: &mut PrimitiveInventory,
+    owners: &[Option<usize>],
+    idx: usize,
+    delta: usize,
+) {
+    let moved: Vec<usize> = (0..normalized.boxes.len())
+        .filter(|&other| other == idx || is_ancestor(&normalized.boxes, idx, other))
+        .collect();
+
+    for &b in &moved {
+        normalized.boxes[b].top_left.1 += delta;
+        normalized.boxes[b].bottom_right.1 += delta;
+    }
+    for (row, owner) in normalized.text_rows.iter_mut().zip(owners) {
+        if owner.is_some_and(|o| moved.contains(&o)) {
+            row.start_col += delta;
+            row.end_col += delta;
+        }
+    }
+}
+
 /// Normalize box widths to fit their content.
 ///
 /// Algorithm:
 /// 1. For each box, find the longest interior text row
 /// 2. Calculate required width (content + 2 for borders + padding)
 /// 3. Expand box if necessary
+///
+/// Nested boxes are processed innermost first. When a child grows, the
+/// siblings and parent text to its right on the same rows move with it, and
+/// the growth propagates outward through `parent_idx` so every ancestor keeps
+/// enclosing its children.
 #[allow(dead_code)] // Reason: Used by main processing pipeline
 #[must_use]
 pub fn normalize_box_widths(inventory: &PrimitiveInventory) -> PrimitiveInventory {
+    normalize_box_widths_padded(inventory, 0)
+}
+
+/// Normalize box widths to fit their content plus `padding` columns before the
+/// right border (see [`normalize_box_widths`]).
+#[must_use]
+pub fn normalize_box_widths_padded(
+    inventory: &PrimitiveInventory,
+    padding: usize,
+) -> PrimitiveInventory {
     let mut normalized = inventory.clone();
+    let owners: Vec<Option<usize>> = normalized
+        .text_rows
+        .iter()
+        .map(|row| owning_box(&normalized.boxes, row))
+        .collect();
 
-    for b in &mut normalized.boxes {
-        // Find longest text row inside this box
-        let max_content_len = normalized
+    let mut order: Vec<usize> = (0..normalized.boxes.len()).collect();
+    order.sort_by_key(|&idx| std::cmp::Reverse(nesting_depth(&normalized.boxes, idx)));
+    let mut grown = vec![false; normalized.boxes.len()];
+
+    for idx in order {
+        let b = normalized.boxes[idx].clone();
+
+        // Find the right border position required by the text inside this box.
+        // Rows owned by a related (nested) box are handled by that box instead.
+        let mut required_right = normalized
             .text_rows
             .iter()
-            .filter(|row| row.row > b.top_left.0 && row.row < b.bottom_right.0)
-            .map(|row| row.content.trim_end().len())
+            .zip(&owners)
+            .filter(|(row, _)| row.row > b.top_left.0 && row.row < b.bottom_right.0)
+            .filter_map(|(row, owner)| {
+                let content_len = row.content.trim_end().chars().count();
+                if content_len == 0 {
+                    return None;
+                }
+                match *owner {
+                    Some(o) if o == idx => {
+                        Some(row.start_col.max(b.top_left.1 + 1) + content_len + padding)
+                    }
+                    Some(_) => None,
+                    // Required width: content + 2 for left/right borders
+                    None => Some(b.top_left.1 + content_len + 1 + padding),
+                }
+            })
             .max()
             .unwrap_or(0);
 
-        if max_content_len > 0 {
-            // Required width: content + 2 for left/right borders
-            let required_width = max_content_len + 2;
-            let current_width = b.width();
+        // Children that grew or moved need a 1-column margin inside this box
+        for &child in &b.child_indices {
+            if child < grown.len() && grown[child] {
+                required_right = required_right.max(normalized.boxes[child].bottom_right.1 + 2);
+            }
+        }
+
+        if required_right <= b.bottom_right.1 {
+            continue;
+        }
 
-            if required_width > current_width {
-                // Expand box to the right
-                let expansion = required_width - current_width;
-                b.bottom_right.1 += expansion;
+        // Expand box to the right
+        let old_right = b.bottom_right.1;
+        let expansion = required_right - old_right;
+        normalized.boxes[idx].bottom_right.1 = required_right;
+        grown[idx] = true;
+
+        // Make room inside the parent: siblings and parent text to the right move along
+        if let Some(parent) = b.parent_idx {
+            let overlaps_rows =
+                |top: usize, bottom: usize| !(bottom < b.top_left.0 || top > b.bottom_right.0);
+            let siblings: Vec<usize> = normalized.boxes[parent]
+                .child_indices
+                .iter()
+                .copied()
+                .filter(|&s| {
+                    let sibling = &normalized.boxes[s];
+                    s != idx
+                        && sibling.top_left.1 > old_right
+                        && overlaps_rows(sibling.top_left.0, sibling.bottom_right.0)
+                })
+                .collect();
+            for s in siblings {
+                shift_box_right(&mut normalized, &owners, s, expansion);
+                grown[s] = true;
+            }
+            for (row, owner) in normalized.text_rows.iter_mut().zip(&owners) {
+                if *owner == Some(parent)
+                    && row.start_col > old_right
+                    && overlaps_rows(row.row, row.row)
+                {
+                    row.start_col += expansion;
+                    row.end_col += expansion;
+                }
             }
         }
     }
 
     // Adjust text rows to match new box widths
-    for row in &mut normalized.text_rows {
-        if let Some(b) = normalized
-            .boxes
-            .iter()
-            .find(|box_| row.row > box_.top_left.0 && row.row < box_.bottom_right.0)
-        {
+    for (row, owner) in normalized.text_rows.iter_mut().zip(&owners) {
+        let b = owner.map(|o| &normalized.boxes[o]).or_else(|| {
+            normalized
+                .boxes
+                .iter()
+                .find(|box_| row.row > box_.top_left.0 && row.row < box_.bottom_right.0)
+        });
+        if let Some(b) = b {
             row.end_col = b.bottom_right.1 - 1;
         }
     }
@@ -1445,6 +1626,87 @@ mod tests {
         assert_eq!(norm1.boxes, norm2.boxes);
     }
 
+    #[test]
+    fn test_child_growth_propagates_to_siblings_and_parent() {
+        let mut inventory = PrimitiveInventory::default();
+        inventory.boxes.push(DiagramBox {
+            top_left: (0, 0),
+            bottom_right: (6, 18),
+            style: BoxStyle::Single,
+            parent_idx: None,
+            child_indices: vec![1, 2],
+        });
+        inventory.boxes.push(DiagramBox {
+            top_left: (2, 2),
+            bottom_right: (4, 8),
+            style: BoxStyle::Single,
+            parent_idx: Some(0),
+            child_indices: Vec::new(),
+        });
+        inventory.boxes.push(DiagramBox {
+            top_left: (2, 10),
+            bottom_right: (4, 16),
+            style: BoxStyle::Single,
+            parent_idx: Some(0),
+            child_indices: Vec::new(),
+        });
+        // Overflowing text in the first child
+        inventory.text_rows.push(crate::primitives::TextRow {
+            row: 3,
+            start_col: 3,
+            end_col: 7,
+            content: " Long label ".to_string(),
+        });
+        // Parent text below the children is not affected
+        inventory.text_rows.push(crate::primitives::TextRow {
+            row: 5,
+            start_col: 1,
+            end_col: 17,
+            content: " Parent".to_string(),
+        });
+
+        let normalized = normalize_box_widths(&inventory);
+
+        // Child: " Long label" (11 chars) from col 3 needs its border at col 14
+        assert_eq!(normalized.boxes[1].bottom_right.1, 14);
+        // Sibling moved right by the same 6 columns
+        assert_eq!(normalized.boxes[2].top_left.1, 16);
+        assert_eq!(normalized.boxes[2].bottom_right.1, 22);
+        // Parent keeps a 1-column margin around the moved sibling
+        assert_eq!(normalized.boxes[0].bottom_right.1, 24);
+        assert_eq!(normalized.text_rows[1].start_col, 1);
+        assert_eq!(normalized.text_rows[1].end_col, 23);
+    }
+
+    #[test]
+    fn test_child_text_does_not_widen_parent_directly() {
+        let mut inventory = PrimitiveInventory::default();
+        inventory.boxes.push(DiagramBox {
+            top_left: (0, 0),
+            bottom_right: (4, 10),
+            style: BoxStyle::Single,
+            parent_idx: None,
+            child_indices: vec![1],
+        });
+        inventory.boxes.push(DiagramBox {
+            top_left: (1, 2),
+            bottom_right: (3, 8),
+            style: BoxStyle::Single,
+            parent_idx: Some(0),
+            child_indices: Vec::new(),
+        });
+        inventory.text_rows.push(crate::primitives::TextRow {
+            row: 2,
+            start_col: 3,
+            end_col: 7,
+            content: " ab  ".to_string(),
+        });
+
+        let normalized = normalize_box_widths(&inventory);
+        assert_eq!(normalized.boxes, inventory.boxes);
+        assert_eq!(normalized.text_rows[0].end_col, 7);
+    }
+
     #[test]
     fn test_normalize_labels_empty() {
         let inventory = PrimitiveInventory::default();
@@ -2,9 +2,14 @@
 
 #![allow(clippy::missing_const_for_fn)] // Reason: Methods modify self, clippy suggestion is incorrect
 
+use crate::transformation_analysis::{Transformation, TransformationSummary};
+use clap::ValueEnum;
 use colored::Colorize;
 use serde::{Deserialize, Serialize};
+use similar::udiff::UnifiedHunkHeader;
 use similar::{ChangeTag, TextDiff};
+use std::fmt::Write as _;
+use std::io::IsTerminal;
 use std::path::Path;
 
 /// Statistics about processing results
@@ -16,6 +21,12 @@ pub struct ProcessingStats {
     pub unchanged_files: usize,
     pub error_files: usize,
     pub skipped_files: usize,
+    /// Diagram blocks left unchanged because a repair would change their text
+    pub text_violations: usize,
+    /// Findings ignored because they are in the baseline
+    pub baselined: usize,
+    /// Baseline entries no longer found
+    pub stale_baseline: usize,
 }
 
 impl ProcessingStats {
@@ -48,6 +59,17 @@ impl ProcessingStats {
         self.skipped_files += 1;
     }
 
+    /// Record diagram blocks whose repair would have changed their text
+    pub fn record_text_violations(&mut self, count: usize) {
+        self.text_violations += count;
+    }
+
+    /// Record findings matched against the baseline
+    pub fn record_baseline(&mut self, known: usize, stale: usize) {
+        self.baselined += known;
+        self.stale_baseline += stale;
+    }
+
     /// Print summary to stderr
     pub fn print_summary(&self) {
         eprintln!("\n{}", "Summary:".bold());
@@ -68,6 +90,26 @@ impl ProcessingStats {
         if self.skipped_files > 0 {
             eprintln!("  {}: {}", "Skipped".yellow(), self.skipped_files);
         }
+
+        if self.text_violations > 0 {
+            eprintln!(
+                "  {}: {}",
+                "Blocks kept to preserve text".red(),
+                self.text_violations
+            );
+        }
+
+        if self.baselined > 0 {
+            eprintln!("  {}: {}", "Baselined findings".blue(), self.baselined);
+        }
+
+        if self.stale_baseline > 0 {
+            eprintln!(
+                "  {}: {}",
+                "Stale baseline entries".yellow(),
+                self.stale_baseline
+            );
+        }
     }
 }
 
@@ -78,11 +120,24 @@ pub enum FileResult {
     #[serde(rename = "modified")]
     Modified {
         file: String,
+        /// Counts of constructive, destructive and neutral changes
         #[serde(skip_serializing_if = "Option::is_none")]
-        transformations: Option<Vec<String>>,
+        summary: Option<TransformationSummary>,
+        /// Every classified change, with its location
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        transformations: Vec<Transformation>,
+        /// Blocks rewritten by each fixer, in lines of the original file
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        changes: Vec<crate::changes::ChangeRecord>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        diagram_blocks: Vec<crate::classifier::BlockScore>,
     },
     #[serde(rename = "unchanged")]
-    Unchanged { file: String },
+    Unchanged {
+        file: String,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        diagram_blocks: Vec<crate::classifier::BlockScore>,
+    },
     #[serde(rename = "error")]
     Error { file: String, error: String },
     #[serde(rename = "skipped")]
@@ -95,7 +150,7 @@ impl FileResult {
     pub fn file_path(&self) -> &str {
         match self {
             Self::Modified { file, .. }
-            | Self::Unchanged { file }
+            | Self::Unchanged { file, .. }
             | Self::Error { file, .. }
             | Self::Skipped { file, .. } => file,
         }
@@ -129,6 +184,9 @@ pub struct StatsOutput {
     pub unchanged: usize,
     pub errors: usize,
     pub skipped: usize,
+    pub text_violations: usize,
+    pub baselined: usize,
+    pub stale_baseline: usize,
 }
 
 impl From<&ProcessingStats> for StatsOutput {
@@ -139,23 +197,154 @@ impl From<&ProcessingStats> for StatsOutput {
             unchanged: stats.unchanged_files,
             errors: stats.error_files,
             skipped: stats.skipped_files,
+            text_violations: stats.text_violations,
+            baselined: stats.baselined,
+            stale_baseline: stats.stale_baseline,
         }
     }
 }
 
-/// Print a unified diff between original and processed content
-pub fn print_diff(path: &Path, original: &str, processed: &str) {
+/// When to color output, selected with `--color`.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorChoice {
+    /// Color when stdout is a terminal and `NO_COLOR` is not set
+    #[default]
+    Auto,
+    /// Always color
+    Always,
+    /// Never color
+    Never,
+}
+
+impl ColorChoice {
+    /// Whether output should be colored.
+    #[must_use]
+    pub fn enabled(self) -> bool {
+        match self {
+            Self::Always => true,
+            Self::Never => false,
+            Self::Auto => {
+                std::io::stdout().is_terminal()
+                    && std::env::var_os("NO_COLOR").is_none_or(|value| value.is_empty())
+            }
+        }
+    }
+
+    /// Turn coloring of all output on or off.
+    pub fn apply(self) {
+        colored::control::set_override(self.enabled());
+    }
+}
+
+/// Unified diff between original and processed content, with `context`
+/// unchanged lines around each hunk, that `git apply` accepts.
+///
+/// Hunks that only add or remove the final newline are left out; empty when
+/// nothing else changed.
+#[must_use]
+pub fn unified_diff(path: &Path, original: &str, processed: &str, context: usize) -> String {
     let diff = TextDiff::from_lines(original, processed);
+    let mut out = String::new();
+    for ops in &diff.grouped_ops(context) {
+        let hunk_changes = || ops.iter().flat_map(|op| diff.iter_changes(op));
+        let changed = |tag| -> String {
+            hunk_changes()
+                .filter(|change| change.tag() == tag)
+                .map(|change| change.value())
+                .collect()
+        };
+        // A hunk that only adds or removes the final newline is noise, but
+        // removed blank lines at the end of the file are not
+        let (deleted, inserted) = (changed(ChangeTag::Delete), changed(ChangeTag::Insert));
+        let only_final_newline = deleted.strip_suffix('\n') == Some(inserted.as_str())
+            || inserted.strip_suffix('\n') == Some(deleted.as_str());
+        if only_final_newline && hunk_changes().any(|change| change.missing_newline()) {
+            continue;
+        }
+        if out.is_empty() {
+            let path = patch_path(path);
+            let _ = write!(out, "--- a/{path}\n+++ b/{path}\n");
+        }
+        let _ = writeln!(out, "{}", UnifiedHunkHeader::new(ops));
+        for change in ops.iter().flat_map(|op| diff.iter_changes(op)) {
+            let sign = match change.tag() {
+                ChangeTag::Delete => '-',
+                ChangeTag::Insert => '+',
+                ChangeTag::Equal => ' ',
+            };
+            out.push(sign);
+            out.push_str(change.value());
+            if change.missing_newline() {
+                out.push_str("\n\\ No newline at end of file\n");
+            }
+        }
+    }
+    out
+}
+
+/// Path as written in a patch: relative to the current directory when
+/// possible, with `/` separators and no leading `./` or `/`.
+fn patch_path(path: &Path) -> String {
+    let relative = std::env::current_dir()
+        .ok()
+        .and_then(|cwd| path.strip_prefix(cwd).ok().map(Path::to_path_buf))
+        .unwrap_or_else(|| path.to_path_buf());
+    let path = relative.display().to_string().replace('\\', "/");
+    path.trim_start_matches("./")
+        .trim_start_matches('/')
+        .to_string()
+}
 
-    println!("{} {}", "diff".bold(), path.display().to_string().yellow());
+/// Print processed content, ending with exactly one newline.
+pub fn print_content(content: &str) {
+    if content.ends_with('\n') {
+        print!("{content}");
+    } else {
+        println!("{content}");
+    }
+}
 
-    for change in diff.iter_all_changes() {
-        let sign = match change.tag() {
-            ChangeTag::Delete => "-".red(),
-            ChangeTag::Insert => "+".green(),
-            ChangeTag::Equal => " ".normal(),
+/// Print a unified diff between original and processed content.
+pub fn print_diff(path: &Path, original: &str, processed: &str, context: usize) {
+    for line in unified_diff(path, original, processed, context).lines() {
+        let line = if line.starts_with("---") || line.starts_with("+++") {
+            line.bold()
+        } else if line.starts_with("@@") {
+            line.cyan()
+        } else if line.starts_with('-') {
+            line.red()
+        } else if line.starts_with('+') {
+            line.green()
+        } else {
+            line.normal()
         };
-        print!("{sign}{change}");
+        println!("{line}");
+    }
+}
+
+/// Print a short breakdown of the classified changes (verbose mode)
+pub fn log_transformations(summary: &TransformationSummary, transformations: &[Transformation]) {
+    log_verbose(&format!(
+        "  Changes: {} constructive, {} destructive, {} neutral (net impact {:+.2})",
+        summary.constructive_count,
+        summary.destructive_count,
+        summary.neutral_count,
+        summary.net_quality_impact
+    ));
+    let mut reasons: Vec<(&str, String, usize)> = Vec::new();
+    for transformation in transformations {
+        let kind = transformation.transform_type.kind();
+        let reason = transformation.transform_type.reason();
+        match reasons
+            .iter_mut()
+            .find(|(k, r, _)| *k == kind && *r == reason)
+        {
+            Some(entry) => entry.2 += 1,
+            None => reasons.push((kind, reason, 1)),
+        }
+    }
+    for (kind, reason, count) in reasons {
+        log_verbose(&format!("    {kind} {reason}: {count}"));
     }
 }
 
@@ -183,6 +372,47 @@ pub fn log_warning(message: &str) {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_unified_diff_has_headers_and_hunks() {
+        let original = (1..=20)
+            .map(|i| format!("line {i}"))
+            .collect::<Vec<_>>()
+            .join("\n")
+            + "\n";
+        let processed = original
+            .replace("line 2\n", "line two\n")
+            .replace("line 19\n", "line nineteen\n");
+        let diff = unified_diff(Path::new("./docs/a.md"), &original, &processed, 1);
+        assert_eq!(
+            diff,
+            "--- a/docs/a.md\n+++ b/docs/a.md\n\
+             @@ -1,3 +1,3 @@\n line 1\n-line 2\n+line two\n line 3\n\
+             @@ -18,3 +18,3 @@\n line 18\n-line 19\n+line nineteen\n line 20\n"
+        );
+
+        let diff = unified_diff(Path::new("a.md"), "a\nb", "a\nc", 3);
+        assert!(
+            diff.ends_with("-b\n\\ No newline at end of file\n+c\n\\ No newline at end of file\n")
+        );
+        assert!(unified_diff(Path::new("a.md"), "same\n", "same\n", 3).is_empty());
+        assert!(unified_diff(
+            Path::new("a.md"),
+            "# Title\n\nText.\n",
+            "# Title\n\nText.",
+            3
+        )
+        .is_empty());
+
+        // Trailing blank lines are a real change, not a missing newline
+        let diff = unified_diff(
+            Path::new("a.md"),
+            "# Title\n\nText\n\n",
+            "# Title\n\nText\n",
+            3,
+        );
+        assert!(diff.ends_with("@@ -1,4 +1,3 @@\n # Title\n \n Text\n-\n"));
+    }
+
     #[test]
     fn test_stats_tracking() {
         let mut stats = ProcessingStats::new();
@@ -205,7 +435,10 @@ mod tests {
     fn test_file_result_accessors() {
         let result = FileResult::Modified {
             file: "test.md".to_string(),
-            transformations: None,
+            summary: None,
+            transformations: Vec::new(),
+            changes: Vec::new(),
+            diagram_blocks: Vec::new(),
         };
 
         assert_eq!(result.file_path(), "test.md");
@@ -215,13 +448,45 @@ mod tests {
 
     #[test]
     fn test_json_serialization() {
+        let analysis = crate::transformation_analysis::analyze_transformations(
+            "┌──┐\n│abcd│\n└────┘",
+            "┌────┐\n│abcd│\n└────┘",
+        );
         let result = FileResult::Modified {
             file: "test.md".to_string(),
-            transformations: Some(vec!["box_expansion".to_string()]),
+            summary: Some(analysis.summary),
+            transformations: analysis.transformations,
+            changes: vec![crate::changes::ChangeRecord {
+                fixer: crate::changes::Fixer::Diagram,
+                rule: crate::rules::Rule::MisalignedBox,
+                start_line: 1,
+                end_line: 3,
+                reason: "diagram repaired".to_string(),
+                replacement: "┌────┐\n│abcd│\n└────┘".to_string(),
+            }],
+            diagram_blocks: Vec::new(),
         };
 
         let json = serde_json::to_string(&result).unwrap();
         assert!(json.contains("modified"));
+        assert!(json.contains(r#""changes":[{"fixer":"diagram","rule":"ASC303","start_line":1"#));
         assert!(json.contains("test.md"));
+        assert!(json.contains(r#""summary":{"destructive_count":0,"constructive_count":1"#));
+        assert!(json.contains(r#""reason":"border_correction""#));
+    }
+
+    #[test]
+    fn test_json_includes_diagram_scores() {
+        let result = FileResult::Unchanged {
+            file: "test.md".to_string(),
+            diagram_blocks: vec![crate::classifier::BlockScore {
+                line: 3,
+                score: 0.25,
+                processed: false,
+            }],
+        };
+
+        let json = serde_json::to_string(&result).unwrap();
+        assert!(json.contains(r#""diagram_blocks":[{"line":3,"score":0.25,"processed":false}]"#));
     }
 }
@@ -1,15 +1,422 @@
 //! Markdown parsing for detecting and extracting diagram blocks.
 
+use crate::config::ProtectConfig;
+
 /// Represents a line of Markdown and whether it's inside a code fence or ignore block.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-#[allow(dead_code)] // Reason: Useful type for semantic clarity, used in tests
-enum LineContext {
+pub enum LineContext {
     /// Outside any code fence or ignore block
     Normal,
     /// Inside a code fence
     InCodeFence,
+    /// Inside an indented code block (4+ columns past the enclosing content column)
+    InIndentedCode,
     /// Inside an ignore block (between ascfix:ignore markers)
     InIgnoreBlock,
+    /// YAML (`---`) or TOML (`+++`) front matter at the top of the file
+    InFrontMatter,
+    /// Inside a `$$` display math block
+    InMathBlock,
+    /// Inside a raw HTML block (`<table>`, `<details>`, comments...)
+    InHtmlBlock,
+    /// Between the `<pre>` and `</pre>` lines of an HTML `<pre>` block
+    InPreBlock,
+    /// MDX `import`/`export` statements or a JSX element
+    InMdx,
+    /// An `<!-- ascfix:... -->` directive comment other than the ignore region markers
+    Directive,
+}
+
+/// A code fence marker line (```` ``` ```` or `~~~`, 3 or more characters).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FenceMarker<'a> {
+    /// Fence character (`` ` `` or `~`)
+    pub fence_char: char,
+    /// Number of fence characters
+    pub length: usize,
+    /// Info string after the fence characters (language and attributes), trimmed
+    pub info: &'a str,
+}
+
+impl FenceMarker<'_> {
+    /// Check whether this marker closes a fence opened by `opening`: same
+    /// character, at least as long, and no info string.
+    #[must_use]
+    pub const fn closes(&self, opening: &FenceMarker<'_>) -> bool {
+        self.fence_char == opening.fence_char
+            && self.length >= opening.length
+            && self.info.is_empty()
+    }
+
+    /// The fence language (first word of the info string), if any.
+    #[must_use]
+    pub fn language(&self) -> Option<&str> {
+        self.info.split_whitespace().next()
+    }
+}
+
+/// Parse a line as a code fence marker.
+///
+/// This is the single fence rule shared by every pass: the line must start
+/// (after indentation) with 3+ backticks or tildes, and a backtick fence's info
+/// string may not contain backticks (that would be inline code).
+#[must_use]
+pub fn parse_fence_marker(line: &str) -> Option<FenceMarker<'_>> {
+    let trimmed = line.trim_start();
+    let fence_char = trimmed.chars().next().filter(|c| *c == '`' || *c == '~')?;
+    let length = trimmed.chars().take_while(|c| *c == fence_char).count();
+    if length < 3 {
+        return None;
+    }
+    let info = trimmed[length..].trim();
+    if fence_char == '`' && info.contains('`') {
+        return None;
+    }
+    Some(FenceMarker {
+        fence_char,
+        length,
+        info,
+    })
+}
+
+/// HTML tags whose blocks run until the matching closing tag (`CommonMark` type 1).
+const HTML_RAW_TAGS: &[&str] = &["pre", "script", "style", "textarea"];
+
+/// HTML tags that start a block running until the next blank line (`CommonMark` type 6).
+const HTML_BLOCK_TAGS: &[&str] = &[
+    "address",
+    "article",
+    "aside",
+    "blockquote",
+    "center",
+    "details",
+    "dialog",
+    "div",
+    "dl",
+    "fieldset",
+    "figcaption",
+    "figure",
+    "footer",
+    "form",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "header",
+    "hr",
+    "li",
+    "main",
+    "nav",
+    "ol",
+    "p",
+    "section",
+    "summary",
+    "table",
+    "tbody",
+    "td",
+    "tfoot",
+    "th",
+    "thead",
+    "tr",
+    "ul",
+];
+
+/// A protected non-Markdown block that is currently open.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum OpenBlock {
+    /// Front matter closed by one of the given delimiter lines
+    FrontMatter(&'static [&'static str]),
+    /// `$$` math block closed by a line ending in `$$`
+    Math,
+    /// HTML or JSX block closed by a line containing `closer`, or by a blank line
+    /// when there is none; `pre` marks the interior of a `<pre>` block
+    Html {
+        closer: Option<String>,
+        pre: bool,
+        context: LineContext,
+    },
+}
+
+/// Return the tag name if the line starts with an opening or closing HTML tag.
+fn html_tag_name(trimmed: &str) -> Option<&str> {
+    let rest = trimmed.strip_prefix('<')?;
+    let rest = rest.strip_prefix('/').unwrap_or(rest);
+    let len = rest
+        .find(|c: char| !c.is_ascii_alphanumeric() && c != '-' && c != '.')
+        .unwrap_or(rest.len());
+    let after = &rest[len..];
+    if len == 0 || !(after.is_empty() || after.starts_with([' ', '\t', '>', '/'])) {
+        return None;
+    }
+    Some(&rest[..len])
+}
+
+/// Check whether a line starts an MDX `import` or `export` statement, as
+/// opposed to prose such as "export the data first".
+fn is_esm_statement(line: &str) -> bool {
+    if let Some(rest) = line.strip_prefix("import ") {
+        return rest.starts_with(['{', '*', '\'', '"'])
+            || line.contains(" from '")
+            || line.contains(" from \"");
+    }
+    line.strip_prefix("export ").is_some_and(|rest| {
+        rest.starts_with(['{', '*'])
+            || [
+                "const ", "let ", "var ", "function", "async ", "class ", "default ",
+            ]
+            .iter()
+            .any(|keyword| rest.starts_with(keyword))
+    })
+}
+
+/// Check whether a line opens a protected block, given the remaining lines for lookahead.
+///
+/// Returns the context of the opening line and the block left open after it
+/// (`None` when the block is a single line).
+fn open_protected_block(
+    line: &str,
+    rest: &[&str],
+    protect: ProtectConfig,
+) -> Option<(LineContext, Option<OpenBlock>)> {
+    let trimmed = line.trim();
+
+    if protect.math && trimmed.starts_with("$$") {
+        let single_line = trimmed.len() > 2 && trimmed.ends_with("$$");
+        let open = (!single_line).then_some(OpenBlock::Math);
+        return Some((LineContext::InMathBlock, open));
+    }
+
+    if protect.html && trimmed.starts_with("<!--") {
+        let open = (!trimmed.contains("-->")).then(|| OpenBlock::Html {
+            closer: Some("-->".to_string()),
+            pre: false,
+            context: LineContext::InHtmlBlock,
+        });
+        return Some((LineContext::InHtmlBlock, open));
+    }
+
+    if protect.mdx && line == trimmed && is_esm_statement(trimmed) {
+        let open = OpenBlock::Html {
+            closer: None,
+            pre: false,
+            context: LineContext::InMdx,
+        };
+        return Some((LineContext::InMdx, Some(open)));
+    }
+
+    if protect.mdx && trimmed.starts_with("<>") {
+        let open = (!trimmed.contains("</>")).then(|| OpenBlock::Html {
+            closer: Some("</>".to_string()),
+            pre: false,
+            context: LineContext::InMdx,
+        });
+        return Some((LineContext::InMdx, open));
+    }
+
+    let name = html_tag_name(trimmed)?;
+    let is_closing = trimmed.starts_with("</");
+
+    if protect.mdx && name.starts_with(|c: char| c.is_ascii_uppercase()) {
+        // JSX element: runs to its closing tag if there is one, otherwise to a blank line
+        let closer = format!("</{name}>");
+        let closed_here = is_closing || trimmed.ends_with("/>") || trimmed.contains(&closer);
+        let open = if closed_here {
+            None
+        } else {
+            let has_closer = rest.iter().any(|l| l.contains(&closer));
+            Some(OpenBlock::Html {
+                closer: has_closer.then_some(closer),
+                pre: false,
+                context: LineContext::InMdx,
+            })
+        };
+        return Some((LineContext::InMdx, open));
+    }
+
+    if !protect.html {
+        return None;
+    }
+    let lower = name.to_ascii_lowercase();
+    if HTML_RAW_TAGS.contains(&lower.as_str()) && !is_closing {
+        let closer = format!("</{lower}>");
+        let open = (!trimmed.to_ascii_lowercase().contains(&closer)).then(|| OpenBlock::Html {
+            closer: Some(closer),
+            pre: lower == "pre",
+            context: LineContext::InHtmlBlock,
+        });
+        return Some((LineContext::InHtmlBlock, open));
+    }
+    if HTML_BLOCK_TAGS.contains(&lower.as_str()) {
+        let open = OpenBlock::Html {
+            closer: None,
+            pre: false,
+            context: LineContext::InHtmlBlock,
+        };
+        return Some((LineContext::InHtmlBlock, Some(open)));
+    }
+    None
+}
+
+/// Classify a line inside an open protected block.
+///
+/// Returns the line's context and whether the block stays open after it.
+fn continue_protected_block(line: &str, block: &OpenBlock) -> (LineContext, bool) {
+    let trimmed = line.trim();
+    match block {
+        OpenBlock::FrontMatter(closers) => (
+            LineContext::InFrontMatter,
+            !closers.contains(&line.trim_end()),
+        ),
+        OpenBlock::Math => (LineContext::InMathBlock, !trimmed.ends_with("$$")),
+        OpenBlock::Html {
+            closer: Some(closer),
+            pre,
+            context,
+        } => {
+            if trimmed
+                .to_ascii_lowercase()
+                .contains(closer.to_ascii_lowercase().as_str())
+            {
+                (*context, false)
+            } else if *pre {
+                (LineContext::InPreBlock, true)
+            } else {
+                (*context, true)
+            }
+        }
+        OpenBlock::Html {
+            closer: None,
+            context,
+            ..
+        } => {
+            if trimmed.is_empty() {
+                (LineContext::Normal, false)
+            } else {
+                (*context, true)
+            }
+        }
+    }
+}
+
+/// Detect front matter at the very top of the file.
+///
+/// Returns the closing delimiters if the first line opens front matter that is closed later.
+fn front_matter_closers(lines: &[&str]) -> Option<&'static [&'static str]> {
+    let closers: &'static [&'static str] = match lines.first()?.trim_end() {
+        "---" => &["---", "..."],
+        "+++" => &["+++"],
+        _ => return None,
+    };
+    lines[1..]
+        .iter()
+        .any(|l| closers.contains(&l.trim_end()))
+        .then_some(closers)
+}
+
+/// Width of the leading whitespace of a line in columns (tabs stop every 4 columns).
+fn visual_indent(line: &str) -> usize {
+    let mut width = 0;
+    for ch in line.chars() {
+        match ch {
+            ' ' => width += 1,
+            '\t' => width += 4 - width % 4,
+            _ => break,
+        }
+    }
+    width
+}
+
+/// If the line starts a list item, return the column where its content begins.
+///
+/// Follows the `CommonMark` rule: content starts after the marker and 1-4 spaces;
+/// with 5+ spaces (indented code inside the item) or nothing after the marker,
+/// it starts one column after the marker.
+#[must_use]
+pub fn list_content_column(line: &str) -> Option<usize> {
+    let indent = visual_indent(line);
+    let rest = line.trim_start();
+    let marker_len = if rest.starts_with(['-', '*', '+']) {
+        1
+    } else {
+        let digits = rest.chars().take_while(char::is_ascii_digit).count();
+        if digits == 0 || digits > 9 || !rest[digits..].starts_with(['.', ')']) {
+            return None;
+        }
+        digits + 1
+    };
+
+    let after = &rest[marker_len..];
+    if after.trim().is_empty() {
+        return Some(indent + marker_len + 1);
+    }
+    let spaces = after.chars().take_while(|c| *c == ' ').count();
+    match spaces {
+        0 => None,
+        1..=4 => Some(indent + marker_len + spaces),
+        _ => Some(indent + marker_len + 1),
+    }
+}
+
+/// Tracks the state needed to recognize indented code blocks.
+#[derive(Debug)]
+struct IndentedCodeTracker {
+    /// Whether the previous line was blank (indented code cannot interrupt a paragraph)
+    prev_blank: bool,
+    /// Whether the previous non-blank line was indented code
+    in_code: bool,
+    /// Content columns of the enclosing list items, innermost last
+    list_columns: Vec<usize>,
+}
+
+impl IndentedCodeTracker {
+    const fn new() -> Self {
+        Self {
+            prev_blank: true,
+            in_code: false,
+            list_columns: Vec::new(),
+        }
+    }
+
+    /// Classify a line outside fences and ignore blocks, returning true if it
+    /// belongs to an indented code block.
+    fn is_indented_code(&mut self, line: &str) -> bool {
+        if line.trim().is_empty() {
+            self.prev_blank = true;
+            return false;
+        }
+
+        let indent = visual_indent(line);
+        // After a blank line, a less indented line leaves the list items it no longer belongs to
+        if self.prev_blank {
+            while self.list_columns.last().is_some_and(|&col| indent < col) {
+                self.list_columns.pop();
+            }
+        }
+
+        let base = self.list_columns.last().copied().unwrap_or(0);
+        let is_code = (self.prev_blank || self.in_code) && indent >= base + 4;
+        self.prev_blank = false;
+        self.in_code = is_code;
+        if is_code {
+            return true;
+        }
+
+        if let Some(col) = list_content_column(line) {
+            while self.list_columns.last().is_some_and(|&c| c > indent) {
+                self.list_columns.pop();
+            }
+            self.list_columns.push(col);
+        }
+        false
+    }
+
+    /// Reset after a fence or ignore block, which ends any indented code block.
+    const fn interrupt(&mut self) {
+        self.prev_blank = false;
+        self.in_code = false;
+    }
 }
 
 /// Check if a line contains an ascfix ignore start marker.
@@ -17,7 +424,6 @@ enum LineContext {
 /// Supports:
 /// - `<!-- ascfix:ignore -->`
 /// - `<!-- ascfix-ignore-start -->`
-#[allow(dead_code)] // Reason: Used by parse_line_contexts
 fn is_ignore_start(line: &str) -> bool {
     let trimmed = line.trim();
     trimmed.contains("<!-- ascfix:ignore -->") || trimmed.contains("<!-- ascfix-ignore-start -->")
@@ -28,78 +434,113 @@ fn is_ignore_start(line: &str) -> bool {
 /// Supports:
 /// - `<!-- /ascfix:ignore -->`
 /// - `<!-- ascfix-ignore-end -->`
-#[allow(dead_code)] // Reason: Used by parse_line_contexts
 fn is_ignore_end(line: &str) -> bool {
     let trimmed = line.trim();
     trimmed.contains("<!-- /ascfix:ignore -->") || trimmed.contains("<!-- ascfix-ignore-end -->")
 }
 
-/// Parse Markdown content and return lines with their context (inside/outside code fences and ignore blocks).
-#[allow(dead_code)] // Reason: Used by extract_normal_lines and tests
-fn parse_line_contexts(text: &str) -> Vec<(usize, &str, LineContext)> {
+/// Classify every line, including fence and ignore marker lines.
+///
+/// Returns the line number, the line, its context and whether it is a marker line.
+fn classify_lines(text: &str, protect: ProtectConfig) -> Vec<(usize, &str, LineContext, bool)> {
     let mut result = Vec::new();
-    let mut in_fence = false;
+    let mut open_fence: Option<FenceMarker> = None;
     let mut in_ignore = false;
+    let mut indented = IndentedCodeTracker::new();
+    let lines: Vec<&str> = text.lines().collect();
+
+    let mut open_block = if protect.front_matter {
+        front_matter_closers(&lines).map(OpenBlock::FrontMatter)
+    } else {
+        None
+    };
+    if open_block.is_some() {
+        result.push((0, lines[0], LineContext::InFrontMatter, false));
+    }
 
-    for (line_num, line) in text.lines().enumerate() {
+    for (line_num, &line) in lines.iter().enumerate().skip(result.len()) {
         // Check for ignore markers first (they take precedence)
         if is_ignore_start(line) {
             in_ignore = true;
-            // Ignore marker lines are skipped entirely
+            indented.interrupt();
+            result.push((line_num, line, LineContext::InIgnoreBlock, true));
             continue;
         } else if is_ignore_end(line) {
             in_ignore = false;
-            // Ignore marker lines are skipped entirely
+            indented.interrupt();
+            result.push((line_num, line, LineContext::InIgnoreBlock, true));
             continue;
         }
 
         // If we're in an ignore block, mark all lines as ignored
         if in_ignore {
-            result.push((line_num, line, LineContext::InIgnoreBlock));
+            result.push((line_num, line, LineContext::InIgnoreBlock, false));
             continue;
         }
 
-        // Count code fence markers (``` or ~~~)
-        let backtick_count = line.matches("```").count();
-        let tilde_count = line.matches("~~~").count();
-        let is_fence_marker = backtick_count > 0 || tilde_count > 0;
+        // Protected blocks run to their own closer; fences inside them are literal
+        if let Some(block) = &open_block {
+            let (line_ctx, still_open) = continue_protected_block(line, block);
+            if !still_open {
+                open_block = None;
+            }
+            if line_ctx != LineContext::Normal {
+                indented.interrupt();
+                result.push((line_num, line, line_ctx, false));
+                continue;
+            }
+        }
 
-        // Toggle fence state if we encounter an odd number of markers
-        if backtick_count % 2 == 1 || tilde_count % 2 == 1 {
-            in_fence = !in_fence;
+        // Code fence markers (``` or ~~~) are checked before protected blocks
+        // so that a fence can hold HTML or `$$`
+        let marker = parse_fence_marker(line);
+        let is_fence_marker = marker.is_some();
+        if let Some(marker) = marker {
+            match open_fence {
+                None => open_fence = Some(marker),
+                Some(open) if marker.closes(&open) => open_fence = None,
+                Some(_) => {}
+            }
         }
+        let in_fence = open_fence.is_some();
 
         // Determine context: fence markers are always skipped, interior lines stay in fence
-        let line_ctx = if is_fence_marker {
-            // Fence marker lines are skipped entirely
-            continue;
-        } else if in_fence {
+        let line_ctx = if is_fence_marker || in_fence {
+            indented.interrupt();
             LineContext::InCodeFence
+        } else if crate::directives::is_directive(line) {
+            indented.interrupt();
+            result.push((line_num, line, LineContext::Directive, true));
+            continue;
+        } else if indented.is_indented_code(line) {
+            LineContext::InIndentedCode
+        } else if let Some((line_ctx, open)) =
+            open_protected_block(line, &lines[line_num + 1..], protect)
+        {
+            indented.interrupt();
+            open_block = open;
+            line_ctx
         } else {
             LineContext::Normal
         };
 
-        result.push((line_num, line, line_ctx));
+        result.push((line_num, line, line_ctx, is_fence_marker));
     }
 
     result
 }
 
-/// Extract all normal (non-code-fence, non-ignored) lines from Markdown content.
+/// Return the context of every line of the content, one entry per line,
+/// recognizing only the protected block kinds enabled in `protect`.
 ///
-/// Lines inside code fences and ignore blocks are filtered out.
-#[allow(dead_code)] // Reason: Used by main processing pipeline
+/// Fence marker lines are reported as [`LineContext::InCodeFence`] and ignore
+/// markers as [`LineContext::InIgnoreBlock`], so passes that rewrite lines can
+/// skip anything that is not [`LineContext::Normal`].
 #[must_use]
-pub fn extract_normal_lines(text: &str) -> Vec<(usize, String)> {
-    parse_line_contexts(text)
+pub fn line_contexts(text: &str, protect: ProtectConfig) -> Vec<LineContext> {
+    classify_lines(text, protect)
         .into_iter()
-        .filter_map(|(line_num, line, line_ctx)| {
-            if line_ctx == LineContext::Normal {
-                Some((line_num, line.to_string()))
-            } else {
-                None
-            }
-        })
+        .map(|(_, _, line_ctx, _)| line_ctx)
         .collect()
 }
 
@@ -107,10 +548,24 @@ pub fn extract_normal_lines(text: &str) -> Vec<(usize, String)> {
 mod tests {
     use super::*;
 
+    fn contexts_of(text: &str) -> Vec<LineContext> {
+        line_contexts(text, ProtectConfig::default())
+    }
+
+    /// The lines outside code, ignore and protected blocks, with their line numbers.
+    fn normal_lines(text: &str) -> Vec<(usize, String)> {
+        text.lines()
+            .zip(contexts_of(text))
+            .enumerate()
+            .filter(|(_, (_, line_ctx))| *line_ctx == LineContext::Normal)
+            .map(|(line_num, (line, _))| (line_num, line.to_string()))
+            .collect()
+    }
+
     #[test]
     fn test_ignores_content_in_backtick_fence() {
         let markdown = "# Header\n\n```\ncode line\nmore code\n```\n\nAfter fence";
-        let normal_lines = extract_normal_lines(markdown);
+        let normal_lines = normal_lines(markdown);
 
         let line_texts: Vec<&str> = normal_lines.iter().map(|(_, l)| l.as_str()).collect();
         assert!(!line_texts.contains(&"code line"));
@@ -122,7 +577,7 @@ mod tests {
     #[test]
     fn test_ignores_content_in_tilde_fence() {
         let markdown = "Before\n\n~~~\nfenced content\n~~~\n\nAfter";
-        let normal_lines = extract_normal_lines(markdown);
+        let normal_lines = normal_lines(markdown);
 
         let line_texts: Vec<&str> = normal_lines.iter().map(|(_, l)| l.as_str()).collect();
         assert!(!line_texts.contains(&"fenced content"));
@@ -133,7 +588,7 @@ mod tests {
     #[test]
     fn test_preserves_line_numbers() {
         let markdown = "Line 0\n\n```\nLine 3 (hidden)\n```\n\nLine 6";
-        let normal_lines = extract_normal_lines(markdown);
+        let normal_lines = normal_lines(markdown);
 
         let line_nums: Vec<usize> = normal_lines.iter().map(|(num, _)| *num).collect();
         // Lines 2 and 4 are fence markers and are skipped
@@ -144,7 +599,7 @@ mod tests {
     fn test_nested_triple_backticks_in_content() {
         // Triple backticks in text should toggle fence state
         let markdown = "text\n```\nhidden\n```\nvisible";
-        let normal_lines = extract_normal_lines(markdown);
+        let normal_lines = normal_lines(markdown);
 
         let line_texts: Vec<&str> = normal_lines.iter().map(|(_, l)| l.as_str()).collect();
         assert!(line_texts.contains(&"text"));
@@ -155,7 +610,7 @@ mod tests {
     #[test]
     fn test_multiple_code_blocks() {
         let markdown = "Before\n\n```\nBlock1\n```\n\nMiddle\n\n```\nBlock2\n```\n\nAfter";
-        let normal_lines = extract_normal_lines(markdown);
+        let normal_lines = normal_lines(markdown);
 
         let line_texts: Vec<&str> = normal_lines.iter().map(|(_, l)| l.as_str()).collect();
         assert!(!line_texts.contains(&"Block1"));
@@ -168,14 +623,14 @@ mod tests {
     #[test]
     fn test_empty_markdown() {
         let markdown = "";
-        let normal_lines = extract_normal_lines(markdown);
+        let normal_lines = normal_lines(markdown);
         assert!(normal_lines.is_empty());
     }
 
     #[test]
     fn test_no_code_blocks() {
         let markdown = "# Header\n\nSome text\nMore text";
-        let normal_lines = extract_normal_lines(markdown);
+        let normal_lines = normal_lines(markdown);
         assert_eq!(normal_lines.len(), 4);
     }
 
@@ -183,7 +638,7 @@ mod tests {
     fn test_ignore_block_basic() {
         let markdown =
             "Before\n<!-- ascfix:ignore -->\nIgnored content\n<!-- /ascfix:ignore -->\nAfter";
-        let normal_lines = extract_normal_lines(markdown);
+        let normal_lines = normal_lines(markdown);
 
         let line_texts: Vec<&str> = normal_lines.iter().map(|(_, l)| l.as_str()).collect();
         assert!(line_texts.contains(&"Before"));
@@ -195,7 +650,7 @@ mod tests {
     fn test_ignore_block_alternative_syntax() {
         let markdown =
             "Before\n<!-- ascfix-ignore-start -->\nIgnored\n<!-- ascfix-ignore-end -->\nAfter";
-        let normal_lines = extract_normal_lines(markdown);
+        let normal_lines = normal_lines(markdown);
 
         let line_texts: Vec<&str> = normal_lines.iter().map(|(_, l)| l.as_str()).collect();
         assert!(line_texts.contains(&"Before"));
@@ -206,7 +661,7 @@ mod tests {
     #[test]
     fn test_ignore_block_multiple() {
         let markdown = "Text1\n<!-- ascfix:ignore -->\nIgnored1\n<!-- /ascfix:ignore -->\nText2\n<!-- ascfix:ignore -->\nIgnored2\n<!-- /ascfix:ignore -->\nText3";
-        let normal_lines = extract_normal_lines(markdown);
+        let normal_lines = normal_lines(markdown);
 
         let line_texts: Vec<&str> = normal_lines.iter().map(|(_, l)| l.as_str()).collect();
         assert!(line_texts.contains(&"Text1"));
@@ -220,7 +675,7 @@ mod tests {
     fn test_ignore_block_multiline() {
         let markdown =
             "Before\n<!-- ascfix:ignore -->\nLine1\nLine2\nLine3\n<!-- /ascfix:ignore -->\nAfter";
-        let normal_lines = extract_normal_lines(markdown);
+        let normal_lines = normal_lines(markdown);
 
         let line_texts: Vec<&str> = normal_lines.iter().map(|(_, l)| l.as_str()).collect();
         assert_eq!(line_texts.len(), 2);
@@ -232,7 +687,7 @@ mod tests {
     fn test_ignore_block_with_code_fence() {
         let markdown =
             "Text\n<!-- ascfix:ignore -->\n```\ncode\n```\n<!-- /ascfix:ignore -->\nAfter";
-        let normal_lines = extract_normal_lines(markdown);
+        let normal_lines = normal_lines(markdown);
 
         let line_texts: Vec<&str> = normal_lines.iter().map(|(_, l)| l.as_str()).collect();
         assert!(line_texts.contains(&"Text"));
@@ -245,7 +700,7 @@ mod tests {
     fn test_code_fence_with_ignore_markers_inside() {
         let markdown =
             "Before\n```\n<!-- ascfix:ignore -->\ncode\n<!-- /ascfix:ignore -->\n```\nAfter";
-        let normal_lines = extract_normal_lines(markdown);
+        let normal_lines = normal_lines(markdown);
 
         let line_texts: Vec<&str> = normal_lines.iter().map(|(_, l)| l.as_str()).collect();
         assert!(line_texts.contains(&"Before"));
@@ -281,7 +736,7 @@ mod tests {
     fn test_ignore_block_preserves_line_numbers() {
         let markdown =
             "Line0\n<!-- ascfix:ignore -->\nLine2\nLine3\n<!-- /ascfix:ignore -->\nLine5";
-        let normal_lines = extract_normal_lines(markdown);
+        let normal_lines = normal_lines(markdown);
 
         let line_nums: Vec<usize> = normal_lines.iter().map(|(num, _)| *num).collect();
         // Line 0 and Line 5 should be present
@@ -292,10 +747,127 @@ mod tests {
     fn test_unclosed_ignore_block() {
         // If an ignore block is not closed, everything after it should be ignored
         let markdown = "Before\n<!-- ascfix:ignore -->\nIgnored1\nIgnored2\nAlso ignored";
-        let normal_lines = extract_normal_lines(markdown);
+        let normal_lines = normal_lines(markdown);
 
         let line_texts: Vec<&str> = normal_lines.iter().map(|(_, l)| l.as_str()).collect();
         assert_eq!(line_texts.len(), 1);
         assert!(line_texts.contains(&"Before"));
     }
+
+    #[test]
+    fn test_indented_code_after_blank_line() {
+        let markdown = "Text\n\n    ┌──┐\n    │ab│\n\n    └──┘\nAfter";
+        let contexts = contexts_of(markdown);
+        assert_eq!(contexts[0], LineContext::Normal);
+        assert_eq!(contexts[2], LineContext::InIndentedCode);
+        assert_eq!(contexts[3], LineContext::InIndentedCode);
+        assert_eq!(contexts[5], LineContext::InIndentedCode);
+        assert_eq!(contexts[6], LineContext::Normal);
+    }
+
+    #[test]
+    fn test_indented_line_cannot_interrupt_paragraph() {
+        let contexts = contexts_of("Paragraph\n    still paragraph");
+        assert_eq!(contexts, vec![LineContext::Normal, LineContext::Normal]);
+    }
+
+    #[test]
+    fn test_indented_code_respects_list_content_column() {
+        // Item content starts at column 3, so 4 spaces is a paragraph, 7 is code
+        let markdown = "1. Item\n\n    continued\n\n       code\n\nAfter\n\n    top-level code";
+        let contexts = contexts_of(markdown);
+        assert_eq!(contexts[2], LineContext::Normal);
+        assert_eq!(contexts[4], LineContext::InIndentedCode);
+        assert_eq!(contexts[6], LineContext::Normal);
+        assert_eq!(contexts[8], LineContext::InIndentedCode);
+    }
+
+    #[test]
+    fn test_line_contexts_include_markers() {
+        let contexts = contexts_of("```\ncode\n```\ntext");
+        assert_eq!(
+            contexts,
+            vec![
+                LineContext::InCodeFence,
+                LineContext::InCodeFence,
+                LineContext::InCodeFence,
+                LineContext::Normal
+            ]
+        );
+    }
+
+    #[test]
+    fn test_front_matter_protected() {
+        let markdown = "---\ntitle: x\n- not: a list\n---\n- item";
+        let contexts = contexts_of(markdown);
+        assert_eq!(&contexts[..4], &[LineContext::InFrontMatter; 4]);
+        assert_eq!(contexts[4], LineContext::Normal);
+
+        let toml = contexts_of("+++\ntitle = 'x'\n+++\ntext");
+        assert_eq!(toml[2], LineContext::InFrontMatter);
+        assert_eq!(toml[3], LineContext::Normal);
+
+        // A thematic break without a closing delimiter is not front matter
+        assert_eq!(contexts_of("---\ntext")[1], LineContext::Normal);
+    }
+
+    #[test]
+    fn test_math_block_protected() {
+        let contexts = contexts_of("$$\n| a | b |\n$$\n$$x^2$$\nafter");
+        assert_eq!(&contexts[..4], &[LineContext::InMathBlock; 4]);
+        assert_eq!(contexts[4], LineContext::Normal);
+    }
+
+    #[test]
+    fn test_html_blocks_protected() {
+        let markdown = "<details>\n- item\n\n- real\n<pre>\n┌─┐\n\n└─┘\n</pre>\n<!--\nnote\n-->";
+        let contexts = contexts_of(markdown);
+        assert_eq!(&contexts[..2], &[LineContext::InHtmlBlock; 2]);
+        assert_eq!(contexts[2], LineContext::Normal);
+        assert_eq!(contexts[3], LineContext::Normal);
+        assert_eq!(contexts[4], LineContext::InHtmlBlock);
+        assert_eq!(&contexts[5..8], &[LineContext::InPreBlock; 3]);
+        assert_eq!(contexts[8], LineContext::InHtmlBlock);
+        assert_eq!(&contexts[9..], &[LineContext::InHtmlBlock; 3]);
+    }
+
+    #[test]
+    fn test_mdx_protected() {
+        let markdown = "import Tabs from './Tabs'\n\n<Tabs>\n\n| a |\n\n</Tabs>\n<Note />\ntext";
+        let contexts = contexts_of(markdown);
+        assert_eq!(contexts[0], LineContext::InMdx);
+        assert_eq!(contexts[1], LineContext::Normal);
+        assert_eq!(&contexts[2..8], &[LineContext::InMdx; 6]);
+        assert_eq!(contexts[8], LineContext::Normal);
+    }
+
+    #[test]
+    fn test_prose_starting_with_import_or_export_is_not_mdx() {
+        let markdown = "export the data first, then:\n| a | b |\n|---|---|\nimport it later.";
+        let contexts = contexts_of(markdown);
+        assert!(contexts.iter().all(|c| *c == LineContext::Normal));
+
+        for statement in [
+            "import { Tabs } from '@site/Tabs'",
+            "import './styles.css'",
+            "export const meta = { title: 'x' }",
+            "export default function Layout() {}",
+        ] {
+            assert_eq!(contexts_of(statement)[0], LineContext::InMdx, "{statement}");
+        }
+    }
+
+    #[test]
+    fn test_protection_toggles() {
+        let markdown = "---\na: 1\n---\n$$\nx\n$$\n<div>\nimport x from 'y'";
+        let protect = ProtectConfig {
+            front_matter: false,
+            math: false,
+            html: false,
+            mdx: false,
+            pre_as_text_fence: false,
+        };
+        let contexts = line_contexts(markdown, protect);
+        assert!(contexts.iter().all(|c| *c == LineContext::Normal));
+    }
 }
@@ -1,7 +1,10 @@
 //! Primitive types representing ASCII diagram elements.
 
+use serde::{Deserialize, Serialize};
+
 /// Box drawing style for different box types.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
 #[allow(dead_code)] // Reason: Part of public API for diagram rendering
 pub enum BoxStyle {
     /// Single-line boxes: ─ │ ┌ ┐ └ ┘
@@ -63,6 +66,17 @@ impl BoxStyle {
         }
     }
 
+    /// Parse a style name (`single`, `double` or `rounded`).
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "single" => Some(Self::Single),
+            "double" => Some(Self::Double),
+            "rounded" => Some(Self::Rounded),
+            _ => None,
+        }
+    }
+
     /// Determine box style from a corner character.
     ///
     /// Returns the style if the character is a corner of a box,
@@ -392,6 +406,72 @@ const fn is_vertical_arrow_char(ch: char) -> bool {
     matches!(ch, '↓' | '↑' | '⇓' | '⇑' | '⟱' | '⟰')
 }
 
+/// Check if a character carries a vertical line downward (walls, top corners, tees, arrows,
+/// and ASCII `|` and `+`).
+#[must_use]
+pub const fn continues_down(ch: char) -> bool {
+    matches!(
+        ch,
+        '│' | '║'
+            | '┃'
+            | '┌'
+            | '┐'
+            | '╔'
+            | '╗'
+            | '╭'
+            | '╮'
+            | '├'
+            | '┤'
+            | '┬'
+            | '┼'
+            | '╠'
+            | '╣'
+            | '╦'
+            | '╬'
+            | '↓'
+            | '⇓'
+            | '▼'
+            | '↑'
+            | '⇑'
+            | '▲'
+            | '|'
+            | '+'
+    )
+}
+
+/// Check if a character carries a vertical line upward (walls, bottom corners, tees,
+/// arrows, and ASCII `|` and `+`).
+#[must_use]
+pub const fn continues_up(ch: char) -> bool {
+    matches!(
+        ch,
+        '│' | '║'
+            | '┃'
+            | '└'
+            | '┘'
+            | '╚'
+            | '╝'
+            | '╰'
+            | '╯'
+            | '├'
+            | '┤'
+            | '┴'
+            | '┼'
+            | '╠'
+            | '╣'
+            | '╩'
+            | '╬'
+            | '↓'
+            | '⇓'
+            | '▼'
+            | '↑'
+            | '⇑'
+            | '▲'
+            | '|'
+            | '+'
+    )
+}
+
 /// What a label is attached to (for tracking during normalization).
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[allow(dead_code)] // Reason: Used by label detection and normalization
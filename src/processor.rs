@@ -2,21 +2,35 @@
 
 use crate::cli::Args;
 use crate::discovery::FileDiscovery;
+use crate::fixes::FixRequest;
 use crate::io;
 use crate::output::{FileResult, ProcessingResults, ProcessingStats, StatsOutput};
 use anyhow::Result;
-use std::path::Path;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 
 /// Exit code for when check mode detects differences.
 pub const CHECK_FAILED_EXIT_CODE: i32 = 1;
 
+/// Exit code under `--strict` when a repair was refused because it would
+/// change the text of a block.
+pub const TEXT_CHANGED_EXIT_CODE: i32 = 2;
+
 /// Exit code for success.
 pub const SUCCESS_EXIT_CODE: i32 = 0;
 
+/// A discovered file with the configuration it is processed under.
+type DiscoveredFile = (PathBuf, Result<crate::config::Config>);
+
 /// Main processor for handling file transformations.
 pub struct Processor {
     args: Args,
+    /// Finds the configuration files that apply to each processed file
+    resolver: crate::config::ConfigResolver,
+    /// Base configuration with command-line flags applied (used for discovery)
     config: crate::config::Config,
+    /// Findings to ignore, from `--baseline`
+    baseline: Option<crate::baseline::Baseline>,
 }
 
 impl Processor {
@@ -24,11 +38,111 @@ impl Processor {
     ///
     /// # Errors
     ///
-    /// Returns an error if the configuration file cannot be loaded or parsed.
+    /// Returns an error if the configuration file cannot be loaded or parsed,
+    /// including when it contains an unknown key, or if the `--baseline` file
+    /// cannot be read.
     pub fn new(args: Args) -> Result<Self> {
-        let config = crate::config::Config::load_from_cwd()
+        let resolver = Self::config_resolver(&args)?;
+        let cwd = std::env::current_dir()?;
+        if args.verbose {
+            for layer in resolver
+                .layers_for(&cwd)
+                .map_err(|e| anyhow::anyhow!("Failed to load config: {e}"))?
+            {
+                crate::output::log_verbose(&format!("Using config: {}", layer.source.display()));
+            }
+        }
+        let config = resolver
+            .resolve(&cwd)
             .map_err(|e| anyhow::anyhow!("Failed to load config: {e}"))?;
-        Ok(Self { args, config })
+        let config = Self::apply_args(config, &args);
+        let baseline = args
+            .baseline
+            .as_deref()
+            .map(crate::baseline::Baseline::load)
+            .transpose()?;
+        Ok(Self {
+            args,
+            resolver,
+            config,
+            baseline,
+        })
+    }
+
+    /// Configuration sources selected by `--config` / `--no-config`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the explicit or user configuration file is invalid.
+    pub(crate) fn config_resolver(args: &Args) -> Result<crate::config::ConfigResolver> {
+        if args.no_config {
+            Ok(crate::config::ConfigResolver::defaults())
+        } else if let Some(path) = &args.config {
+            crate::config::ConfigResolver::explicit(path)
+        } else {
+            crate::config::ConfigResolver::discover()
+        }
+        .map_err(|e| anyhow::anyhow!("Failed to load config: {e}"))
+    }
+
+    /// Effective configuration for a file: the configuration files from the
+    /// repository root down to the file merged in order (including matching
+    /// `[[overrides]]` sections), then command-line flags applied.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a configuration file on the way is invalid.
+    fn config_for(&self, path: &Path) -> Result<crate::config::Config> {
+        let config = self
+            .resolver
+            .resolve(path)
+            .map_err(|e| anyhow::anyhow!("Failed to load config: {e}"))?;
+        Ok(Self::apply_args(config, &self.args))
+    }
+
+    /// Layer command-line flags over the loaded configuration.
+    ///
+    /// Precedence is CLI > config file > defaults: a flag only replaces the
+    /// file value when it was actually given.
+    pub(crate) fn apply_args(
+        mut config: crate::config::Config,
+        args: &Args,
+    ) -> crate::config::Config {
+        if let Some(mode) = &args.mode {
+            config.mode = mode.clone();
+        }
+        match args.fix_request() {
+            Some(FixRequest::Exactly(selection)) => {
+                config.fixes = crate::config::FixesConfig::exactly(selection);
+            }
+            Some(FixRequest::Adding(selection)) => {
+                for fix in selection.iter() {
+                    config.fixes.set(fix, true);
+                }
+            }
+            None => {}
+        }
+        if let Some(skipped) = args.skip {
+            for fix in skipped.iter() {
+                config.fixes.set(fix, false);
+            }
+        }
+        if let Some(ext) = &args.ext {
+            config.extensions.clone_from(ext);
+        }
+        config.extensions = crate::cli::normalize_exts(&config.extensions);
+        if args.max_size.is_some() {
+            config.max_size = args.max_size;
+        }
+        if args.no_gitignore {
+            config.respect_gitignore = false;
+        }
+        config.strip_ansi |= args.strip_ansi;
+        config.strip_ansi_in_fences |= args.strip_ansi_fences;
+        if let Some(threshold) = args.diagram_threshold {
+            config.diagram_threshold = threshold;
+        }
+        config
     }
 
     /// Process a single file.
@@ -41,15 +155,9 @@ impl Processor {
     #[allow(dead_code)] // Reason: Part of public API for library usage
     pub fn process_file(&self, path: &Path) -> Result<String> {
         let content = io::read_markdown(path)?;
-        // Determine if we should repair fences (--all implies --fences)
-        let repair_fences = self.args.fences || self.args.all;
-        // Determine the mode (--all implies --mode=diagram)
-        let mode = if self.args.all {
-            &crate::cli::Mode::Diagram
-        } else {
-            &self.args.mode
-        };
-        let processed = crate::modes::process_by_mode(mode, &content, repair_fences, &self.config);
+        let config = self.config_for(path)?;
+        let processed =
+            crate::modes::process_with_fixes(&content, config.selected_fixes(), &config).content;
         Ok(processed)
     }
 
@@ -57,20 +165,24 @@ impl Processor {
     ///
     /// Uses `FileDiscovery` to find files matching configured extensions.
     /// Collects errors and continues processing, reporting all at the end.
-    /// In check mode, returns `CHECK_FAILED_EXIT_CODE` if any file needs fixing.
+    /// In check mode, returns `CHECK_FAILED_EXIT_CODE` if any file needs fixing,
+    /// or with `--fail-on`, if any finding is at least that severe.
+    /// With `--strict`, returns `TEXT_CHANGED_EXIT_CODE` if any diagram repair
+    /// was refused because it would have changed the text.
     /// In other modes, writes files and returns appropriate exit code.
     ///
     /// # Errors
     ///
     /// Returns an error if file discovery fails or if there are fatal I/O errors.
     pub fn process_all(&self) -> Result<i32> {
-        // Create file discovery with configured extensions and gitignore setting
-        let discovery = FileDiscovery::new(self.args.ext.clone(), !self.args.no_gitignore);
+        // Discover files matching the extensions and gitignore setting of each
+        let files = self.discover_files()?;
 
-        // Discover files matching the criteria
-        let file_paths = discovery.discover(&self.args.paths)?;
+        let mut stats = ProcessingStats::new();
+        let file_paths: Vec<PathBuf> = files.iter().map(|(path, _)| path.clone()).collect();
+        self.check_unvisited_baseline(&file_paths, &mut stats);
 
-        if file_paths.is_empty() {
+        if files.is_empty() {
             if self.args.verbose {
                 crate::output::log_warning("No files matching extensions found");
             } else {
@@ -80,21 +192,43 @@ impl Processor {
         }
 
         if self.args.verbose {
-            crate::output::log_verbose(&format!("Found {} files to process", file_paths.len()));
+            crate::output::log_verbose(&format!("Found {} files to process", files.len()));
         }
 
-        let mut stats = ProcessingStats::new();
         let mut file_results: Vec<FileResult> = Vec::new();
+        let mut diagnostics = Vec::new();
+        let mut known = Vec::new();
+        let mut patch = String::new();
         let mut any_needs_fixing = false;
 
-        for file_path in file_paths {
-            let result =
-                self.process_single_file_enhanced(&file_path, &mut any_needs_fixing, &mut stats);
+        for (file_path, config) in files {
+            let result = self.process_single_file_enhanced(
+                (&file_path, config),
+                &mut any_needs_fixing,
+                &mut stats,
+                &mut diagnostics,
+                &mut known,
+                &mut patch,
+            );
             file_results.push(result);
         }
 
+        let worst = diagnostics.iter().map(|d| d.severity).max();
+        let recorded =
+            self.args.write_baseline.as_ref().map(|_| {
+                crate::baseline::Baseline::from_diagnostics(diagnostics.iter().chain(&known))
+            });
+
         // Handle different output modes
-        if self.args.json {
+        if let Some(format) = self.args.format {
+            let report = Self::report(format, &file_results, diagnostics, &self.config.rules);
+            if matches!(format, crate::report::ReportFormat::Sarif) {
+                // The JSON document has no final newline, as with `--json`
+                println!("{report}");
+            } else {
+                print!("{report}");
+            }
+        } else if self.args.json {
             // JSON output mode
             let results = ProcessingResults {
                 files: file_results,
@@ -115,47 +249,224 @@ impl Processor {
             stats.print_summary();
         }
 
+        if let Some(path) = &self.args.patch {
+            std::fs::write(path, &patch)
+                .map_err(|e| anyhow::anyhow!("Failed to write patch {}: {e}", path.display()))?;
+        }
+
+        if let (Some(path), Some(baseline)) = (&self.args.write_baseline, recorded) {
+            baseline.save(path)?;
+            eprintln!(
+                "Wrote {} findings to baseline {}",
+                baseline.findings.len(),
+                path.display()
+            );
+        } else if stats.stale_baseline > 0 {
+            crate::output::log_warning(&format!(
+                "{} baseline entries are no longer found; run with --write-baseline to remove them",
+                stats.stale_baseline
+            ));
+        }
+
         // Return appropriate exit code
-        if stats.error_files > 0 || (self.args.check && any_needs_fixing) {
+        if self.args.strict && stats.text_violations > 0 {
+            Ok(TEXT_CHANGED_EXIT_CODE)
+        } else if stats.error_files > 0
+            || (self.args.write_baseline.is_none() && self.check_failed(any_needs_fixing, worst))
+        {
             Ok(CHECK_FAILED_EXIT_CODE)
         } else {
             Ok(SUCCESS_EXIT_CODE)
         }
     }
 
+    /// Files to process under the command-line paths, each with its
+    /// configuration (resolved once per file).
+    ///
+    /// Each file is kept according to its own configuration: its `extensions`,
+    /// and `respect_gitignore` for files that `.gitignore` excludes. Excluded
+    /// files are only looked for when a configuration turns `respect_gitignore`
+    /// off, so ignored trees are not walked otherwise.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a path does not exist or cannot be read.
+    fn discover_files(&self) -> Result<Vec<DiscoveredFile>> {
+        let paths = &self.args.paths;
+        let mut visible: HashMap<PathBuf, Result<crate::config::Config>> = HashMap::new();
+        let mut candidates = Vec::new();
+        for path in FileDiscovery::all_files(true).discover(paths)? {
+            visible.insert(path.clone(), self.config_for(&path));
+            candidates.push(path);
+        }
+        let includes_ignored = !self.config.respect_gitignore
+            || visible.values().any(|config| {
+                config
+                    .as_ref()
+                    .is_ok_and(|config| !config.respect_gitignore)
+            });
+        if includes_ignored {
+            candidates = FileDiscovery::all_files(false).discover(paths)?;
+        }
+        Ok(candidates
+            .into_iter()
+            .filter_map(|path| {
+                let (config, is_visible) = visible
+                    .remove(&path)
+                    .map_or_else(|| (self.config_for(&path), false), |config| (config, true));
+                self.discovers(&path, is_visible, &config)
+                    .then_some((path, config))
+            })
+            .collect())
+    }
+
+    /// Whether a found file is processed under its own configuration. A file
+    /// whose configuration cannot be loaded is kept if the base configuration
+    /// would keep it, so that the error is reported.
+    fn discovers(
+        &self,
+        path: &Path,
+        visible: bool,
+        config: &Result<crate::config::Config>,
+    ) -> bool {
+        config.as_ref().map_or_else(
+            |_| visible && FileDiscovery::has_extension(path, &self.config.extensions),
+            |config| {
+                FileDiscovery::has_extension(path, &config.extensions)
+                    && (visible || !config.respect_gitignore)
+            },
+        )
+    }
+
+    /// Whether the run fails: with `--fail-on`, when a finding is at least that
+    /// severe; with `--baseline`, when a new finding is at least a warning;
+    /// otherwise, in check mode, when a file needs fixing.
+    fn check_failed(&self, any_needs_fixing: bool, worst: Option<crate::rules::Severity>) -> bool {
+        let threshold = self.args.fail_on.or_else(|| {
+            self.baseline
+                .as_ref()
+                .map(|_| crate::rules::Severity::Warning)
+        });
+        threshold.map_or(self.args.check && any_needs_fixing, |threshold| {
+            worst >= Some(threshold)
+        })
+    }
+
+    /// Set aside the diagnostics recorded in the baseline (added to `known`)
+    /// and warn about baseline entries for the file that no longer match.
+    /// Returns the new diagnostics.
+    fn apply_baseline(
+        &self,
+        file: &str,
+        found: Vec<crate::report::Diagnostic>,
+        stats: &mut ProcessingStats,
+        known: &mut Vec<crate::report::Diagnostic>,
+    ) -> Vec<crate::report::Diagnostic> {
+        let Some(baseline) = &self.baseline else {
+            return found;
+        };
+        let split = baseline.split(file, found);
+        stats.record_baseline(split.known.len(), split.stale.len());
+        for entry in &split.stale {
+            crate::output::log_warning(&format!(
+                "{file}: baseline entry no longer found: {}: {}",
+                entry.rule, entry.message
+            ));
+        }
+        known.extend(split.known);
+        split.new
+    }
+
+    /// Warn about baseline entries for files below the checked paths that were
+    /// not discovered, and count them as stale.
+    fn check_unvisited_baseline(&self, file_paths: &[PathBuf], stats: &mut ProcessingStats) {
+        let Some(baseline) = &self.baseline else {
+            return;
+        };
+        let roots: Vec<String> = self
+            .args
+            .paths
+            .iter()
+            .map(|path| path.display().to_string())
+            .collect();
+        let visited: HashSet<String> = file_paths
+            .iter()
+            .map(|path| crate::baseline::normalize_path(&path.display().to_string()))
+            .collect();
+        let stale = baseline.unvisited(&roots, &visited);
+        stats.record_baseline(0, stale.len());
+        for entry in stale {
+            crate::output::log_warning(&format!(
+                "{}: baseline entry no longer found (file not checked): {}: {}",
+                entry.file, entry.rule, entry.message
+            ));
+        }
+    }
+
+    /// Render the diagnostics of every checked file in a report format.
+    ///
+    /// Files that could not be processed are reported as errors.
+    fn report(
+        format: crate::report::ReportFormat,
+        file_results: &[FileResult],
+        mut diagnostics: Vec<crate::report::Diagnostic>,
+        rules: &crate::rules::RulesConfig,
+    ) -> String {
+        let mut files = Vec::new();
+        for result in file_results {
+            match result {
+                FileResult::Skipped { .. } => continue,
+                FileResult::Error { file, error } => {
+                    diagnostics.extend(crate::report::file_error(file, error, rules));
+                }
+                _ => {}
+            }
+            files.push(result.file_path().to_string());
+        }
+        crate::report::render(format, &files, &diagnostics)
+    }
+
+    /// Whether results are reported instead of printing the processed content.
+    const fn reports_only(&self) -> bool {
+        self.args.json || self.args.list_files || self.args.format.is_some()
+    }
+
     /// Process a single file and return detailed result.
     ///
     /// Enhanced version that tracks statistics and supports all output modes.
+    /// The file comes with the configuration it was discovered under.
+    /// Diagnostics for `--format` are added to `diagnostics`, or to `known` if
+    /// they are in the baseline, and the diff of a modified file to `patch`.
     fn process_single_file_enhanced(
         &self,
-        file_path: &Path,
+        (file_path, config): (&Path, Result<crate::config::Config>),
         any_needs_fixing: &mut bool,
         stats: &mut ProcessingStats,
+        diagnostics: &mut Vec<crate::report::Diagnostic>,
+        known: &mut Vec<crate::report::Diagnostic>,
+        patch: &mut String,
     ) -> FileResult {
         let file_str = file_path.display().to_string();
-
-        // Check file size if max_size is set
-        if let Some(max_size) = self.args.max_size {
-            if let Ok(metadata) = file_path.metadata() {
-                let file_size = metadata.len();
-                if file_size > max_size {
-                    if self.args.verbose {
-                        crate::output::log_warning(&format!(
-                            "Skipping {} (exceeds maximum size: {} bytes, max: {} bytes)",
-                            file_path.display(),
-                            file_size,
-                            max_size
-                        ));
-                    }
-                    stats.record_skipped();
-                    return FileResult::Skipped {
-                        file: file_str,
-                        reason: format!(
-                            "File size ({file_size} bytes) exceeds maximum ({max_size} bytes)"
-                        ),
-                    };
+        let config = match config {
+            Ok(config) => config,
+            Err(e) => {
+                if self.args.verbose {
+                    crate::output::log_error(&format!("{}: {e}", file_path.display()));
                 }
+                stats.record_error();
+                return FileResult::Error {
+                    file: file_str,
+                    error: e.to_string(),
+                };
             }
+        };
+
+        if let Some(reason) = self.skip_reason(file_path, &config) {
+            stats.record_skipped();
+            return FileResult::Skipped {
+                file: file_str,
+                reason,
+            };
         }
 
         // Read file content
@@ -178,62 +489,207 @@ impl Processor {
         };
 
         // Process the content
-        let repair_fences = self.args.fences || self.args.all;
-        let mode = if self.args.all {
-            &crate::cli::Mode::Diagram
-        } else {
-            &self.args.mode
-        };
-        let processed = crate::modes::process_by_mode(mode, &content, repair_fences, &self.config);
+        let outcome = crate::modes::process_with_fixes(&content, config.selected_fixes(), &config);
+        let processed = outcome.content.clone();
+
+        Self::log_outcome_warnings(file_path, &outcome);
+        stats.record_text_violations(outcome.text_violations.len());
+        let found = crate::report::diagnostics_for(&file_str, &content, &outcome, &config.rules);
+        let found = self.apply_baseline(&file_str, found, stats, known);
+        if self.args.check && !self.reports_only() {
+            Self::log_diagnostics(&found);
+        }
+        let has_new_findings = self.baseline.is_none() || !found.is_empty();
+        diagnostics.extend(found);
 
         // Check if file needs fixing
         if crate::modes::content_needs_fixing(&content, &processed) {
             *any_needs_fixing = true;
             stats.record_modified();
 
+            let analysis =
+                crate::transformation_analysis::analyze_changes(&content, &outcome.changes);
             if self.args.verbose {
                 crate::output::log_success(&format!("Modified: {}", file_path.display()));
+                Self::log_outcome_details(file_path, &outcome);
+                crate::output::log_transformations(&analysis.summary, &analysis.transformations);
             }
 
-            // Handle different output modes
-            if self.args.diff {
-                crate::output::print_diff(file_path, &content, &processed);
-            } else if self.args.check {
-                // In check mode, just report without writing
-                if !self.args.list_files && !self.args.json {
-                    eprintln!("File needs fixing: {}", file_path.display());
-                }
-            } else if self.args.in_place {
-                // Write the file
-                if let Err(e) = io::write_markdown(file_path, &processed) {
-                    stats.record_error();
-                    return FileResult::Error {
-                        file: file_str,
-                        error: format!("Failed to write: {e}"),
-                    };
-                }
-            } else if !self.args.json && !self.args.list_files {
-                // Output to stdout
-                println!("{processed}");
+            if let Err(e) =
+                self.emit_modified(file_path, &content, &processed, has_new_findings, patch)
+            {
+                stats.record_error();
+                return FileResult::Error {
+                    file: file_str,
+                    error: format!("Failed to write: {e}"),
+                };
             }
 
             FileResult::Modified {
                 file: file_str,
-                transformations: None,
+                summary: Some(analysis.summary),
+                transformations: analysis.transformations,
+                changes: outcome.changes,
+                diagram_blocks: outcome.block_scores,
             }
         } else {
             stats.record_unchanged();
 
             if self.args.verbose {
                 crate::output::log_verbose(&format!("Unchanged: {}", file_path.display()));
+                Self::log_outcome_details(file_path, &outcome);
             }
 
-            if !self.args.check && !self.args.in_place && !self.args.json && !self.args.list_files {
+            if !self.args.check
+                && !self.args.in_place
+                && !self.args.diff
+                && !self.reports_only()
+                && self.args.patch.is_none()
+            {
                 // File doesn't need fixing and we're in normal output mode
-                println!("{processed}");
+                crate::output::print_content(&processed);
+            }
+
+            FileResult::Unchanged {
+                file: file_str,
+                diagram_blocks: outcome.block_scores,
+            }
+        }
+    }
+
+    /// Output a modified file: add its diff to `patch`, then print the diff,
+    /// report it (check mode), write it (in place) or print its content.
+    fn emit_modified(
+        &self,
+        file_path: &Path,
+        content: &str,
+        processed: &str,
+        has_new_findings: bool,
+        patch: &mut String,
+    ) -> Result<()> {
+        if self.args.patch.is_some() {
+            patch.push_str(&crate::output::unified_diff(
+                file_path,
+                content,
+                processed,
+                self.args.context,
+            ));
+        }
+
+        if self.args.diff {
+            crate::output::print_diff(file_path, content, processed, self.args.context);
+        } else if self.args.check {
+            // In check mode, just report without writing
+            if !self.reports_only() && has_new_findings {
+                eprintln!("File needs fixing: {}", file_path.display());
             }
+        } else if self.args.in_place {
+            io::write_markdown(file_path, processed)?;
+        } else if !self.reports_only() && self.args.patch.is_none() {
+            // Output to stdout
+            crate::output::print_content(processed);
+        }
+        Ok(())
+    }
+
+    /// Reason to skip a file: `ignore = true` in its configuration, or a size
+    /// over `max_size`.
+    fn skip_reason(&self, file_path: &Path, config: &crate::config::Config) -> Option<String> {
+        if config.ignore {
+            if self.args.verbose {
+                crate::output::log_verbose(&format!(
+                    "Skipping {} (ignored by configuration)",
+                    file_path.display()
+                ));
+            }
+            return Some("Ignored by configuration".to_string());
+        }
+
+        let max_size = config.max_size?;
+        let file_size = file_path.metadata().ok()?.len();
+        if file_size <= max_size {
+            return None;
+        }
+        if self.args.verbose {
+            crate::output::log_warning(&format!(
+                "Skipping {} (exceeds maximum size: {} bytes, max: {} bytes)",
+                file_path.display(),
+                file_size,
+                max_size
+            ));
+        }
+        Some(format!(
+            "File size ({file_size} bytes) exceeds maximum ({max_size} bytes)"
+        ))
+    }
+
+    /// Log the diagnostics of fixer rules (check mode). Other rules are
+    /// already logged as warnings.
+    fn log_diagnostics(diagnostics: &[crate::report::Diagnostic]) {
+        for d in diagnostics.iter().filter(|d| d.rule.fixer().is_some()) {
+            eprintln!(
+                "{}:{}:{}: {} {}: {}",
+                d.file, d.line, d.column, d.severity, d.rule, d.message
+            );
+        }
+    }
+
+    /// Log problems found while processing (always shown).
+    fn log_outcome_warnings(file_path: &Path, outcome: &crate::modes::ProcessingOutcome) {
+        let block_warnings = outcome
+            .block_warnings
+            .iter()
+            .map(|warning| (warning.line, &warning.message));
+        let directive_warnings = outcome
+            .directive_warnings
+            .iter()
+            .map(|warning| (warning.line, &warning.message));
+        for (line, message) in block_warnings.chain(directive_warnings) {
+            crate::output::log_warning(&format!("{}:{}: {message}", file_path.display(), line + 1));
+        }
+        for mismatch in &outcome.text_violations {
+            crate::output::log_warning(&format!(
+                "{}:{}:{}: diagram repair would change the text ({mismatch}); left unchanged",
+                file_path.display(),
+                mismatch.line + 1,
+                mismatch.col + 1
+            ));
+        }
+    }
 
-            FileResult::Unchanged { file: file_str }
+    /// Log what processing changed beyond the content diff (verbose mode).
+    fn log_outcome_details(file_path: &Path, outcome: &crate::modes::ProcessingOutcome) {
+        for (line, overrides) in &outcome.applied_overrides {
+            crate::output::log_verbose(&format!("  Block at line {line}: overrides {overrides}"));
+        }
+        for block in &outcome.block_scores {
+            crate::output::log_verbose(&format!(
+                "  Block at line {}: diagram score {:.2} ({})",
+                block.line,
+                block.score,
+                if block.processed {
+                    "processed"
+                } else {
+                    "skipped, below threshold"
+                }
+            ));
+        }
+        if outcome.ansi_sequences_removed > 0 {
+            crate::output::log_verbose(&format!(
+                "  Removed {} ANSI escape sequence(s)",
+                outcome.ansi_sequences_removed
+            ));
+        }
+        for sub in &outcome.substitutions {
+            crate::output::log_verbose(&format!(
+                "  {}:{}:{}: replaced {:?} (U+{:04X}) with {:?}",
+                file_path.display(),
+                sub.line + 1,
+                sub.col + 1,
+                sub.original,
+                u32::from(sub.original),
+                sub.replacement
+            ));
         }
     }
 
@@ -244,8 +700,13 @@ impl Processor {
     /// Skips files that exceed `max_size` without error.
     #[allow(dead_code)] // Reason: Kept for backward compatibility
     fn process_single_file(&self, file_path: &Path, any_needs_fixing: &mut bool) -> Result<()> {
+        let config = self.config_for(file_path)?;
+        if config.ignore {
+            return Ok(());
+        }
+
         // Check file size if max_size is set
-        if let Some(max_size) = self.args.max_size {
+        if let Some(max_size) = config.max_size {
             let file_size = file_path.metadata()?.len();
             if file_size > max_size {
                 eprintln!(
@@ -260,14 +721,8 @@ impl Processor {
 
         let content = io::read_markdown(file_path)?;
         // Determine if we should repair fences (--all implies --fences)
-        let repair_fences = self.args.fences || self.args.all;
-        // Determine the mode (--all implies --mode=diagram)
-        let mode = if self.args.all {
-            &crate::cli::Mode::Diagram
-        } else {
-            &self.args.mode
-        };
-        let processed = crate::modes::process_by_mode(mode, &content, repair_fences, &self.config);
+        let processed =
+            crate::modes::process_with_fixes(&content, config.selected_fixes(), &config).content;
 
         // Check if file needs fixing
         if crate::modes::content_needs_fixing(&content, &processed) {
@@ -281,12 +736,12 @@ impl Processor {
                 if self.args.in_place {
                     io::write_markdown(file_path, &processed)?;
                 } else {
-                    println!("{processed}");
+                    crate::output::print_content(&processed);
                 }
             }
         } else if !self.args.check && !self.args.in_place {
             // File doesn't need fixing and we're in normal output mode
-            println!("{processed}");
+            crate::output::print_content(&processed);
         }
 
         Ok(())
@@ -326,6 +781,181 @@ mod tests {
         assert_eq!(processor.args.paths.len(), 1);
     }
 
+    #[test]
+    fn test_cli_flags_override_config_file() {
+        use clap::Parser;
+        let file_config: crate::config::Config = toml::from_str(
+            "mode = \"diagram\"\nextensions = [\"MD\"]\nmax_size = \"1KB\"\nrespect_gitignore = true",
+        )
+        .unwrap();
+
+        // Without flags the file values apply
+        let args = Args::try_parse_from(["ascfix", "test.md"]).unwrap();
+        let config = Processor::apply_args(file_config.clone(), &args);
+        assert_eq!(config.mode, crate::cli::Mode::Diagram);
+        assert_eq!(config.extensions, vec![".md".to_string()]);
+        assert_eq!(config.max_size, Some(1024));
+        assert!(config.respect_gitignore);
+
+        // Flags that were given win
+        let args = Args::try_parse_from([
+            "ascfix",
+            "--mode",
+            "safe",
+            "--ext",
+            "txt",
+            "--max-size",
+            "5",
+            "--no-gitignore",
+            "test.md",
+        ])
+        .unwrap();
+        let config = Processor::apply_args(file_config, &args);
+        assert_eq!(config.mode, crate::cli::Mode::Safe);
+        assert_eq!(config.extensions, vec![".txt".to_string()]);
+        assert_eq!(config.max_size, Some(5));
+        assert!(!config.respect_gitignore);
+    }
+
+    #[test]
+    fn test_fix_and_skip_flags_select_fixers() {
+        use clap::Parser;
+        let file_config: crate::config::Config =
+            toml::from_str("mode = \"diagram\"\n[fixes]\nfix_lists = false\n").unwrap();
+        let selected = |argv: &[&str]| {
+            let args = Args::try_parse_from(argv).unwrap();
+            Processor::apply_args(file_config.clone(), &args)
+                .selected_fixes()
+                .to_string()
+        };
+
+        assert_eq!(selected(&["ascfix", "x.md"]), "boxes,arrows");
+        assert_eq!(selected(&["ascfix", "--skip", "arrows", "x.md"]), "boxes");
+        assert_eq!(
+            selected(&["ascfix", "--fix", "tables,lists", "--fences", "x.md"]),
+            "fences,tables,lists"
+        );
+        assert_eq!(selected(&["ascfix", "--mode", "safe", "x.md"]), "tables");
+        assert_eq!(
+            selected(&["ascfix", "--fix", "+fences", "x.md"]),
+            "fences,boxes,arrows"
+        );
+        assert_eq!(
+            selected(&["ascfix", "--all", "--skip", "boxes", "x.md"]),
+            "fences,tables,lists,arrows"
+        );
+        assert!(Args::try_parse_from(["ascfix", "--fix", "links", "x.md"]).is_err());
+        assert!(Args::try_parse_from(["ascfix", "--mode", "check", "x.md"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_flags_override_path_sections() {
+        use clap::Parser;
+        let dir = TempDir::new().unwrap();
+        let config_path = dir.path().join(".ascfix.toml");
+        fs::write(
+            &config_path,
+            "[[overrides]]\npaths = [\"legacy/**\"]\nmode = \"safe\"\nstrip_ansi = true\n",
+        )
+        .unwrap();
+        let config_arg = config_path.to_str().unwrap();
+        let args = Args::try_parse_from([
+            "ascfix", "--mode", "diagram", "--config", config_arg, "x.md",
+        ])
+        .unwrap();
+        let processor = Processor::new(args).unwrap();
+
+        let config = processor
+            .config_for(&dir.path().join("legacy/a.md"))
+            .unwrap();
+        assert_eq!(config.mode, crate::cli::Mode::Diagram);
+        assert!(config.strip_ansi);
+        assert!(
+            !processor
+                .config_for(&dir.path().join("a.md"))
+                .unwrap()
+                .strip_ansi
+        );
+    }
+
+    #[test]
+    fn test_discovery_uses_each_files_configuration() -> Result<()> {
+        use clap::Parser;
+        let dir = TempDir::new()?;
+        let root = dir.path();
+        fs::create_dir_all(root.join(".git"))?;
+        fs::create_dir_all(root.join("sub"))?;
+        fs::create_dir_all(root.join("vendor"))?;
+        fs::write(root.join(".gitignore"), "vendor/\n")?;
+        fs::write(root.join("sub/.ascfix.toml"), "extensions = [\".rst\"]\n")?;
+        fs::write(
+            root.join("vendor/.ascfix.toml"),
+            "respect_gitignore = false\n",
+        )?;
+        for file in ["a.md", "sub/x.rst", "sub/y.md", "vendor/v.md"] {
+            fs::write(root.join(file), "# Title\n")?;
+        }
+
+        // Run on the root: sub/ keeps its own extensions wherever ascfix runs
+        let args = Args::try_parse_from(["ascfix", root.to_str().unwrap()])?;
+        let mut found = Processor::new(args)?.discover_files()?;
+        found.sort_by(|(a, _), (b, _)| a.cmp(b));
+        // Each file comes with the configuration it was discovered under
+        let extensions: Vec<Vec<String>> = found
+            .iter()
+            .map(|(_, config)| config.as_ref().unwrap().extensions.clone())
+            .collect();
+        assert_eq!(extensions[1], vec![".rst".to_string()]);
+        let files: Vec<PathBuf> = found.into_iter().map(|(path, _)| path).collect();
+        assert_eq!(files, vec![root.join("a.md"), root.join("sub/x.rst")]);
+
+        // An ignored file is found when its configuration does not respect
+        // .gitignore, if a configuration that is visited says so
+        fs::write(root.join("sub/.ascfix.toml"), "respect_gitignore = false\n")?;
+        let args = Args::try_parse_from(["ascfix", root.to_str().unwrap()])?;
+        let mut files: Vec<PathBuf> = Processor::new(args)?
+            .discover_files()?
+            .into_iter()
+            .map(|(path, _)| path)
+            .collect();
+        files.sort();
+        assert_eq!(
+            files,
+            vec![
+                root.join("a.md"),
+                root.join("sub/y.md"),
+                root.join("vendor/v.md")
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_no_config_uses_defaults() {
+        use clap::Parser;
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join(".ascfix.toml"), "mode = \"diagram\"\n").unwrap();
+        let file = dir.path().join("a.md");
+
+        let args = Args::try_parse_from(["ascfix", "x.md"]).unwrap();
+        let processor = Processor::new(args).unwrap();
+        assert_eq!(
+            processor.config_for(&file).unwrap().mode,
+            crate::cli::Mode::Diagram
+        );
+
+        let args = Args::try_parse_from(["ascfix", "--no-config", "x.md"]).unwrap();
+        let processor = Processor::new(args).unwrap();
+        assert_eq!(
+            processor.config_for(&file).unwrap().mode,
+            crate::cli::Mode::Safe
+        );
+
+        assert!(
+            Args::try_parse_from(["ascfix", "--no-config", "--config", "c.toml", "x.md"]).is_err()
+        );
+    }
+
     #[test]
     fn test_max_size_enforcement() -> Result<()> {
         use clap::Parser;
@@ -373,4 +1003,229 @@ mod tests {
         assert_eq!(exit_code, 0);
         Ok(())
     }
+
+    #[test]
+    fn test_strict_fails_when_text_would_change() -> Result<()> {
+        use clap::Parser;
+        let temp_dir = TempDir::new()?;
+        let file_path = temp_dir.path().join("test.md");
+        // The repair would cut the label short, so the block is kept as is
+        let content = "┌─────────────┐\n│ Load Balancer│─┐\n└─────────────┘ │\n                ▼\n            ┌────────────┐\n            │  Cluster  │\n            └────────────┘";
+        fs::write(&file_path, content)?;
+
+        let run = |strict: bool| -> Result<i32> {
+            let mut argv = vec!["ascfix", file_path.to_str().unwrap(), "--mode", "diagram"];
+            argv.extend(["--check", "--no-config"]);
+            if strict {
+                argv.push("--strict");
+            }
+            Processor::new(Args::try_parse_from(argv)?)?.process_all()
+        };
+        assert_eq!(run(false)?, SUCCESS_EXIT_CODE);
+        assert_eq!(run(true)?, TEXT_CHANGED_EXIT_CODE);
+        assert_eq!(fs::read_to_string(&file_path)?, content);
+        Ok(())
+    }
+
+    #[test]
+    fn test_report_includes_file_errors() {
+        let results = [
+            FileResult::Error {
+                file: "broken.md".to_string(),
+                error: "invalid UTF-8".to_string(),
+            },
+            FileResult::Skipped {
+                file: "big.md".to_string(),
+                reason: "too large".to_string(),
+            },
+        ];
+        let report = Processor::report(
+            crate::report::ReportFormat::Checkstyle,
+            &results,
+            Vec::new(),
+            &crate::rules::RulesConfig::default(),
+        );
+        assert!(report.contains("<file name=\"broken.md\">"));
+        assert!(report.contains("severity=\"error\" message=\"invalid UTF-8\""));
+        assert!(!report.contains("big.md"));
+    }
+
+    #[test]
+    fn test_fail_on_sets_the_severity_that_fails() -> Result<()> {
+        use clap::Parser;
+        let temp_dir = TempDir::new()?;
+        let file_path = temp_dir.path().join("test.md");
+        // Aligned, but drawn in two box styles (an info finding)
+        fs::write(&file_path, "╔═══╗   ┌───┐\n║ a ║   │ b │\n╚═══╝   └───┘")?;
+
+        let run = |fail_on: &str| -> Result<i32> {
+            let argv = ["ascfix", file_path.to_str().unwrap(), "--mode", "diagram"];
+            let argv = argv
+                .into_iter()
+                .chain(["--check", "--no-config", "--fail-on", fail_on]);
+            Processor::new(Args::try_parse_from(argv)?)?.process_all()
+        };
+        assert_eq!(run("info")?, CHECK_FAILED_EXIT_CODE);
+        assert_eq!(run("warning")?, SUCCESS_EXIT_CODE);
+        assert!(Args::try_parse_from(["ascfix", "a.md", "--fail-on", "off"]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_patch_collects_every_modified_file() -> Result<()> {
+        use clap::Parser;
+        let temp_dir = TempDir::new()?;
+        let table = temp_dir.path().join("table.md");
+        let clean = temp_dir.path().join("clean.md");
+        let patch_path = temp_dir.path().join("fix.patch");
+        fs::write(&table, "| a | b |\n|---|---|\n| long cell | x |\n")?;
+        fs::write(&clean, "# Title\n\nSome text.\n")?;
+
+        let run = |files: &[&Path]| -> Result<String> {
+            let argv = files.iter().map(|file| file.to_str().unwrap());
+            let argv = std::iter::once("ascfix").chain(argv).chain([
+                "--no-config",
+                "--patch",
+                patch_path.to_str().unwrap(),
+                "--context",
+                "0",
+            ]);
+            let exit_code = Processor::new(Args::try_parse_from(argv)?)?.process_all()?;
+            assert_eq!(exit_code, SUCCESS_EXIT_CODE);
+            Ok(fs::read_to_string(&patch_path)?)
+        };
+
+        let patch = run(&[&table, &clean])?;
+        assert!(patch.contains("table.md\n@@ -1,2 +1,2 @@\n-| a | b |\n"));
+        assert!(!patch.contains("clean.md"));
+        assert!(!patch.contains("No newline at end of file"));
+        // The patch is written instead of changing the files
+        assert_eq!(
+            fs::read_to_string(&table)?,
+            "| a | b |\n|---|---|\n| long cell | x |\n"
+        );
+
+        assert_eq!(run(&[&clean])?, "");
+        Ok(())
+    }
+
+    #[test]
+    fn test_patch_removes_trailing_blank_lines_like_in_place() -> Result<()> {
+        use clap::Parser;
+        let temp_dir = TempDir::new()?;
+        let file = temp_dir.path().join("a.md");
+        let patch_path = temp_dir.path().join("fix.patch");
+        fs::write(&file, "# Title\n\nText\n\n")?;
+
+        let run = |flags: &[&str]| -> Result<i32> {
+            let argv = ["ascfix", file.to_str().unwrap(), "--no-config"];
+            Processor::new(Args::try_parse_from(argv.iter().chain(flags))?)?.process_all()
+        };
+
+        assert_eq!(
+            run(&["--patch", patch_path.to_str().unwrap()])?,
+            SUCCESS_EXIT_CODE
+        );
+        assert!(
+            fs::read_to_string(&patch_path)?.ends_with("@@ -1,4 +1,3 @@\n # Title\n \n Text\n-\n")
+        );
+
+        assert_eq!(run(&["-i"])?, SUCCESS_EXIT_CODE);
+        assert_eq!(fs::read_to_string(&file)?, "# Title\n\nText\n");
+        Ok(())
+    }
+
+    #[test]
+    fn test_baseline_only_fails_on_new_findings() -> Result<()> {
+        use clap::Parser;
+        let temp_dir = TempDir::new()?;
+        let file_path = temp_dir.path().join("test.md");
+        let baseline_path = temp_dir.path().join("baseline.json");
+        let table = "| a | b |\n|---|---|\n| long cell | x |\n";
+        fs::write(&file_path, table)?;
+
+        let run = |flag: &str| -> Result<(i32, ProcessingStats)> {
+            let argv = [
+                "ascfix",
+                file_path.to_str().unwrap(),
+                "--check",
+                "--no-config",
+            ];
+            let argv = argv
+                .into_iter()
+                .chain([flag, baseline_path.to_str().unwrap()]);
+            let processor = Processor::new(Args::try_parse_from(argv)?)?;
+            let mut stats = ProcessingStats::new();
+            let exit_code = processor.process_all()?;
+            processor.process_single_file_enhanced(
+                (&file_path, processor.config_for(&file_path)),
+                &mut false,
+                &mut stats,
+                &mut Vec::new(),
+                &mut Vec::new(),
+                &mut String::new(),
+            );
+            Ok((exit_code, stats))
+        };
+        assert_eq!(run("--write-baseline")?.0, SUCCESS_EXIT_CODE);
+        let (exit_code, stats) = run("--baseline")?;
+        assert_eq!(exit_code, SUCCESS_EXIT_CODE);
+        assert_eq!((stats.baselined, stats.stale_baseline), (1, 0));
+
+        // The known table moves down and a new one is added above it
+        fs::write(
+            &file_path,
+            format!("| c | d |\n|---|---|\n| new row | y |\n\n{table}"),
+        )?;
+        let (exit_code, stats) = run("--baseline")?;
+        assert_eq!(exit_code, CHECK_FAILED_EXIT_CODE);
+        assert_eq!((stats.baselined, stats.stale_baseline), (1, 0));
+
+        fs::write(&file_path, "| a | b |\n|---|---|\n| c | d |\n")?;
+        let (exit_code, stats) = run("--baseline")?;
+        assert_eq!(exit_code, SUCCESS_EXIT_CODE);
+        assert_eq!((stats.baselined, stats.stale_baseline), (0, 1));
+        Ok(())
+    }
+
+    #[test]
+    fn test_baseline_entries_of_removed_files_are_stale() -> Result<()> {
+        use clap::Parser;
+        let temp_dir = TempDir::new()?;
+        let docs = temp_dir.path().join("docs");
+        fs::create_dir(&docs)?;
+        let table = "| a | b |\n|---|---|\n| long cell | x |\n";
+        fs::write(docs.join("a.md"), table)?;
+        fs::write(docs.join("b.md"), table)?;
+        let baseline_path = temp_dir.path().join("baseline.json");
+
+        let processor = |flag: &str, root: &Path| -> Result<Processor> {
+            Processor::new(Args::try_parse_from([
+                "ascfix",
+                root.to_str().unwrap(),
+                "--check",
+                "--no-config",
+                flag,
+                baseline_path.to_str().unwrap(),
+            ])?)
+        };
+        processor("--write-baseline", &docs)?.process_all()?;
+        fs::remove_file(docs.join("b.md"))?;
+
+        let stale = |root: &Path| -> Result<usize> {
+            let processor = processor("--baseline", root)?;
+            let mut stats = ProcessingStats::new();
+            let files: Vec<PathBuf> = processor
+                .discover_files()?
+                .into_iter()
+                .map(|(path, _)| path)
+                .collect();
+            processor.check_unvisited_baseline(&files, &mut stats);
+            Ok(stats.stale_baseline)
+        };
+        assert_eq!(stale(&docs)?, 1);
+        // A run on another file says nothing about b.md
+        assert_eq!(stale(&docs.join("a.md"))?, 0);
+        Ok(())
+    }
 }
@@ -4,13 +4,17 @@
 //! ensuring that all transformations maintain high quality standards and preserve
 //! original content integrity.
 
-#![allow(dead_code)] // Reason: Framework for future quality validation features
+#![allow(dead_code)] // Reason: Issue details and fixture helpers are only read by tests and library users
 
 // Quality validation module
 use crate::cli::Mode;
 use crate::config::Config;
 use crate::modes;
-use crate::transformation_analysis::{analyze_transformations, TransformationType};
+use crate::transformation_analysis::{
+    analyze_transformations, is_text_char, text_content, TransformationType,
+};
+use serde::{Deserialize, Serialize};
+use std::fmt;
 
 /// Comprehensive quality report for diagram processing
 #[derive(Debug, Clone)]
@@ -101,13 +105,21 @@ pub struct Point {
     pub col: usize,
 }
 
-/// Quality validation configuration
-#[derive(Debug, Clone)]
+/// Quality validation configuration (the `[quality]` table)
+///
+/// Every repaired diagram block is compared with the original; a block that
+/// fails any of these checks is left unchanged.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct QualityConfig {
+    /// Whether to revert diagram blocks that fail the checks (default: true)
+    pub enabled: bool,
+    /// Minimum acceptable overall score (default: 0.8)
+    pub min_score: f64,
     /// Minimum acceptable text preservation (default: 0.95)
-    pub min_text_preservation: f32,
+    pub min_text_preservation: f64,
     /// Minimum acceptable structure preservation (default: 0.90)
-    pub min_structure_preservation: f32,
+    pub min_structure_preservation: f64,
     /// Maximum allowed line count delta (default: 0)
     pub max_line_count_delta: i32,
     /// Whether to allow any text corruption (default: false)
@@ -119,6 +131,8 @@ pub struct QualityConfig {
 impl Default for QualityConfig {
     fn default() -> Self {
         Self {
+            enabled: true,
+            min_score: 0.8,
             min_text_preservation: 0.95,
             min_structure_preservation: 0.90,
             max_line_count_delta: 0,
@@ -132,12 +146,45 @@ impl QualityReport {
     /// Check if the quality meets acceptable standards
     #[must_use]
     pub fn is_acceptable(&self, config: &QualityConfig) -> bool {
-        self.score >= 0.8
-            && self.metrics.text_preservation >= config.min_text_preservation
-            && self.metrics.structure_preservation >= config.min_structure_preservation
-            && self.metrics.line_count_delta.abs() <= config.max_line_count_delta.abs()
-            && (config.allow_text_corruption || self.metrics.text_corruption_count == 0)
-            && (config.allow_data_loss || self.metrics.data_loss_count == 0)
+        self.failed_checks(config).is_empty()
+    }
+
+    /// Describe every check in `config` this report fails, in a fixed order
+    #[must_use]
+    pub fn failed_checks(&self, config: &QualityConfig) -> Vec<String> {
+        let metrics = &self.metrics;
+        let mut failed = Vec::new();
+        if f64::from(self.score) < config.min_score {
+            failed.push(format!("score {:.2} < {:.2}", self.score, config.min_score));
+        }
+        if f64::from(metrics.text_preservation) < config.min_text_preservation {
+            failed.push(format!(
+                "text preservation {:.2} < {:.2}",
+                metrics.text_preservation, config.min_text_preservation
+            ));
+        }
+        if f64::from(metrics.structure_preservation) < config.min_structure_preservation {
+            failed.push(format!(
+                "structure preservation {:.2} < {:.2}",
+                metrics.structure_preservation, config.min_structure_preservation
+            ));
+        }
+        if metrics.line_count_delta.abs() > config.max_line_count_delta.abs() {
+            failed.push(format!(
+                "line count changed by {}",
+                metrics.line_count_delta
+            ));
+        }
+        if !config.allow_text_corruption && metrics.text_corruption_count > 0 {
+            failed.push(format!(
+                "{} text corruption(s)",
+                metrics.text_corruption_count
+            ));
+        }
+        if !config.allow_data_loss && metrics.data_loss_count > 0 {
+            failed.push(format!("{} data loss(es)", metrics.data_loss_count));
+        }
+        failed
     }
 }
 
@@ -225,34 +272,17 @@ pub fn validate_quality(input: &str, output: &str) -> QualityReport {
 /// Check for text corruption (arrows/pipes appearing in text content)
 fn check_text_corruption(input_lines: &[&str], output_lines: &[&str], report: &mut QualityReport) {
     for (line_idx, output_line) in output_lines.iter().enumerate() {
-        let chars: Vec<char> = output_line.chars().collect();
-
-        for (col_idx, &ch) in chars.iter().enumerate() {
-            // Check for corruption indicators - arrows that appear to be in text
-            if ch == '↑' || ch == '↓' || ch == '←' || ch == '→' {
-                // Only flag as corruption if arrow appears to be replacing a letter
-                if is_arrow_corrupting_text(&chars, col_idx) {
-                    report.issues.push(QualityIssue::TextCorruption {
-                        line: line_idx,
-                        expected: "text content".to_string(),
-                        got: ch.to_string(),
-                    });
-                    report.metrics.text_corruption_count += 1;
-                }
-            }
-
-            // Check for pipes that appear to be in text content (not borders)
-            if ch == '│' {
-                // Only flag if pipe appears to be in the middle of text
-                if is_pipe_in_text_content(&chars, col_idx) {
-                    report.issues.push(QualityIssue::TextCorruption {
-                        line: line_idx,
-                        expected: "text content".to_string(),
-                        got: ch.to_string(),
-                    });
-                    report.metrics.text_corruption_count += 1;
-                }
-            }
+        // Suspicious characters already in the original line are not corruption
+        let existing = input_lines
+            .get(line_idx)
+            .map_or(0, |line| suspicious_chars(line).len());
+        for ch in suspicious_chars(output_line).into_iter().skip(existing) {
+            report.issues.push(QualityIssue::TextCorruption {
+                line: line_idx,
+                expected: "text content".to_string(),
+                got: ch.to_string(),
+            });
+            report.metrics.text_corruption_count += 1;
         }
     }
 
@@ -269,10 +299,27 @@ fn check_text_corruption(input_lines: &[&str], output_lines: &[&str], report: &m
     }
 }
 
+/// Arrows and pipes in a line that look like they replaced text
+fn suspicious_chars(line: &str) -> Vec<char> {
+    let chars: Vec<char> = line.chars().collect();
+    chars
+        .iter()
+        .enumerate()
+        .filter(|&(col, &ch)| match ch {
+            // Only flag arrows that appear to be replacing a letter
+            '↑' | '↓' | '←' | '→' => is_arrow_corrupting_text(&chars, col),
+            // Only flag pipes that appear to be in the middle of text
+            '│' => is_pipe_in_text_content(&chars, col),
+            _ => false,
+        })
+        .map(|(_, &ch)| ch)
+        .collect()
+}
+
 /// Check for data loss
 fn check_data_loss(input_lines: &[&str], output_lines: &[&str], report: &mut QualityReport) {
     // Check if line count changed significantly
-    let line_diff = output_lines.len().saturating_sub(input_lines.len());
+    let line_diff = input_lines.len().saturating_sub(output_lines.len());
     if line_diff > 2 {
         // Allow some line additions for formatting, but not losses
         report.metrics.data_loss_count += 1;
@@ -401,20 +448,105 @@ fn is_pipe_in_text_content(line_chars: &[char], col: usize) -> bool {
         || (prev == ' ' && next.is_alphabetic())
 }
 
-/// Count text characters (letters, numbers, punctuation)
+/// Count text characters (everything but whitespace, borders and arrows)
 fn count_text_chars(lines: &[&str]) -> usize {
     lines
         .iter()
-        .map(|line| {
-            line.chars()
-                .filter(|&c| {
-                    c.is_alphabetic() || c.is_numeric() || c.is_ascii_punctuation() || c == ' '
-                })
-                .count()
-        })
+        .map(|line| text_content(&line.chars().collect::<Vec<_>>()).len())
         .sum()
 }
 
+/// First place where a repair changed the text of a block
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextMismatch {
+    /// Line of the differing character (0-indexed, in the original block
+    /// unless the original has no text left)
+    pub line: usize,
+    /// Column of the differing character (0-indexed, in characters)
+    pub col: usize,
+    /// Character of the original text (`None` when text was added at the end)
+    pub expected: Option<char>,
+    /// Character of the repaired text (`None` when text was dropped at the end)
+    pub found: Option<char>,
+}
+
+impl TextMismatch {
+    /// Shift the position by `offset` lines (block-relative to file lines).
+    #[must_use]
+    pub const fn rebased(self, offset: usize) -> Self {
+        Self {
+            line: self.line + offset,
+            ..self
+        }
+    }
+}
+
+impl fmt::Display for TextMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.expected, self.found) {
+            (Some(expected), Some(found)) => write!(f, "expected {expected:?}, found {found:?}"),
+            (Some(expected), None) => write!(f, "{expected:?} and the text after it were dropped"),
+            (None, Some(found)) => write!(f, "{found:?} was added after the text"),
+            (None, None) => f.write_str("text changed"),
+        }
+    }
+}
+
+/// Check that a repair kept the text of a block.
+///
+/// The ordered sequence of non-whitespace, non-drawing characters must be
+/// identical, and so must the spacing inside each run of text and the
+/// indentation of lines that draw nothing.
+///
+/// Returns the first difference, or `None` when the text is preserved.
+#[must_use]
+pub fn first_text_difference<A: AsRef<str>, B: AsRef<str>>(
+    original: &[A],
+    repaired: &[B],
+) -> Option<TextMismatch> {
+    let mut expected = text_positions(original);
+    let mut found = text_positions(repaired);
+    loop {
+        match (expected.next(), found.next()) {
+            (None, None) => return None,
+            (Some(a), Some(b)) if a.2 == b.2 => {}
+            (a, b) => {
+                let (line, col, _) = a.or(b)?;
+                return Some(TextMismatch {
+                    line,
+                    col,
+                    expected: a.map(|(_, _, ch)| ch),
+                    found: b.map(|(_, _, ch)| ch),
+                });
+            }
+        }
+    }
+}
+
+/// Text characters of `lines` with their line and column, including the
+/// whitespace between two text characters of a run and, on lines without
+/// drawing characters, the leading whitespace
+fn text_positions<S: AsRef<str>>(lines: &[S]) -> impl Iterator<Item = (usize, usize, char)> + '_ {
+    lines.iter().enumerate().flat_map(|(line, text)| {
+        let chars: Vec<char> = text.as_ref().chars().collect();
+        let draws =
+            (0..chars.len()).any(|col| !chars[col].is_whitespace() && !is_text_char(&chars, col));
+        let mut kept = Vec::new();
+        // Leading whitespace counts on lines that draw nothing, and whitespace
+        // counts when only whitespace separates it from text on both sides
+        let mut run_start = (!draws).then_some(0);
+        for col in 0..chars.len() {
+            if is_text_char(&chars, col) {
+                kept.extend((run_start.unwrap_or(col)..=col).map(|c| (line, c, chars[c])));
+                run_start = Some(col + 1);
+            } else if !chars[col].is_whitespace() {
+                run_start = None;
+            }
+        }
+        kept
+    })
+}
+
 /// Validate a fixture against quality standards
 ///
 /// # Errors
@@ -590,4 +722,47 @@ mod tests {
         );
         assert!(report.score < 1.0, "Data loss should reduce score");
     }
+
+    #[test]
+    fn test_first_text_difference() {
+        let original = ["┌──────┐", "│ abc  │", "└──────┘"];
+        let widened = ["┌────────┐", "│  abc   │", "└────────┘"];
+        assert_eq!(first_text_difference(&original, &widened), None);
+
+        let cut = ["┌────┐", "│ ab │", "└────┘"];
+        assert_eq!(
+            first_text_difference(&original, &cut),
+            Some(TextMismatch {
+                line: 1,
+                col: 4,
+                expected: Some('c'),
+                found: None,
+            })
+        );
+
+        let replaced = ["┌──────┐", "│ a→c  │", "└──────┘"];
+        let mismatch = first_text_difference(&original, &replaced).unwrap();
+        assert_eq!((mismatch.line, mismatch.col), (1, 3));
+        assert_eq!(mismatch.to_string(), "expected 'b', found '→'");
+    }
+
+    #[test]
+    fn test_first_text_difference_checks_spacing() {
+        // Spacing inside a run of text is part of the text
+        let original = ["┌────────────┐", "│ Client APP │", "└────────────┘"];
+        let squeezed = ["┌────────────┐", "│ ClientAPP  │", "└────────────┘"];
+        let mismatch = first_text_difference(&original, &squeezed).unwrap();
+        assert_eq!((mismatch.line, mismatch.col), (1, 8));
+
+        // So is the indentation of a line that draws nothing
+        let heading = ["# Box", "┌──┐", "│ab│", "└──┘"];
+        let shifted = ["#  Box", "┌──┐", "│ab│", "└──┘"];
+        assert!(first_text_difference(&heading, &shifted).is_some());
+        let indented = [" # Box", "┌──┐", "│ab│", "└──┘"];
+        assert!(first_text_difference(&heading, &indented).is_some());
+
+        // Padding between text and a border may change
+        let padded = ["# Box", "┌────┐", "│ ab │", "└────┘"];
+        assert_eq!(first_text_difference(&heading, &padded), None);
+    }
 }
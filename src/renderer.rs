@@ -67,6 +67,27 @@ pub fn render_diagram(inventory: &PrimitiveInventory) -> Grid {
     grid
 }
 
+/// ASCII replacement for a box-drawing or arrow character.
+///
+/// Used when `preserve_unicode = false`: lines become `-`, `|` and `+`,
+/// arrowheads become `>`, `<`, `^` and `v`. Other characters are unchanged.
+#[must_use]
+pub const fn ascii_fallback(ch: char) -> char {
+    match ch {
+        '─' | '═' | '━' | '┄' | '┈' | '╌' => '-',
+        '│' | '║' | '┃' | '┆' | '┊' | '╎' => '|',
+        '┌' | '┐' | '└' | '┘' | '╭' | '╮' | '╰' | '╯' | '╔' | '╗' | '╚' | '╝' | '├' | '┤' | '┬'
+        | '┴' | '┼' | '╠' | '╣' | '╦' | '╩' | '╬' | '┏' | '┓' | '┗' | '┛' => {
+            '+'
+        }
+        '→' | '⇒' | '⟶' | '⟹' | '▶' | '►' => '>',
+        '←' | '⇐' | '◀' | '◄' => '<',
+        '↑' | '⇑' | '▲' => '^',
+        '↓' | '⇓' | '▼' => 'v',
+        _ => ch,
+    }
+}
+
 /// Check if a position (row, col) falls inside any box's interior.
 /// Interior means: between top and bottom borders, and between left and right borders.
 fn is_position_inside_any_box(boxes: &[crate::primitives::Box], row: usize, col: usize) -> bool {
@@ -277,23 +298,25 @@ fn calculate_bounds(inventory: &PrimitiveInventory) -> (usize, usize) {
 fn draw_box(grid: &mut Grid, b: &crate::primitives::Box) {
     let chars = b.style.chars();
 
-    // Top and bottom borders
+    // Top and bottom borders, keeping the tees and arrowheads of lines that meet the box
     for col in b.top_left.1..=b.bottom_right.1 {
-        if let Some(cell) = grid.get_mut(b.top_left.0, col) {
-            *cell = chars.horizontal;
-        }
-        if let Some(cell) = grid.get_mut(b.bottom_right.0, col) {
-            *cell = chars.horizontal;
+        for row in [b.top_left.0, b.bottom_right.0] {
+            if let Some(cell) = grid.get_mut(row, col) {
+                if !joins_horizontal_border(*cell) {
+                    *cell = chars.horizontal;
+                }
+            }
         }
     }
 
     // Left and right borders
     for row in b.top_left.0..=b.bottom_right.0 {
-        if let Some(cell) = grid.get_mut(row, b.top_left.1) {
-            *cell = chars.vertical;
-        }
-        if let Some(cell) = grid.get_mut(row, b.bottom_right.1) {
-            *cell = chars.vertical;
+        for col in [b.top_left.1, b.bottom_right.1] {
+            if let Some(cell) = grid.get_mut(row, col) {
+                if !joins_vertical_border(*cell) {
+                    *cell = chars.vertical;
+                }
+            }
         }
     }
 
@@ -312,13 +335,58 @@ fn draw_box(grid: &mut Grid, b: &crate::primitives::Box) {
     }
 }
 
+/// Tee or arrowhead on a top or bottom border where a line meets the box.
+const fn joins_horizontal_border(ch: char) -> bool {
+    matches!(
+        ch,
+        '┬' | '┴'
+            | '┼'
+            | '╦'
+            | '╩'
+            | '╬'
+            | '╤'
+            | '╧'
+            | '╥'
+            | '╨'
+            | '╪'
+            | '╫'
+            | '▼'
+            | '▲'
+    )
+}
+
+/// Tee or arrowhead on a side wall where a line meets the box.
+const fn joins_vertical_border(ch: char) -> bool {
+    matches!(
+        ch,
+        '├' | '┤'
+            | '┼'
+            | '╠'
+            | '╣'
+            | '╬'
+            | '╞'
+            | '╡'
+            | '╟'
+            | '╢'
+            | '╪'
+            | '╫'
+            | '▶'
+            | '◀'
+    )
+}
+
 /// Draw a text row on the grid.
+///
+/// A misplaced wall trimmed off the end of the row is cleared, since the
+/// box's right border is drawn where it belongs.
 fn draw_text_row(grid: &mut Grid, row: &crate::primitives::TextRow) {
-    for (i, ch) in row.content.chars().enumerate() {
-        let col = row.start_col + i;
-        if col <= row.end_col {
-            if let Some(cell) = grid.get_mut(row.row, col) {
+    let mut chars = row.content.chars();
+    for col in row.start_col..=row.end_col {
+        if let Some(cell) = grid.get_mut(row.row, col) {
+            if let Some(ch) = chars.next() {
                 *cell = ch;
+            } else if matches!(*cell, '│' | '║' | '┃') {
+                *cell = ' ';
             }
         }
     }
@@ -493,26 +561,22 @@ fn draw_elbows_at_points(
                     row: seg_row,
                     start_col,
                     end_col,
-                } if *seg_row == row => {
-                    if *start_col <= col && col <= *end_col {
-                        if col == *start_col {
-                            horizontal_from_right = true; // Coming from left, going right
-                        } else if col == *end_col {
-                            horizontal_from_left = true; // Coming from right, going left
-                        }
+                } if *seg_row == row && *start_col <= col && col <= *end_col => {
+                    if col == *start_col {
+                        horizontal_from_right = true; // Coming from left, going right
+                    } else if col == *end_col {
+                        horizontal_from_left = true; // Coming from right, going left
                     }
                 }
                 crate::primitives::Segment::Vertical {
                     col: seg_col,
                     start_row,
                     end_row,
-                } if *seg_col == col => {
-                    if *start_row <= row && row <= *end_row {
-                        if row == *start_row {
-                            vertical_from_above = true; // Coming from above, going down
-                        } else if row == *end_row {
-                            vertical_from_below = true; // Coming from below, going up
-                        }
+                } if *seg_col == col && *start_row <= row && row <= *end_row => {
+                    if row == *start_row {
+                        vertical_from_above = true; // Coming from above, going down
+                    } else if row == *end_row {
+                        vertical_from_below = true; // Coming from below, going up
                     }
                 }
                 _ => {}
@@ -556,32 +620,33 @@ fn draw_elbows_at_points(
 fn draw_label(grid: &mut Grid, label: &crate::primitives::Label) {
     // For labels attached to vertical arrows, position them under the arrows
     // but preserve some reasonable spacing
-    let final_col = match &label.attached_to {
-        crate::primitives::LabelAttachment::VerticalArrow(_) => {
-            if label.row > 0 {
-                // Search for arrows near the label's original position
-                let search_start = label.col.saturating_sub(3);
-                let search_end = (label.col + label.content.len() + 3).min(grid.width());
-
-                for col in search_start..search_end {
-                    if let Some(ch) = grid.get(label.row - 1, col) {
-                        if ch == '↓' || ch == '↑' {
-                            // Found an arrow near this label's original position
-                            let clean_content = label.content.trim_matches('"');
-                            let formatted_content = format!(" {clean_content} ");
-                            let label_width = formatted_content.chars().count();
-                            let centered = col.saturating_sub(label_width / 2);
-                            return draw_label_at_position(grid, label, label.row, centered.max(0));
-                        }
+    if let crate::primitives::LabelAttachment::VerticalArrow(_) = &label.attached_to {
+        if label.row > 0 {
+            // Search for arrows near the label's original position
+            let search_start = label.col.saturating_sub(3);
+            let search_end = (label.col + label.content.len() + 3).min(grid.width());
+
+            for col in search_start..search_end {
+                if let Some(ch) = grid.get(label.row - 1, col) {
+                    if ch == '↓' || ch == '↑' {
+                        // Found an arrow near this label's original position
+                        let clean_content = label.content.trim_matches('"');
+                        let formatted_content = format!(" {clean_content} ");
+                        let label_width = formatted_content.chars().count();
+                        let centered = col.saturating_sub(label_width / 2);
+                        return draw_label_at_position(grid, label, label.row, centered);
                     }
                 }
             }
-            label.col // Fallback
         }
-        _ => label.col,
-    };
+    }
 
-    draw_label_at_position(grid, label, label.row, final_col);
+    // A label that stays where it is keeps its text and spacing exactly
+    for (i, ch) in label.content.chars().enumerate() {
+        if let Some(cell) = grid.get_mut(label.row, label.col + i) {
+            *cell = ch;
+        }
+    }
 }
 
 fn draw_label_at_position(
@@ -691,12 +756,12 @@ fn test_render_with_labels() {
     // Box should be rendered
     assert_eq!(grid.get(0, 0), Some('┌'));
 
-    // Label should be rendered with formatting (truncated due to grid width)
-    assert_eq!(grid.get(4, 0), Some(' '));
-    assert_eq!(grid.get(4, 1), Some('L'));
-    assert_eq!(grid.get(4, 2), Some('a'));
-    assert_eq!(grid.get(4, 3), Some('b'));
-    assert_eq!(grid.get(4, 4), Some('e'));
+    // Label should be rendered where it was found, text unchanged
+    assert_eq!(grid.get(4, 0), Some('L'));
+    assert_eq!(grid.get(4, 1), Some('a'));
+    assert_eq!(grid.get(4, 2), Some('b'));
+    assert_eq!(grid.get(4, 3), Some('e'));
+    assert_eq!(grid.get(4, 4), Some('l'));
     // Grid should accommodate label space
     assert!(grid.height() > 4);
 }
@@ -745,12 +810,11 @@ fn test_render_label_no_collision() {
     assert_eq!(grid.get(0, 0), Some('┌'));
     assert_eq!(grid.get(2, 4), Some('┘'));
 
-    // Label should be placed in empty space with formatting
-    assert_eq!(grid.get(5, 0), Some(' '));
-    assert_eq!(grid.get(5, 1), Some('T'));
-    assert_eq!(grid.get(5, 2), Some('e'));
-    assert_eq!(grid.get(5, 3), Some('x'));
-    assert_eq!(grid.get(5, 4), Some('t'));
+    // Label should be placed in empty space, text unchanged
+    assert_eq!(grid.get(5, 0), Some('T'));
+    assert_eq!(grid.get(5, 1), Some('e'));
+    assert_eq!(grid.get(5, 2), Some('x'));
+    assert_eq!(grid.get(5, 3), Some('t'));
 }
 
 #[test]
@@ -794,17 +858,7 @@ fn test_render_multiple_labels() {
     assert_eq!(grid.get(0, 0), Some('┌'));
     assert_eq!(grid.get(0, 5), Some('┌'));
 
-    // Both labels should be rendered with formatting (truncated due to grid width)
-    // " First " starts at col 1, " Second " starts at col 7
-    assert_eq!(grid.get(4, 1), Some(' '));
-    assert_eq!(grid.get(4, 2), Some('F'));
-    assert_eq!(grid.get(4, 3), Some('i'));
-    assert_eq!(grid.get(4, 4), Some('r'));
-    assert_eq!(grid.get(4, 5), Some('s'));
-    assert_eq!(grid.get(4, 6), Some(' '));
-    assert_eq!(grid.get(4, 7), Some('S'));
-    assert_eq!(grid.get(4, 8), Some('e'));
-    assert_eq!(grid.get(4, 9), Some('c'));
-    assert_eq!(grid.get(4, 10), Some('o'));
-    assert_eq!(grid.get(4, 11), Some('n'));
+    // Both labels should be rendered at their own columns, text unchanged
+    let row: String = (1..=11).filter_map(|col| grid.get(4, col)).collect();
+    assert_eq!(row, "FirstSecond");
 }
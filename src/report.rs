@@ -0,0 +1,451 @@
+//! Machine-readable reports for CI: SARIF, GitHub annotations, `JUnit` and
+//! Checkstyle.
+//!
+//! Every format is built from the same per-block [`Diagnostic`]s, so a
+//! problem is reported at the same line and column whatever the format.
+
+use crate::changes::ChangeRecord;
+use crate::modes::ProcessingOutcome;
+use crate::rules::{Rule, RulesConfig, Severity};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write as _;
+
+/// Report format selected with `--format`.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    /// SARIF 2.1.0 (GitHub code scanning and other dashboards)
+    Sarif,
+    /// GitHub Actions workflow commands (`::warning file=...::`)
+    Github,
+    /// `JUnit` XML (one test suite per file)
+    Junit,
+    /// Checkstyle XML
+    Checkstyle,
+}
+
+/// A problem found in one block of a file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Diagnostic {
+    /// File the problem was found in
+    pub file: String,
+    /// Line of the problem (1-indexed)
+    pub line: usize,
+    /// Column of the problem, in characters (1-indexed)
+    pub column: usize,
+    /// Last line of the affected block (1-indexed, inclusive)
+    pub end_line: usize,
+    /// Rule that found the problem
+    pub rule: Rule,
+    /// How serious the problem is
+    pub severity: Severity,
+    /// Description of the problem
+    pub message: String,
+    /// Hash of the lines of the affected block, unchanged when the block
+    /// moves (see [`crate::baseline::content_hash`])
+    pub content_hash: String,
+}
+
+/// Diagnostics for one processed file, with the severities of `rules`.
+/// Rules set to `off` are left out.
+///
+/// `original` is the file content before processing; change records point at
+/// the first line and column their replacement differs from it.
+#[must_use]
+pub fn diagnostics_for(
+    file: &str,
+    original: &str,
+    outcome: &ProcessingOutcome,
+    rules: &RulesConfig,
+) -> Vec<Diagnostic> {
+    let lines: Vec<&str> = original.lines().collect();
+    let changes = outcome.changes.iter().map(|change| {
+        let (line, column) = first_change(&lines, change);
+        (
+            change.rule,
+            (line, column),
+            (change.start_line, change.end_line),
+            change.reason.clone(),
+        )
+    });
+    let findings = outcome.findings.iter().map(|finding| {
+        let line = finding.line + 1;
+        (
+            finding.rule,
+            (line, finding.col + 1),
+            (line, line),
+            finding.message.clone(),
+        )
+    });
+    let text_violations = outcome.text_violations.iter().map(|mismatch| {
+        let line = mismatch.line + 1;
+        let message = format!("diagram repair would change the text ({mismatch}); left unchanged");
+        (
+            Rule::TextChanged,
+            (line, mismatch.col + 1),
+            (line, line),
+            message,
+        )
+    });
+    let block_warnings = outcome.block_warnings.iter().map(|warning| {
+        let line = warning.line + 1;
+        (
+            warning.rule,
+            (line, 1),
+            (line, line),
+            warning.message.clone(),
+        )
+    });
+    let directive_warnings = outcome.directive_warnings.iter().map(|warning| {
+        let line = warning.line + 1;
+        (
+            Rule::InvalidDirective,
+            (line, 1),
+            (line, line),
+            warning.message.clone(),
+        )
+    });
+    let mut diagnostics: Vec<Diagnostic> = changes
+        .chain(findings)
+        .chain(text_violations)
+        .chain(block_warnings)
+        .chain(directive_warnings)
+        .filter_map(|(rule, (line, column), (start, end_line), message)| {
+            let severity = rules.severity(rule);
+            let block = lines
+                .get(start - 1..end_line.min(lines.len()))
+                .unwrap_or_default();
+            (severity != Severity::Off).then(|| Diagnostic {
+                file: file.to_string(),
+                line,
+                column,
+                end_line,
+                rule,
+                severity,
+                message,
+                content_hash: crate::baseline::content_hash(block),
+            })
+        })
+        .collect();
+    diagnostics.sort_by_key(|diagnostic| (diagnostic.line, diagnostic.column));
+    diagnostics
+}
+
+/// Diagnostic for a file that could not be read or processed, or `None` if
+/// the rule is off.
+#[must_use]
+pub fn file_error(file: &str, message: &str, rules: &RulesConfig) -> Option<Diagnostic> {
+    let severity = rules.severity(Rule::FileError);
+    (severity != Severity::Off).then(|| Diagnostic {
+        file: file.to_string(),
+        line: 1,
+        column: 1,
+        end_line: 1,
+        rule: Rule::FileError,
+        severity,
+        message: message.to_string(),
+        content_hash: crate::baseline::content_hash(&[]),
+    })
+}
+
+/// First line and column (1-indexed) where a change differs from the original.
+fn first_change(lines: &[&str], change: &ChangeRecord) -> (usize, usize) {
+    let original = lines
+        .get(change.start_line - 1..change.end_line.min(lines.len()))
+        .unwrap_or_default();
+    let mut replacement = change.replacement.lines();
+    for (offset, old) in original.iter().enumerate() {
+        let new = replacement.next().unwrap_or_default();
+        if *old != new {
+            let column = old
+                .chars()
+                .zip(new.chars())
+                .take_while(|(a, b)| a == b)
+                .count();
+            return (change.start_line + offset, column + 1);
+        }
+    }
+    (change.start_line, 1)
+}
+
+/// Render diagnostics in a report format.
+///
+/// `files` lists every file that was checked, so formats that report clean
+/// files (`JUnit`, Checkstyle) can include them.
+#[must_use]
+pub fn render(format: ReportFormat, files: &[String], diagnostics: &[Diagnostic]) -> String {
+    match format {
+        ReportFormat::Sarif => render_sarif(diagnostics),
+        ReportFormat::Github => render_github(diagnostics),
+        ReportFormat::Junit => render_junit(files, diagnostics),
+        ReportFormat::Checkstyle => render_checkstyle(files, diagnostics),
+    }
+}
+
+fn render_sarif(diagnostics: &[Diagnostic]) -> String {
+    let mut rules: Vec<Rule> = diagnostics.iter().map(|d| d.rule).collect();
+    rules.sort_unstable();
+    rules.dedup();
+    let rules: Vec<serde_json::Value> = rules
+        .iter()
+        .map(|rule| {
+            serde_json::json!({
+                "id": rule.id(),
+                "name": rule.name(),
+                "shortDescription": { "text": rule.description() },
+                "defaultConfiguration": { "level": sarif_level(rule.default_severity()) },
+            })
+        })
+        .collect();
+    let results: Vec<serde_json::Value> = diagnostics
+        .iter()
+        .map(|d| {
+            serde_json::json!({
+                "ruleId": d.rule.id(),
+                "level": sarif_level(d.severity),
+                "message": { "text": d.message },
+                "partialFingerprints": { "ascfixContentHash/v1": d.content_hash },
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation": { "uri": d.file.replace('\\', "/") },
+                        "region": {
+                            "startLine": d.line,
+                            "startColumn": d.column,
+                            "endLine": d.end_line,
+                        },
+                    },
+                }],
+            })
+        })
+        .collect();
+    let sarif = serde_json::json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "ascfix",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "informationUri": "https://github.com/evoludigit/ascfix",
+                    "rules": rules,
+                },
+            },
+            "results": results,
+        }],
+    });
+    serde_json::to_string_pretty(&sarif).unwrap_or_default()
+}
+
+const fn sarif_level(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Off => "none",
+        Severity::Info => "note",
+        Severity::Warning => "warning",
+        Severity::Error => "error",
+    }
+}
+
+fn render_github(diagnostics: &[Diagnostic]) -> String {
+    let mut out = String::new();
+    for d in diagnostics {
+        let command = match d.severity {
+            Severity::Off | Severity::Info => "notice",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        let _ = writeln!(
+            out,
+            "::{command} file={},line={},col={},endLine={},title={}::{}",
+            escape_github_property(&d.file),
+            d.line,
+            d.column,
+            d.end_line,
+            escape_github_property(&format!("ascfix {}", d.rule)),
+            escape_github_data(&d.message)
+        );
+    }
+    out
+}
+
+/// Escape the message of a workflow command.
+fn escape_github_data(value: &str) -> String {
+    value
+        .replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// Escape a property (`file=`, `title=`) of a workflow command.
+fn escape_github_property(value: &str) -> String {
+    escape_github_data(value)
+        .replace(':', "%3A")
+        .replace(',', "%2C")
+}
+
+fn render_junit(files: &[String], diagnostics: &[Diagnostic]) -> String {
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let _ = writeln!(
+        out,
+        "<testsuites name=\"ascfix\" tests=\"{}\" failures=\"{}\">",
+        files
+            .iter()
+            .map(|file| file_diagnostics(file, diagnostics).count().max(1))
+            .sum::<usize>(),
+        diagnostics.len()
+    );
+    for file in files {
+        let found: Vec<&Diagnostic> = file_diagnostics(file, diagnostics).collect();
+        let name = escape_xml(file);
+        let _ = writeln!(
+            out,
+            "  <testsuite name=\"{name}\" tests=\"{}\" failures=\"{}\">",
+            found.len().max(1),
+            found.len()
+        );
+        if found.is_empty() {
+            let _ = writeln!(out, "    <testcase name=\"ascfix\" classname=\"{name}\"/>");
+        }
+        for d in found {
+            let _ = writeln!(
+                out,
+                "    <testcase name=\"{} {}\" classname=\"{name}\">\n      <failure message=\"{}\" type=\"{}\">{}:{}:{}: {}</failure>\n    </testcase>",
+                d.rule.id(),
+                d.rule.name(),
+                escape_xml(&d.message),
+                d.severity.name(),
+                name,
+                d.line,
+                d.column,
+                escape_xml(&d.message)
+            );
+        }
+        out.push_str("  </testsuite>\n");
+    }
+    out.push_str("</testsuites>\n");
+    out
+}
+
+fn render_checkstyle(files: &[String], diagnostics: &[Diagnostic]) -> String {
+    let mut out =
+        String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<checkstyle version=\"4.3\">\n");
+    for file in files {
+        let _ = writeln!(out, "  <file name=\"{}\">", escape_xml(file));
+        for d in file_diagnostics(file, diagnostics) {
+            let _ = writeln!(
+                out,
+                "    <error line=\"{}\" column=\"{}\" severity=\"{}\" message=\"{}\" source=\"ascfix.{}\"/>",
+                d.line,
+                d.column,
+                d.severity.name(),
+                escape_xml(&d.message),
+                d.rule.id()
+            );
+        }
+        out.push_str("  </file>\n");
+    }
+    out.push_str("</checkstyle>\n");
+    out
+}
+
+fn file_diagnostics<'a>(
+    file: &'a str,
+    diagnostics: &'a [Diagnostic],
+) -> impl Iterator<Item = &'a Diagnostic> {
+    diagnostics.iter().filter(move |d| d.file == file)
+}
+
+/// Escape text for XML attributes and content.
+fn escape_xml(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for ch in value.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\n' => escaped.push_str("&#10;"),
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diagnostic() -> Diagnostic {
+        Diagnostic {
+            file: "docs/a,b.md".to_string(),
+            line: 3,
+            column: 5,
+            end_line: 4,
+            rule: Rule::RaggedTableRow,
+            severity: Severity::Warning,
+            message: "table columns aligned <50%>".to_string(),
+            content_hash: "0123456789abcdef".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_change_points_at_first_difference() {
+        let original = "Intro\n\n| a | b |\n|---|---|\n| long | x |";
+        let outcome = crate::modes::process_document(
+            &crate::cli::Mode::Safe,
+            original,
+            false,
+            &crate::config::Config::default(),
+        );
+        let diagnostics = diagnostics_for("a.md", original, &outcome, &RulesConfig::default());
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!((diagnostics[0].line, diagnostics[0].column), (3, 5));
+        assert_eq!(diagnostics[0].end_line, 5);
+        assert_eq!(diagnostics[0].rule, Rule::RaggedTableRow);
+
+        let mut rules = RulesConfig::default();
+        rules.set(Rule::RaggedTableRow, Severity::Off);
+        assert!(diagnostics_for("a.md", original, &outcome, &rules).is_empty());
+    }
+
+    #[test]
+    fn test_render_sarif() {
+        let sarif = render(ReportFormat::Sarif, &[], &[diagnostic()]);
+        let value: serde_json::Value = serde_json::from_str(&sarif).unwrap();
+        let result = &value["runs"][0]["results"][0];
+        assert_eq!(result["ruleId"], "ASC101");
+        assert_eq!(result["level"], "warning");
+        let region = &result["locations"][0]["physicalLocation"]["region"];
+        assert_eq!(
+            (region["startLine"].as_u64(), region["startColumn"].as_u64()),
+            (Some(3), Some(5))
+        );
+        let rule = &value["runs"][0]["tool"]["driver"]["rules"][0];
+        assert_eq!(rule["id"], "ASC101");
+        assert_eq!(rule["name"], "ragged-table-row");
+    }
+
+    #[test]
+    fn test_render_github_escapes_properties() {
+        assert_eq!(
+            render(ReportFormat::Github, &[], &[diagnostic()]),
+            "::warning file=docs/a%2Cb.md,line=3,col=5,endLine=4,title=ascfix ASC101 (ragged-table-row)::table columns aligned <50%25>\n"
+        );
+    }
+
+    #[test]
+    fn test_render_xml_formats_list_clean_files() {
+        let files = ["docs/a,b.md".to_string(), "clean.md".to_string()];
+        let junit = render(ReportFormat::Junit, &files, &[diagnostic()]);
+        assert!(junit.contains("<testsuites name=\"ascfix\" tests=\"2\" failures=\"1\">"));
+        assert!(junit
+            .contains("<failure message=\"table columns aligned &lt;50%&gt;\" type=\"warning\">"));
+        assert!(junit.contains("<testcase name=\"ascfix\" classname=\"clean.md\"/>"));
+
+        let checkstyle = render(ReportFormat::Checkstyle, &files, &[diagnostic()]);
+        assert!(checkstyle.contains(
+            "<error line=\"3\" column=\"5\" severity=\"warning\" message=\"table columns aligned &lt;50%&gt;\" source=\"ascfix.ASC101\"/>"
+        ));
+        assert!(checkstyle.contains("<file name=\"clean.md\">\n  </file>"));
+    }
+}
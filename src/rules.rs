@@ -0,0 +1,328 @@
+//! Catalog of diagnostic rules.
+//!
+//! Every issue ascfix reports has a rule with a stable ID (`ASC101`), a name,
+//! and a default severity that the `[rules]` table can change.
+
+use crate::changes::Fixer;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// How serious a diagnostic is.
+#[derive(
+    ValueEnum, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// Not reported
+    #[value(skip)]
+    Off,
+    /// Worth knowing, nothing to fix
+    Info,
+    /// Should be fixed
+    Warning,
+    /// Must be fixed
+    Error,
+}
+
+impl Severity {
+    /// Name used in configuration and reports.
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Off => "off",
+            Self::Info => "info",
+            Self::Warning => "warning",
+            Self::Error => "error",
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// A check that can produce diagnostics.
+///
+/// IDs are grouped by area: `ASC0xx` fences, `ASC1xx` tables, `ASC2xx`
+/// lists, `ASC3xx` diagrams, `ASC4xx` refused repairs, `ASC5xx` directives
+/// and `ASC9xx` files. An ID is never reused for a different check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub enum Rule {
+    /// A code fence is never closed
+    UnclosedFence,
+    /// A closing fence is shorter or longer than its opening
+    FenceLengthMismatch,
+    /// A fence is closed with the other marker (backticks vs tildes)
+    FenceTypeMismatch,
+    /// A closing fence has no opening
+    OrphanedFence,
+    /// Table rows have cells of different widths
+    RaggedTableRow,
+    /// A table row is wrapped over several lines
+    WrappedTableRow,
+    /// List indentation or bullets are inconsistent
+    InconsistentList,
+    /// A vertical arrow is not lined up with the boxes it connects
+    MisalignedVerticalArrow,
+    /// A horizontal arrow is not lined up with the boxes it connects
+    MisalignedHorizontalArrow,
+    /// Box borders, widths or padding are uneven
+    MisalignedBox,
+    /// One diagram draws boxes in different styles
+    MixedBoxStyles,
+    /// A repaired diagram would be wider than `max_line_length`
+    TextOverflow,
+    /// A diagram repair would change the text of the block
+    TextChanged,
+    /// A repaired diagram fails the `[quality]` checks
+    QualityCheckFailed,
+    /// An ascfix directive cannot be understood
+    InvalidDirective,
+    /// A file cannot be read or processed
+    FileError,
+}
+
+impl Rule {
+    /// Every rule, in ID order.
+    pub const ALL: [Self; 16] = [
+        Self::UnclosedFence,
+        Self::FenceLengthMismatch,
+        Self::FenceTypeMismatch,
+        Self::OrphanedFence,
+        Self::RaggedTableRow,
+        Self::WrappedTableRow,
+        Self::InconsistentList,
+        Self::MisalignedVerticalArrow,
+        Self::MisalignedHorizontalArrow,
+        Self::MisalignedBox,
+        Self::MixedBoxStyles,
+        Self::TextOverflow,
+        Self::TextChanged,
+        Self::QualityCheckFailed,
+        Self::InvalidDirective,
+        Self::FileError,
+    ];
+
+    /// Stable identifier, used in reports and as the `[rules]` key.
+    #[must_use]
+    pub const fn id(self) -> &'static str {
+        match self {
+            Self::UnclosedFence => "ASC001",
+            Self::FenceLengthMismatch => "ASC002",
+            Self::FenceTypeMismatch => "ASC003",
+            Self::OrphanedFence => "ASC004",
+            Self::RaggedTableRow => "ASC101",
+            Self::WrappedTableRow => "ASC102",
+            Self::InconsistentList => "ASC201",
+            Self::MisalignedVerticalArrow => "ASC301",
+            Self::MisalignedHorizontalArrow => "ASC302",
+            Self::MisalignedBox => "ASC303",
+            Self::MixedBoxStyles => "ASC304",
+            Self::TextOverflow => "ASC401",
+            Self::TextChanged => "ASC402",
+            Self::QualityCheckFailed => "ASC403",
+            Self::InvalidDirective => "ASC501",
+            Self::FileError => "ASC901",
+        }
+    }
+
+    /// Short readable name.
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::UnclosedFence => "unclosed-fence",
+            Self::FenceLengthMismatch => "fence-length-mismatch",
+            Self::FenceTypeMismatch => "fence-type-mismatch",
+            Self::OrphanedFence => "orphaned-fence",
+            Self::RaggedTableRow => "ragged-table-row",
+            Self::WrappedTableRow => "wrapped-table-row",
+            Self::InconsistentList => "inconsistent-list",
+            Self::MisalignedVerticalArrow => "misaligned-vertical-arrow",
+            Self::MisalignedHorizontalArrow => "misaligned-horizontal-arrow",
+            Self::MisalignedBox => "misaligned-box",
+            Self::MixedBoxStyles => "mixed-box-styles",
+            Self::TextOverflow => "text-overflow",
+            Self::TextChanged => "text-changed",
+            Self::QualityCheckFailed => "quality-check-failed",
+            Self::InvalidDirective => "invalid-directive",
+            Self::FileError => "file-error",
+        }
+    }
+
+    /// One-sentence description of the problem.
+    #[must_use]
+    pub const fn description(self) -> &'static str {
+        match self {
+            Self::UnclosedFence => "Code fence is never closed",
+            Self::FenceLengthMismatch => "Closing fence length differs from its opening",
+            Self::FenceTypeMismatch => {
+                "Fence is closed with a different marker than it was opened with"
+            }
+            Self::OrphanedFence => "Closing fence has no matching opening",
+            Self::RaggedTableRow => "Table columns are not aligned",
+            Self::WrappedTableRow => "Table row is wrapped over several lines",
+            Self::InconsistentList => "List indentation or bullets are inconsistent",
+            Self::MisalignedVerticalArrow => {
+                "Vertical arrow is not lined up with the boxes it connects"
+            }
+            Self::MisalignedHorizontalArrow => {
+                "Horizontal arrow is not lined up with the boxes it connects"
+            }
+            Self::MisalignedBox => "Box borders, widths or padding are uneven",
+            Self::MixedBoxStyles => "Diagram mixes box styles",
+            Self::TextOverflow => "Repaired diagram would exceed max_line_length",
+            Self::TextChanged => "Diagram repair would change the text of the block",
+            Self::QualityCheckFailed => "Repaired diagram fails the quality checks",
+            Self::InvalidDirective => "ascfix directive cannot be understood",
+            Self::FileError => "File cannot be read or processed",
+        }
+    }
+
+    /// Severity unless configured otherwise.
+    #[must_use]
+    pub const fn default_severity(self) -> Severity {
+        match self {
+            Self::MixedBoxStyles => Severity::Info,
+            Self::TextChanged | Self::FileError => Severity::Error,
+            _ => Severity::Warning,
+        }
+    }
+
+    /// Fixer that repairs what this rule finds, if any.
+    #[must_use]
+    pub const fn fixer(self) -> Option<Fixer> {
+        match self {
+            Self::UnclosedFence
+            | Self::FenceLengthMismatch
+            | Self::FenceTypeMismatch
+            | Self::OrphanedFence => Some(Fixer::Fence),
+            Self::RaggedTableRow | Self::WrappedTableRow => Some(Fixer::Table),
+            Self::InconsistentList => Some(Fixer::List),
+            Self::MisalignedVerticalArrow
+            | Self::MisalignedHorizontalArrow
+            | Self::MisalignedBox
+            | Self::MixedBoxStyles => Some(Fixer::Diagram),
+            Self::TextOverflow
+            | Self::TextChanged
+            | Self::QualityCheckFailed
+            | Self::InvalidDirective
+            | Self::FileError => None,
+        }
+    }
+
+    /// Look a rule up by its ID.
+    #[must_use]
+    pub fn from_id(id: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|rule| rule.id() == id)
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.id(), self.name())
+    }
+}
+
+impl From<Rule> for String {
+    fn from(rule: Rule) -> Self {
+        rule.id().to_string()
+    }
+}
+
+impl TryFrom<String> for Rule {
+    type Error = String;
+
+    fn try_from(id: String) -> Result<Self, Self::Error> {
+        Self::from_id(&id)
+            .ok_or_else(|| format!("unknown rule '{id}' (expected an ID such as ASC101)"))
+    }
+}
+
+/// An issue found without a change to show for it, such as a diagram that
+/// mixes box styles.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    /// Rule that found the issue
+    pub rule: Rule,
+    /// Line of the issue in the original content (0-indexed)
+    pub line: usize,
+    /// Column of the issue, in characters (0-indexed)
+    pub col: usize,
+    /// Description of the issue
+    pub message: String,
+}
+
+/// Severities set in the `[rules]` table, keyed by rule ID. Rules not listed
+/// keep their default severity.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct RulesConfig(BTreeMap<Rule, Severity>);
+
+impl RulesConfig {
+    /// Every rule at its default severity (the full `[rules]` schema).
+    #[must_use]
+    pub fn defaults() -> Self {
+        Self(
+            Rule::ALL
+                .into_iter()
+                .map(|rule| (rule, rule.default_severity()))
+                .collect(),
+        )
+    }
+
+    /// Whether no rule is configured.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Set the severity of a rule.
+    #[allow(dead_code)] // Reason: Part of public API for library usage
+    pub fn set(&mut self, rule: Rule, severity: Severity) {
+        self.0.insert(rule, severity);
+    }
+
+    /// Effective severity of a rule.
+    #[must_use]
+    pub fn severity(&self, rule: Rule) -> Severity {
+        self.0
+            .get(&rule)
+            .copied()
+            .unwrap_or_else(|| rule.default_severity())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ids_are_unique_and_round_trip() {
+        let mut ids: Vec<&str> = Rule::ALL.iter().map(|rule| rule.id()).collect();
+        ids.sort_unstable();
+        ids.dedup();
+        assert_eq!(ids.len(), Rule::ALL.len());
+        for rule in Rule::ALL {
+            assert_eq!(Rule::from_id(rule.id()), Some(rule));
+        }
+    }
+
+    #[test]
+    fn test_rules_config_from_toml() {
+        let config: RulesConfig = toml::from_str("ASC304 = \"error\"\nASC101 = \"off\"").unwrap();
+        assert_eq!(config.severity(Rule::MixedBoxStyles), Severity::Error);
+        assert_eq!(config.severity(Rule::RaggedTableRow), Severity::Off);
+        assert_eq!(config.severity(Rule::TextChanged), Severity::Error);
+
+        let error = toml::from_str::<RulesConfig>("ragged-table-row = \"off\"").unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("unknown rule 'ragged-table-row'"));
+        assert!(toml::from_str::<RulesConfig>("ASC101 = \"loud\"").is_err());
+    }
+}
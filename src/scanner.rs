@@ -1,6 +1,7 @@
 //! Diagram block extraction from Markdown content.
 
-use crate::parser;
+use crate::document::Document;
+use crate::primitives::{continues_down, continues_up};
 
 // Inline definition of InlineCodeSpan to avoid module resolution issues
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -75,58 +76,222 @@ pub struct DiagramBlock {
     /// Lines comprising this diagram block
     pub lines: Vec<String>,
     /// Inline code spans for each line (for protecting content during processing)
+    ///
+    /// Columns refer to the full source line, including the container prefix.
     pub inline_code_spans: Vec<Vec<InlineCodeSpan>>,
+    /// Container prefix shared by every line (list indentation, `>` quote markers)
+    ///
+    /// It is stripped from `lines` before gridding and put back on output.
+    pub prefix: String,
+    /// Prefix actually removed from each line (differs from `prefix` on
+    /// container-only lines such as a bare `>`)
+    pub line_prefixes: Vec<String>,
+    /// Configuration overrides from an `<!-- ascfix: key=value -->` directive
+    /// placed right before the block
+    pub overrides: Option<crate::directives::BlockOverrides>,
 }
 
-/// Extract diagram blocks from Markdown content.
+impl DiagramBlock {
+    /// Put the container prefix back on a rendered line of this block.
+    ///
+    /// Empty lines get their original prefix without trailing whitespace, so a
+    /// bare `>` stays a bare `>`. Other lines get the shared prefix, including
+    /// container-only lines that the repair filled in (a blank interior row).
+    #[must_use]
+    pub fn restore_prefix(&self, idx: usize, line: &str) -> String {
+        if line.is_empty() {
+            let prefix = self.line_prefixes.get(idx).unwrap_or(&self.prefix);
+            prefix.trim_end().to_string()
+        } else {
+            format!("{}{line}", self.prefix)
+        }
+    }
+}
+
+/// Check if a character can be part of a container prefix.
+const fn is_container_char(ch: char) -> bool {
+    matches!(ch, ' ' | '\t' | '>')
+}
+
+/// Find the container prefix shared by all lines that have content past it.
+///
+/// The prefix is made of indentation and blockquote markers only. Lines made
+/// up entirely of container characters (a bare `>` or an empty bridged line)
+/// do not constrain it.
+fn common_container_prefix(lines: &[String]) -> String {
+    let mut common: Option<Vec<char>> = None;
+
+    for line in lines {
+        let container: Vec<char> = line.chars().take_while(|&c| is_container_char(c)).collect();
+        if container.len() == line.chars().count() {
+            continue;
+        }
+        common = Some(match common {
+            None => container,
+            Some(prev) => prev
+                .iter()
+                .zip(&container)
+                .take_while(|(a, b)| a == b)
+                .map(|(a, _)| *a)
+                .collect(),
+        });
+    }
+
+    common
+        .map(|chars| chars.into_iter().collect())
+        .unwrap_or_default()
+}
+
+/// Split a block's lines into the shared container prefix, the per-line
+/// prefixes removed, and the stripped lines.
+fn strip_container_prefix(lines: Vec<String>) -> (String, Vec<String>, Vec<String>) {
+    let prefix = common_container_prefix(&lines);
+    if prefix.is_empty() {
+        let line_prefixes = vec![String::new(); lines.len()];
+        return (prefix, line_prefixes, lines);
+    }
+
+    let mut line_prefixes = Vec::with_capacity(lines.len());
+    let mut stripped = Vec::with_capacity(lines.len());
+    for line in lines {
+        if let Some(rest) = line.strip_prefix(prefix.as_str()) {
+            line_prefixes.push(prefix.clone());
+            stripped.push(rest.to_string());
+        } else {
+            // Container-only line, e.g. a bare `>` inside a `> ` quote
+            line_prefixes.push(line);
+            stripped.push(String::new());
+        }
+    }
+    (prefix, line_prefixes, stripped)
+}
+
+/// Check if a character is a horizontal border that an arrow can hang from
+/// or point at.
+const fn is_horizontal_border(ch: char) -> bool {
+    matches!(ch, '─' | '═' | '━' | '-' | '=')
+}
+
+/// Check if the character at `col` is a vertical arrow standing on its own,
+/// with nothing next to it on the line (so `v` in a word does not count).
+fn is_lone_arrow(chars: &[char], col: usize) -> bool {
+    let is_arrow = matches!(
+        chars.get(col),
+        Some('↓' | '⇓' | '▼' | '↑' | '⇑' | '▲' | 'v' | 'V' | '^' | '|')
+    );
+    let blank = |c: Option<&char>| c.is_none_or(|c| c.is_whitespace());
+    is_arrow
+        && blank(col.checked_sub(1).and_then(|left| chars.get(left)))
+        && blank(chars.get(col + 1))
+}
+
+/// Check if a vertical wall, arrow or connector runs from `above` to `below`
+/// in the same column, i.e. a blank gap between them is part of one diagram.
+///
+/// An arrow on its own under a box's bottom border, or above the top border of
+/// the next box, also joins the tiers.
+fn continues_across_gap(above: &str, below: &str) -> bool {
+    let above: Vec<char> = above.chars().collect();
+    let below: Vec<char> = below.chars().collect();
+    above
+        .iter()
+        .zip(&below)
+        .enumerate()
+        .any(|(col, (&up, &down))| {
+            (continues_down(up) && continues_up(down))
+                || (is_horizontal_border(up) && is_lone_arrow(&below, col))
+                || (is_lone_arrow(&above, col) && is_horizontal_border(down))
+        })
+}
+
+/// Extract diagram blocks from a parsed document.
+///
+/// A diagram block is a contiguous sequence of non-empty lines that the
+/// document marks as eligible for diagram repair (see
+/// [`Document::diagram_lines`]), so protected blocks are never scanned.
+/// Blank lines separate blocks, unless a vertical box wall, arrow or connector
+/// continues in the same column on both sides of the gap, or an arrow on its
+/// own sits under a horizontal border or above one: the blank lines then stay
+/// inside the block so a multi-tier diagram is processed as a whole.
 ///
-/// A diagram block is a contiguous sequence of non-empty lines outside code fences.
-/// Blank lines separate blocks.
+/// A container prefix shared by all lines of a block (list indentation, `> `
+/// blockquote or alert markers) is stripped from the lines and kept in
+/// [`DiagramBlock::prefix`], so diagrams are gridded from their own first column.
 ///
 /// This function also masks inline code spans to protect them from diagram processing.
-#[allow(dead_code)] // Reason: Used by main processing pipeline
 #[must_use]
-pub fn extract_diagram_blocks(text: &str) -> Vec<DiagramBlock> {
-    let normal_lines = parser::extract_normal_lines(text);
+pub fn extract_diagram_blocks(document: &Document) -> Vec<DiagramBlock> {
+    let normal_lines = document.diagram_lines();
     let mut blocks = Vec::new();
     let mut current_block: Option<(usize, Vec<String>, Vec<Vec<InlineCodeSpan>>)> = None;
+    // Blank lines seen since the last non-empty line of the current block
+    let mut pending_blanks: Vec<(usize, String)> = Vec::new();
+    let mut last_line_num = 0;
+
+    let finalize = |block: Option<(usize, Vec<String>, Vec<Vec<InlineCodeSpan>>)>,
+                    blocks: &mut Vec<DiagramBlock>| {
+        if let Some((start, block_lines, inline_spans)) = block {
+            if !block_lines.is_empty() {
+                let overrides = document
+                    .overrides_for(start, start + block_lines.len() - 1)
+                    .cloned();
+                let (prefix, line_prefixes, lines) = strip_container_prefix(block_lines);
+                blocks.push(DiagramBlock {
+                    start_line: start,
+                    lines,
+                    inline_code_spans: inline_spans,
+                    prefix,
+                    line_prefixes,
+                    overrides,
+                });
+            }
+        }
+    };
 
     for (line_num, line) in normal_lines {
         if line.trim().is_empty() {
-            // Blank line: finalize current block if any
-            if let Some((start, block_lines, inline_spans)) = current_block.take() {
-                if !block_lines.is_empty() {
-                    blocks.push(DiagramBlock {
-                        start_line: start,
-                        lines: block_lines,
-                        inline_code_spans: inline_spans,
-                    });
-                }
+            // Blank line: hold it until we know whether the diagram continues
+            if current_block.is_some() {
+                pending_blanks.push((line_num, line));
             }
-        } else {
-            // Mask inline code in the line
-            let (masked_line, spans) = mask_inline_code(&line);
+            continue;
+        }
+
+        // Mask inline code in the line
+        let (masked_line, spans) = mask_inline_code(&line);
+
+        if let Some((_start, block_lines, all_spans)) = &mut current_block {
+            let bridges_gap = pending_blanks.is_empty()
+                || (line_num == last_line_num + pending_blanks.len() + 1
+                    && pending_blanks
+                        .iter()
+                        .enumerate()
+                        .all(|(i, (num, _))| *num == last_line_num + i + 1)
+                    && block_lines
+                        .last()
+                        .is_some_and(|above| continues_across_gap(above, &masked_line)));
 
-            // Non-empty line: add to current block or start new one
-            if let Some((_start, ref mut block_lines, ref mut all_spans)) = &mut current_block {
+            if bridges_gap {
+                for (_, blank) in std::mem::take(&mut pending_blanks) {
+                    block_lines.push(blank);
+                    all_spans.push(Vec::new());
+                }
                 block_lines.push(masked_line);
                 all_spans.push(spans);
             } else {
+                // Blank gap ends the block
+                pending_blanks.clear();
+                finalize(current_block.take(), &mut blocks);
                 current_block = Some((line_num, vec![masked_line], vec![spans]));
             }
+        } else {
+            current_block = Some((line_num, vec![masked_line], vec![spans]));
         }
+        last_line_num = line_num;
     }
 
-    // Finalize last block if any
-    if let Some((start, block_lines, inline_spans)) = current_block {
-        if !block_lines.is_empty() {
-            blocks.push(DiagramBlock {
-                start_line: start,
-                lines: block_lines,
-                inline_code_spans: inline_spans,
-            });
-        }
-    }
+    // Finalize last block if any (trailing blank lines are not part of it)
+    finalize(current_block, &mut blocks);
 
     blocks
 }
@@ -134,11 +299,16 @@ pub fn extract_diagram_blocks(text: &str) -> Vec<DiagramBlock> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::ProtectConfig;
+
+    fn extract(markdown: &str) -> Vec<DiagramBlock> {
+        extract_diagram_blocks(&Document::parse(markdown, ProtectConfig::default()))
+    }
 
     #[test]
     fn test_single_diagram_block() {
         let markdown = "Line 1\nLine 2\nLine 3";
-        let blocks = extract_diagram_blocks(markdown);
+        let blocks = extract(markdown);
         assert_eq!(blocks.len(), 1);
         // Lines are now masked, so we check the count instead
         assert_eq!(blocks[0].lines.len(), 3);
@@ -148,7 +318,7 @@ mod tests {
     #[test]
     fn test_multiple_diagram_blocks() {
         let markdown = "Block1Line1\nBlock1Line2\n\nBlock2Line1\nBlock2Line2\n\nBlock3";
-        let blocks = extract_diagram_blocks(markdown);
+        let blocks = extract(markdown);
         assert_eq!(blocks.len(), 3);
         assert_eq!(blocks[0].lines.len(), 2);
         assert_eq!(blocks[1].lines.len(), 2);
@@ -158,7 +328,7 @@ mod tests {
     #[test]
     fn test_preserves_start_line_number() {
         let markdown = "Line0\n\n\nLine3\nLine4\n\nLine6";
-        let blocks = extract_diagram_blocks(markdown);
+        let blocks = extract(markdown);
         assert_eq!(blocks[0].start_line, 0);
         assert_eq!(blocks[1].start_line, 3);
         assert_eq!(blocks[2].start_line, 6);
@@ -167,7 +337,7 @@ mod tests {
     #[test]
     fn test_ignores_content_in_code_fence() {
         let markdown = "Visible1\n\n```\nHidden\n```\n\nVisible2";
-        let blocks = extract_diagram_blocks(markdown);
+        let blocks = extract(markdown);
         // Visible1, code fence (ignored), Visible2 = 2 blocks
         assert_eq!(blocks.len(), 2);
         assert_eq!(blocks[0].lines.len(), 1);
@@ -177,21 +347,21 @@ mod tests {
     #[test]
     fn test_empty_markdown() {
         let markdown = "";
-        let blocks = extract_diagram_blocks(markdown);
+        let blocks = extract(markdown);
         assert!(blocks.is_empty());
     }
 
     #[test]
     fn test_only_blank_lines() {
         let markdown = "\n\n\n";
-        let blocks = extract_diagram_blocks(markdown);
+        let blocks = extract(markdown);
         assert!(blocks.is_empty());
     }
 
     #[test]
     fn test_preserves_line_content() {
         let markdown = "  Indented  \n\tTabbed\nNormal";
-        let blocks = extract_diagram_blocks(markdown);
+        let blocks = extract(markdown);
         assert_eq!(blocks.len(), 1);
         // Verify structure is preserved
         assert_eq!(blocks[0].lines.len(), 3);
@@ -205,9 +375,72 @@ mod tests {
     #[test]
     fn test_multiple_blank_lines_separate_blocks() {
         let markdown = "Block1\n\n\n\nBlock2";
-        let blocks = extract_diagram_blocks(markdown);
+        let blocks = extract(markdown);
         assert_eq!(blocks.len(), 2);
         assert_eq!(blocks[0].lines.len(), 1);
         assert_eq!(blocks[1].lines.len(), 1);
     }
+
+    #[test]
+    fn test_blank_line_inside_box_keeps_block() {
+        let markdown = "┌───┐\n│ A │\n\n│ B │\n└───┘";
+        let blocks = extract(markdown);
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(blocks[0].lines.len(), 5);
+        assert_eq!(blocks[0].lines[2], "");
+        assert_eq!(blocks[0].inline_code_spans.len(), 5);
+    }
+
+    #[test]
+    fn test_arrow_across_blank_lines_joins_tiers() {
+        let markdown = "┌───┐\n│ A │\n└─┬─┘\n\n\n  ↓\n┌───┐\n│ B │\n└───┘";
+        let blocks = extract(markdown);
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(blocks[0].start_line, 0);
+        assert_eq!(blocks[0].lines.len(), 9);
+    }
+
+    #[test]
+    fn test_unaligned_gap_still_separates_blocks() {
+        let markdown = "┌───┐\n│ A │\n└───┘\n\n┌───┐\n│ B │\n└───┘";
+        let blocks = extract(markdown);
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[1].start_line, 4);
+    }
+
+    #[test]
+    fn test_gap_across_code_fence_not_bridged() {
+        let markdown = "  │\n\n```\ncode\n```\n\n  │";
+        let blocks = extract(markdown);
+        assert_eq!(blocks.len(), 2);
+    }
+
+    #[test]
+    fn test_blockquote_prefix_stripped() {
+        let markdown = "> ┌──┐\n> │ab│\n>\n> └──┘";
+        let blocks = extract(markdown);
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(blocks[0].prefix, "> ");
+        assert_eq!(blocks[0].lines, vec!["┌──┐", "│ab│", "", "└──┘"]);
+        assert_eq!(blocks[0].restore_prefix(2, ""), ">");
+        assert_eq!(blocks[0].restore_prefix(0, "┌──┐"), "> ┌──┐");
+        // A blank row filled in by the repair keeps the quote
+        assert_eq!(blocks[0].restore_prefix(2, "│  │"), "> │  │");
+    }
+
+    #[test]
+    fn test_list_indentation_prefix_stripped() {
+        let markdown = "   ┌──┐\n   │ab│\n   └──┘\n     ↓";
+        let blocks = extract(markdown);
+        assert_eq!(blocks[0].prefix, "   ");
+        assert_eq!(blocks[0].lines[3], "  ↓");
+    }
+
+    #[test]
+    fn test_no_shared_prefix() {
+        let markdown = "- item\n  ┌──┐";
+        let blocks = extract(markdown);
+        assert_eq!(blocks[0].prefix, "");
+        assert_eq!(blocks[0].lines[1], "  ┌──┐");
+    }
 }
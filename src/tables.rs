@@ -50,11 +50,26 @@ pub fn has_wrapped_cells(content: &str) -> bool {
 }
 
 /// Check if a line looks like a table row (starts and ends with |).
-fn is_table_row(line: &str) -> bool {
+#[must_use]
+pub fn is_table_row(line: &str) -> bool {
     let trimmed = line.trim();
     trimmed.starts_with('|') && trimmed.ends_with('|')
 }
 
+/// Check if a line is a table separator (pipes, dashes and alignment colons only).
+#[must_use]
+pub fn is_table_separator(line: &str) -> bool {
+    let trimmed = line.trim();
+    if !trimmed.starts_with('|') || !trimmed.ends_with('|') {
+        return false;
+    }
+    trimmed
+        .split('|')
+        .skip(1)
+        .take_while(|cell| !cell.is_empty())
+        .all(|cell| cell.trim().chars().all(|c| c == '-' || c == ':'))
+}
+
 /// Check if a row is a continuation of the previous row.
 ///
 /// A continuation row typically has:
@@ -4,12 +4,12 @@
 //! between destructive changes (bad), constructive changes (good), and neutral
 //! changes (acceptable).
 
-#![allow(dead_code)] // Reason: Framework for future transformation analysis features
-
-// Transformation analysis module
+use crate::changes::ChangeRecord;
+use serde::{Deserialize, Serialize};
 
 /// Classification of transformation types
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "reason", rename_all = "snake_case")]
 pub enum TransformationType {
     /// Destructive changes that must be prevented
     Destructive(DestructiveReason),
@@ -20,7 +20,8 @@ pub enum TransformationType {
 }
 
 /// Reasons for destructive transformations
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum DestructiveReason {
     ContentDeletion,
     CharacterCorruption,
@@ -29,7 +30,8 @@ pub enum DestructiveReason {
 }
 
 /// Reasons for constructive transformations
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum ConstructiveReason {
     ArrowDuplication, // ↓ → ↓↓ for alignment
     BoxExpansion,     // Parent boxes grow to contain children
@@ -39,7 +41,8 @@ pub enum ConstructiveReason {
 }
 
 /// Reasons for neutral transformations
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 #[allow(clippy::enum_variant_names)] // Reason: Descriptive names for transformation types
 pub enum NeutralReason {
     CaseNormalization,
@@ -48,6 +51,27 @@ pub enum NeutralReason {
     WhitespaceNormalization,
 }
 
+impl TransformationType {
+    /// Kind of change: `constructive`, `destructive` or `neutral`
+    #[must_use]
+    pub const fn kind(&self) -> &'static str {
+        match self {
+            Self::Destructive(_) => "destructive",
+            Self::Constructive(_) => "constructive",
+            Self::Neutral(_) => "neutral",
+        }
+    }
+
+    /// Reason for the change, as written in `--json` output
+    #[must_use]
+    pub fn reason(&self) -> String {
+        serde_json::to_value(self)
+            .ok()
+            .and_then(|value| value.get("reason")?.as_str().map(ToString::to_string))
+            .unwrap_or_default()
+    }
+}
+
 /// Analysis of transformations between input and output
 #[derive(Debug, Clone)]
 pub struct TransformationAnalysis {
@@ -56,7 +80,7 @@ pub struct TransformationAnalysis {
 }
 
 /// Individual transformation instance
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Transformation {
     pub transform_type: TransformationType,
     pub location: Location,
@@ -64,16 +88,33 @@ pub struct Transformation {
     pub impact_score: f32, // -1.0 (very bad) to +1.0 (very good)
 }
 
-/// Location of transformation
-#[derive(Debug, Clone)]
+/// Location of transformation (0-indexed line and character columns).
+///
+/// `line` is written 1-indexed in JSON, like every other line in the output.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Location {
+    #[serde(
+        serialize_with = "serialize_line",
+        deserialize_with = "deserialize_line"
+    )]
     pub line: usize,
     pub col_start: usize,
     pub col_end: usize,
 }
 
+#[allow(clippy::trivially_copy_pass_by_ref)] // Reason: Signature required by serde
+fn serialize_line<S: serde::Serializer>(line: &usize, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_u64(*line as u64 + 1)
+}
+
+fn deserialize_line<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<usize, D::Error> {
+    let line = usize::deserialize(deserializer)?;
+    line.checked_sub(1)
+        .ok_or_else(|| serde::de::Error::custom("line numbers start at 1"))
+}
+
 /// Summary of all transformations
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TransformationSummary {
     pub destructive_count: usize,
     pub constructive_count: usize,
@@ -97,8 +138,12 @@ pub fn analyze_transformations(input: &str, output: &str) -> TransformationAnaly
         analyze_line_transformations(line_idx, input_line, output_line, &mut transformations);
     }
 
-    // Handle different line counts
-    let max_lines = input_lines.len().max(output_lines.len());
+    // Handle different line counts, at the first line without a counterpart
+    // (the last input line when lines were added)
+    let diverging_line = input_lines
+        .len()
+        .min(output_lines.len())
+        .min(input_lines.len().saturating_sub(1));
     if input_lines.len() != output_lines.len() {
         transformations.push(Transformation {
             transform_type: if output_lines.len() > input_lines.len() {
@@ -107,7 +152,7 @@ pub fn analyze_transformations(input: &str, output: &str) -> TransformationAnaly
                 TransformationType::Destructive(DestructiveReason::DataLoss)
             },
             location: Location {
-                line: max_lines,
+                line: diverging_line,
                 col_start: 0,
                 col_end: 0,
             },
@@ -133,6 +178,33 @@ pub fn analyze_transformations(input: &str, output: &str) -> TransformationAnaly
     }
 }
 
+/// Analyze the blocks rewritten in `original`, each against its replacement.
+///
+/// Comparing block by block keeps lines inserted or removed in one block
+/// from shifting the comparison of every line after it.
+#[must_use]
+pub fn analyze_changes(original: &str, changes: &[ChangeRecord]) -> TransformationAnalysis {
+    let lines: Vec<&str> = original.lines().collect();
+    let mut transformations = Vec::new();
+    for change in changes {
+        let start = change.start_line.saturating_sub(1);
+        let block = lines
+            .get(start..change.end_line.min(lines.len()))
+            .unwrap_or_default()
+            .join("\n");
+        let analysis = analyze_transformations(&block, &change.replacement);
+        transformations.extend(analysis.transformations.into_iter().map(|mut t| {
+            t.location.line += start;
+            t
+        }));
+    }
+    let summary = calculate_summary(&transformations);
+    TransformationAnalysis {
+        transformations,
+        summary,
+    }
+}
+
 /// Analyze transformations within a single line
 fn analyze_line_transformations(
     line_idx: usize,
@@ -142,6 +214,7 @@ fn analyze_line_transformations(
 ) {
     let input_chars: Vec<char> = input_line.chars().collect();
     let output_chars: Vec<char> = output_line.chars().collect();
+    let first_new = transformations.len();
 
     // Find differences
     let mut i = 0;
@@ -190,6 +263,27 @@ fn analyze_line_transformations(
             impact_score: -0.3,
         });
     }
+
+    // Borders, arrows and spacing moved but every word and box side is still there
+    let destructive = transformations[first_new..]
+        .iter()
+        .any(|t| matches!(t.transform_type, TransformationType::Destructive(_)));
+    if destructive
+        && text_content(&input_chars) == text_content(&output_chars)
+        && count_box_sides(&output_chars) >= count_box_sides(&input_chars)
+    {
+        transformations.truncate(first_new);
+        transformations.push(Transformation {
+            transform_type: TransformationType::Constructive(ConstructiveReason::BorderCorrection),
+            location: Location {
+                line: line_idx,
+                col_start: 0,
+                col_end: input_chars.len().max(output_chars.len()),
+            },
+            description: "Diagram structure realigned".to_string(),
+            impact_score: 0.2,
+        });
+    }
 }
 
 /// Analyze a specific character difference
@@ -425,6 +519,46 @@ fn is_in_text_content(chars: &[char], pos: usize) -> bool {
     context.chars().any(char::is_alphabetic) && !context.contains("┌┐└┘│─") // Not in box borders
 }
 
+/// Text of a line with drawing characters and whitespace removed
+#[must_use]
+pub fn text_content(chars: &[char]) -> Vec<char> {
+    (0..chars.len())
+        .filter(|&i| is_text_char(chars, i))
+        .map(|i| chars[i])
+        .collect()
+}
+
+/// Check if the character at `pos` is text: neither whitespace nor drawing
+#[must_use]
+pub fn is_text_char(chars: &[char], pos: usize) -> bool {
+    !chars[pos].is_whitespace() && !is_drawing_char(chars, pos)
+}
+
+/// Check if the character at `pos` draws a border, line or arrow
+fn is_drawing_char(chars: &[char], pos: usize) -> bool {
+    let is_word = |c: Option<&char>| c.is_some_and(|c| c.is_alphanumeric());
+    let stands_alone =
+        || !is_word(pos.checked_sub(1).and_then(|i| chars.get(i))) && !is_word(chars.get(pos + 1));
+    match chars[pos] {
+        '\u{2500}'..='\u{257F}' | '+' | '-' | '|' | '=' => true,
+        // Look-alike dashes, bars and spaces are redrawn canonically
+        ch if crate::confusables::is_confusable(ch) => true,
+        // Arrows inside a word are corruption, not drawing
+        '\u{2190}'..='\u{21FF}' | '\u{25A0}'..='\u{25FF}' | '<' | '>' | '^' | 'v' | 'V' => {
+            stands_alone()
+        }
+        _ => false,
+    }
+}
+
+/// Count the characters of a line that draw the side of a box
+fn count_box_sides(chars: &[char]) -> usize {
+    chars
+        .iter()
+        .filter(|&&ch| matches!(ch, '│' | '║' | '┃' | '|' | '¦' | '∣' | '｜'))
+        .count()
+}
+
 /// Calculate transformation summary
 fn calculate_summary(transformations: &[Transformation]) -> TransformationSummary {
     let mut destructive_count = 0;
@@ -504,4 +638,79 @@ mod tests {
         // Should have high risk score
         assert!(analysis.summary.risk_score > 0.5);
     }
+
+    #[test]
+    fn test_transformations_serialize_with_kind_and_reason() {
+        let analysis = analyze_transformations("┌──┐\n│abcd│\n└────┘", "┌────┐\n│abcd│\n└────┘");
+        let change = &analysis.transformations[0];
+        assert_eq!(change.transform_type.kind(), "constructive");
+        assert_eq!(change.transform_type.reason(), "border_correction");
+
+        let json = serde_json::to_string(change).unwrap();
+        assert!(
+            json.contains(
+                r#""transform_type":{"kind":"constructive","reason":"border_correction"}"#
+            ),
+            "got: {json}"
+        );
+        // Lines are 1-indexed in JSON
+        assert!(json.contains(r#""location":{"line":1,"col_start":0,"col_end":6}"#));
+        let parsed: Transformation = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.location.line, 0);
+    }
+
+    #[test]
+    fn test_line_count_change_located_inside_input() {
+        let analysis = analyze_transformations("a\nb", "a\nb\nc");
+        let change = analysis.transformations.last().unwrap();
+        assert!(change.description.starts_with("Line count changed"));
+        assert_eq!(change.location.line, 1);
+
+        let analysis = analyze_transformations("a\nb\nc", "a");
+        let change = analysis.transformations.last().unwrap();
+        assert_eq!(change.location.line, 1);
+    }
+
+    #[test]
+    fn test_changes_analyzed_per_block() {
+        use crate::changes::Fixer;
+        use crate::rules::Rule;
+
+        // The table loses a line, which must not shift the diagram below it
+        let original = "| a |\n|---|\n| long |\n|      |\n\n┌──┐\n│abcd│\n└────┘";
+        let record = |fixer, rule, start_line, end_line, replacement: &str| ChangeRecord {
+            fixer,
+            rule,
+            start_line,
+            end_line,
+            reason: String::new(),
+            replacement: replacement.to_string(),
+        };
+        let changes = [
+            record(
+                Fixer::Table,
+                Rule::WrappedTableRow,
+                1,
+                4,
+                "| a    |\n|------|\n| long |",
+            ),
+            record(
+                Fixer::Diagram,
+                Rule::MisalignedBox,
+                6,
+                8,
+                "┌────┐\n│abcd│\n└────┘",
+            ),
+        ];
+        let analysis = analyze_changes(original, &changes);
+        let diagram: Vec<_> = analysis
+            .transformations
+            .iter()
+            .filter(|t| t.location.line >= 5)
+            .collect();
+        assert_eq!(diagram.len(), 1);
+        assert_eq!(diagram[0].location.line, 5);
+        assert_eq!(diagram[0].transform_type.reason(), "border_correction");
+        assert!(analysis.transformations.iter().all(|t| t.location.line < 8));
+    }
 }
@@ -0,0 +1,430 @@
+//! Realignment of box sides that drift off their column.
+//!
+//! Generated diagrams often draw one row of a box a column or two wider or
+//! narrower than the rest ("│Memory│" under a "┌─────┐" border). Box
+//! detection then sees a broken rectangle and the renderer redraws it over
+//! the text. Before detection, each box is traced from its top border down to
+//! its bottom border, tolerating sides that are slightly off or rows that stop
+//! short, and every row is moved to the span of its borders (or, when the top
+//! and bottom borders disagree, the span most of its rows agree on).
+
+/// Furthest a side may drift from its border and still be traced.
+const MAX_SHIFT: usize = 2;
+
+const fn is_top_left(ch: char) -> bool {
+    matches!(ch, '┌' | '╔' | '╭' | '┏')
+}
+
+const fn is_top_right(ch: char) -> bool {
+    matches!(ch, '┐' | '╗' | '╮' | '┓')
+}
+
+const fn is_bottom_left(ch: char) -> bool {
+    matches!(ch, '└' | '╚' | '╰' | '┗')
+}
+
+const fn is_bottom_right(ch: char) -> bool {
+    matches!(ch, '┘' | '╝' | '╯' | '┛')
+}
+
+/// Plain horizontal border character.
+const fn is_horizontal(ch: char) -> bool {
+    matches!(ch, '─' | '═' | '━')
+}
+
+/// Character that may sit on a top or bottom border: lines, tees and arrows.
+const fn is_border(ch: char) -> bool {
+    is_horizontal(ch)
+        || matches!(
+            ch,
+            '┬' | '┴'
+                | '┼'
+                | '╤'
+                | '╧'
+                | '╦'
+                | '╩'
+                | '╪'
+                | '╬'
+                | '╥'
+                | '╨'
+                | '╫'
+                | '▼'
+                | '▲'
+                | '↓'
+                | '↑'
+        )
+}
+
+/// Plain side wall.
+const fn is_plain_wall(ch: char) -> bool {
+    matches!(ch, '│' | '║' | '┃')
+}
+
+/// Character that may stand for a side wall, including tees.
+const fn is_wall(ch: char) -> bool {
+    is_plain_wall(ch)
+        || matches!(
+            ch,
+            '├' | '┤' | '┼' | '╠' | '╣' | '╟' | '╢' | '╞' | '╡' | '╪' | '╫'
+        )
+}
+
+/// Character that can be repeated or dropped to fit a gap without losing text.
+const fn is_stretchable(ch: char) -> bool {
+    matches!(ch, ' ' | '─' | '═' | '━' | '-' | '=')
+}
+
+/// Which part of a box a row is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Part {
+    Border,
+    Interior,
+}
+
+/// Columns of one row of a box, sides included.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Span {
+    row: usize,
+    left: usize,
+    right: usize,
+    part: Part,
+}
+
+/// Move the rows of every box whose sides drift off their column to the span
+/// of its borders, or to the span most of the box's rows share when the
+/// borders disagree.
+///
+/// Boxes that overlap another box, whose rows disagree without a majority,
+/// or whose text would not fit are left as they are.
+#[must_use]
+pub fn align_walls(lines: &[String]) -> Vec<String> {
+    let mut rows: Vec<Vec<char>> = lines.iter().map(|line| line.chars().collect()).collect();
+    let mut stacks = trace_boxes(&rows);
+
+    for idx in 0..stacks.len() {
+        let stack = &stacks[idx];
+        let overlaps = stacks
+            .iter()
+            .enumerate()
+            .any(|(other_idx, other)| other_idx != idx && overlap(stack, other));
+        let Some((left, right)) = target_span(stack) else {
+            continue;
+        };
+        if overlaps || stack.iter().all(|s| (s.left, s.right) == (left, right)) {
+            continue;
+        }
+
+        // Rebuild every row of the box, keeping the other boxes on that row in place
+        let rebuilt: Option<Vec<(usize, Vec<char>)>> = stack
+            .iter()
+            .filter(|span| (span.left, span.right) != (left, right))
+            .map(|span| {
+                let mut moves: Vec<(Span, usize, usize)> = stacks
+                    .iter()
+                    .enumerate()
+                    .filter(|&(other_idx, _)| other_idx != idx)
+                    .filter_map(|(_, other)| other.iter().find(|s| s.row == span.row))
+                    .map(|s| (*s, s.left, s.right))
+                    .collect();
+                moves.push((*span, left, right));
+                moves.sort_by_key(|(s, _, _)| s.left);
+                rebuild_row(&rows[span.row], &moves).map(|row| (span.row, row))
+            })
+            .collect();
+        if let Some(rebuilt) = rebuilt {
+            for (row, chars) in rebuilt {
+                rows[row] = chars;
+            }
+            for span in &mut stacks[idx] {
+                (span.left, span.right) = (left, right);
+            }
+        }
+    }
+
+    rows.into_iter()
+        .map(|chars| chars.into_iter().collect())
+        .collect()
+}
+
+/// Every box traced from a top border to a bottom border, as the spans of its rows.
+fn trace_boxes(rows: &[Vec<char>]) -> Vec<Vec<Span>> {
+    let mut stacks = Vec::new();
+    for (top, chars) in rows.iter().enumerate() {
+        for left in (0..chars.len()).filter(|&col| is_top_left(chars[col])) {
+            let Some(right) = border_end(chars, left, is_top_right) else {
+                continue;
+            };
+            if let Some(stack) = trace_down(rows, top, left, right) {
+                stacks.push(stack);
+            }
+        }
+    }
+    stacks
+}
+
+/// Follow a box down from its top border at `left..=right`.
+fn trace_down(rows: &[Vec<char>], top: usize, left: usize, right: usize) -> Option<Vec<Span>> {
+    let mut stack = vec![Span {
+        row: top,
+        left,
+        right,
+        part: Part::Border,
+    }];
+    for (row, chars) in rows.iter().enumerate().skip(top + 1) {
+        let bottom = near(left, |col| {
+            chars.get(col).copied().is_some_and(is_bottom_left)
+        })
+        .and_then(|l| Some((l, border_end(chars, l, is_bottom_right)?)))
+        .filter(|&(_, r)| r.abs_diff(right) <= MAX_SHIFT);
+        if let Some((l, r)) = bottom {
+            stack.push(Span {
+                row,
+                left: l,
+                right: r,
+                part: Part::Border,
+            });
+            return (stack.len() > 2).then_some(stack);
+        }
+        let (l, r) = wall_pair(chars, left, right).or_else(|| short_row(chars, left, right))?;
+        stack.push(Span {
+            row,
+            left: l,
+            right: r,
+            part: Part::Interior,
+        });
+    }
+    None
+}
+
+/// Columns within `MAX_SHIFT` of `col` matching `pred`, nearest first.
+fn near(col: usize, pred: impl Fn(usize) -> bool) -> Option<usize> {
+    candidates(col).find(|&c| pred(c))
+}
+
+fn candidates(col: usize) -> impl Iterator<Item = usize> {
+    (0..=MAX_SHIFT).flat_map(move |d| {
+        [col.checked_sub(d), col.checked_add(d)]
+            .into_iter()
+            .flatten()
+            .take(if d == 0 { 1 } else { 2 })
+    })
+}
+
+/// The closest pair of walls to `left` and `right` with no wall between them.
+fn wall_pair(chars: &[char], left: usize, right: usize) -> Option<(usize, usize)> {
+    let is_wall_at = |col: usize| chars.get(col).copied().is_some_and(is_wall);
+    candidates(left)
+        .filter(|&l| is_wall_at(l))
+        .flat_map(|l| {
+            candidates(right)
+                .filter(move |&r| r > l + 1 && is_wall_at(r))
+                .map(move |r| (l, r))
+        })
+        .filter(|&(l, r)| !chars[l + 1..r].iter().copied().any(is_plain_wall))
+        .min_by_key(|&(l, r)| l.abs_diff(left) + r.abs_diff(right))
+}
+
+/// Walls of a row that ends well before the box's right border.
+fn short_row(chars: &[char], left: usize, right: usize) -> Option<(usize, usize)> {
+    let l = near(left, |col| chars.get(col).copied().is_some_and(is_wall))?;
+    let r = chars.iter().rposition(|&ch| ch != ' ')?;
+    (l + 1 < r
+        && r < right
+        && is_wall(chars[r])
+        && !chars[l + 1..r].iter().copied().any(is_plain_wall))
+    .then_some((l, r))
+}
+
+/// End of a border starting at a corner in `start`, if it ends in a corner
+/// matching `is_end`.
+fn border_end(chars: &[char], start: usize, is_end: fn(char) -> bool) -> Option<usize> {
+    let mut col = start + 1;
+    while chars.get(col).copied().is_some_and(is_border) {
+        col += 1;
+    }
+    (col > start + 1 && chars.get(col).copied().is_some_and(is_end)).then_some(col)
+}
+
+/// The span of the box's borders when they agree, otherwise the span of most rows.
+fn target_span(stack: &[Span]) -> Option<(usize, usize)> {
+    let (top, bottom) = (stack.first()?, stack.last()?);
+    if (top.left, top.right) == (bottom.left, bottom.right) {
+        return Some((top.left, top.right));
+    }
+    majority_span(stack)
+}
+
+/// The span shared by more rows of the box than any other.
+fn majority_span(stack: &[Span]) -> Option<(usize, usize)> {
+    let mut counts: Vec<((usize, usize), usize)> = Vec::new();
+    for span in stack {
+        let key = (span.left, span.right);
+        match counts.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((key, 1)),
+        }
+    }
+    let best = counts.iter().map(|(_, count)| *count).max()?;
+    let mut winners = counts.iter().filter(|(_, count)| *count == best);
+    let (span, _) = winners.next()?;
+    winners.next().is_none().then_some(*span)
+}
+
+/// Check whether two boxes share a cell.
+fn overlap(a: &[Span], b: &[Span]) -> bool {
+    a.iter().any(|x| {
+        b.iter()
+            .any(|y| x.row == y.row && x.left.max(y.left) <= x.right.min(y.right))
+    })
+}
+
+/// Rebuild a row with each box segment moved from its old span to its new one.
+///
+/// `moves` holds `(old span, new left, new right)` sorted by column. The text
+/// between segments is stretched or shrunk in its runs of spaces and lines.
+fn rebuild_row(chars: &[char], moves: &[(Span, usize, usize)]) -> Option<Vec<char>> {
+    let mut out = Vec::with_capacity(chars.len() + MAX_SHIFT);
+    let mut cursor = 0;
+    for &(span, left, right) in moves {
+        let gap = chars.get(cursor..span.left)?;
+        out.extend(fit_gap(gap, left.checked_sub(out.len())?)?);
+        out.extend(fit_segment(
+            &chars[span.left..=span.right],
+            span.part,
+            left,
+            right,
+        )?);
+        cursor = span.right + 1;
+    }
+    // Text after the last box keeps its end column
+    let rest = chars.get(cursor..).unwrap_or_default();
+    if !rest.iter().all(|&ch| ch == ' ') {
+        out.extend(fit_gap(rest, chars.len().checked_sub(out.len())?)?);
+    }
+    Some(out)
+}
+
+/// Fit the text between two boxes into `width` columns by growing or shrinking
+/// its longest run of spaces or lines.
+fn fit_gap(gap: &[char], width: usize) -> Option<Vec<char>> {
+    if gap.len() == width {
+        return Some(gap.to_vec());
+    }
+    if gap.iter().all(|&ch| ch == ' ') {
+        return Some(vec![' '; width]);
+    }
+    // Longest run of one stretchable character (the last one on ties)
+    let mut best: Option<(usize, usize)> = None;
+    let mut start = 0;
+    while start < gap.len() {
+        let mut end = start;
+        while end < gap.len() && gap[end] == gap[start] {
+            end += 1;
+        }
+        if is_stretchable(gap[start]) && best.is_none_or(|(s, e)| end - start >= e - s) {
+            best = Some((start, end));
+        }
+        start = end;
+    }
+    let (start, end) = best?;
+    let run = (end - start + width)
+        .checked_sub(gap.len())
+        .filter(|&run| run > 0)?;
+    let mut out = gap[..start].to_vec();
+    out.extend(std::iter::repeat_n(gap[start], run));
+    out.extend(&gap[end..]);
+    Some(out)
+}
+
+/// Redraw one row of a box between `left` and `right`.
+fn fit_segment(segment: &[char], part: Part, left: usize, right: usize) -> Option<Vec<char>> {
+    let width = right - left + 1;
+    if segment.len() == width {
+        return Some(segment.to_vec());
+    }
+    let (first, last) = (segment[0], segment[segment.len() - 1]);
+    let inner = &segment[1..segment.len() - 1];
+    let inner_width = width - 2;
+    let mut out = vec![first];
+    match part {
+        Part::Border => {
+            // Tees and arrows keep their offset from the left corner
+            let fill = inner.iter().copied().find(|&ch| is_horizontal(ch))?;
+            if inner[inner_width.min(inner.len())..]
+                .iter()
+                .any(|&ch| !is_horizontal(ch))
+            {
+                return None;
+            }
+            out.extend((0..inner_width).map(|i| inner.get(i).copied().unwrap_or(fill)));
+        }
+        Part::Interior => {
+            let text_end = inner.iter().rposition(|&ch| ch != ' ').map_or(0, |i| i + 1);
+            if text_end > inner_width {
+                return None;
+            }
+            out.extend(&inner[..text_end]);
+            out.extend(std::iter::repeat_n(' ', inner_width - text_end));
+        }
+    }
+    out.push(last);
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn align(text: &str) -> String {
+        let lines: Vec<String> = text.lines().map(ToString::to_string).collect();
+        align_walls(&lines).join("\n")
+    }
+
+    #[test]
+    fn test_short_row_widened_to_its_borders() {
+        assert_eq!(
+            align("┌───────────────┐\n│Initialization│\n└────┬──────────┘"),
+            "┌───────────────┐\n│Initialization │\n└────┬──────────┘"
+        );
+    }
+
+    #[test]
+    fn test_borders_follow_rows_that_agree() {
+        let input = "┌──▼──┐ ┌─▼─────┐\n│Memory│ │ Disk  │\n└──────┘ └───────┘";
+        assert_eq!(
+            align(input),
+            "┌──▼───┐ ┌─▼─────┐\n│Memory│ │ Disk  │\n└──────┘ └───────┘"
+        );
+    }
+
+    #[test]
+    fn test_connector_between_boxes_stretched() {
+        let input = "╔════╗   ┌─────┐\n║ ab ║──▶│ cd │\n╚════╝   └─────┘";
+        assert_eq!(
+            align(input),
+            "╔════╗   ┌─────┐\n║ ab ║──▶│ cd  │\n╚════╝   └─────┘"
+        );
+        let input = "╔════╗   ┌────┐\n║ ab║───▶│ cd │\n╚════╝   └────┘";
+        assert_eq!(
+            align(input),
+            "╔════╗   ┌────┐\n║ ab ║──▶│ cd │\n╚════╝   └────┘"
+        );
+    }
+
+    #[test]
+    fn test_aligned_and_ambiguous_boxes_untouched() {
+        let aligned = "┌────┐\n│ ab │\n└────┘";
+        assert_eq!(align(aligned), aligned);
+        let ambiguous = "┌────┐\n│ ab  │\n└──────┘";
+        assert_eq!(align(ambiguous), ambiguous);
+    }
+
+    #[test]
+    fn test_rows_stopping_short_padded_to_borders() {
+        let input = "╔══════════╗\n║ ab   ║\n║       ║\n║ cd  ║\n╚══════════╝";
+        assert_eq!(
+            align(input),
+            "╔══════════╗\n║ ab       ║\n║          ║\n║ cd       ║\n╚══════════╝"
+        );
+    }
+}
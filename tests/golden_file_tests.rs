@@ -303,3 +303,54 @@ fn golden_file_ignore_ascii_art() {
         "Output does not match expected for ignore_ascii_art - the ASCII art in ignore block should be preserved unchanged, but the box below should be normalized"
     );
 }
+
+/// Run diagram mode with the default configuration, quality gate included.
+fn process_diagram(input: &str) -> ascfix::modes::ProcessingOutcome {
+    let config = ascfix::config::Config::default();
+    ascfix::modes::process_document(&ascfix::cli::Mode::Diagram, input, false, &config)
+}
+
+#[test]
+fn golden_file_tiered_boxes() {
+    let input = fs::read_to_string("tests/data/unit/input/tiered_boxes.md")
+        .expect("Failed to read input fixture");
+    let expected = fs::read_to_string("tests/data/unit/expected/tiered_boxes.md")
+        .expect("Failed to read expected fixture");
+
+    let outcome = process_diagram(&input);
+    assert!(
+        outcome.block_warnings.is_empty(),
+        "{:?}",
+        outcome.block_warnings
+    );
+    let block_lines: Vec<usize> = outcome.block_scores.iter().map(|b| b.line).collect();
+    assert!(
+        block_lines.contains(&5) && !block_lines.contains(&9) && !block_lines.contains(&11),
+        "{block_lines:?}"
+    );
+    assert_eq!(
+        outcome.content.trim(),
+        expected.trim(),
+        "Output does not match expected for tiered_boxes - arrows between blank lines should keep the tiers in one block"
+    );
+}
+
+#[test]
+fn golden_file_box_blank_row() {
+    let input = fs::read_to_string("tests/data/unit/input/box_blank_row.md")
+        .expect("Failed to read input fixture");
+    let expected = fs::read_to_string("tests/data/unit/expected/box_blank_row.md")
+        .expect("Failed to read expected fixture");
+
+    let outcome = process_diagram(&input);
+    assert!(
+        outcome.block_warnings.is_empty(),
+        "{:?}",
+        outcome.block_warnings
+    );
+    assert_eq!(
+        outcome.content.trim(),
+        expected.trim(),
+        "Output does not match expected for box_blank_row - the blank row should stay inside the box"
+    );
+}
@@ -5,7 +5,8 @@
 
 use ascfix::cli::Mode;
 use ascfix::config::Config;
-use ascfix::modes::process_by_mode;
+use ascfix::fixes::FixSelection;
+use ascfix::modes::{process_by_mode, process_with_fixes};
 
 #[test]
 fn test_inline_code_with_arrows_preserved() {
@@ -146,13 +147,13 @@ fn test_safe_mode_preserves_inline_code() {
 }
 
 #[test]
-fn test_check_mode_preserves_inline_code() {
+fn test_all_fixers_preserve_inline_code() {
     let config = Config::default();
-    let content = "Check mode test: `⇒ ⇓`";
-    let result = process_by_mode(&Mode::Check, content, false, &config);
+    let content = "All fixers test: `⇒ ⇓`";
+    let result = process_with_fixes(content, FixSelection::ALL, &config).content;
     assert!(
         result.contains("`⇒ ⇓`"),
-        "Check mode should preserve inline code"
+        "Every fixer should preserve inline code"
     );
 }
 
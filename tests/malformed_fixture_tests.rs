@@ -582,4 +582,20 @@ mod malformed_fixture_tests {
                 include_str!("data/integration/dirty/error_recovery_corrupted.md");
         }
     }
+
+    #[test]
+    fn test_diagram_repairs_are_idempotent() {
+        // A second run over repaired output changes nothing
+        let mut paths: Vec<_> = std::fs::read_dir("tests/data/integration/dirty")
+            .expect("Failed to read fixture directory")
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        paths.sort();
+        for path in paths {
+            let content = std::fs::read_to_string(&path).unwrap();
+            let once = process_fixture_content(&content, &Mode::Diagram, false);
+            let twice = process_fixture_content(&once, &Mode::Diagram, false);
+            assert_eq!(once, twice, "{} changed on the second run", path.display());
+        }
+    }
 }
@@ -283,3 +283,57 @@ fn test_parent_child_rendering() {
         // Complex scenarios may have acceptable rendering variations
     }
 }
+
+/// Text in nested boxes is extracted per innermost box, around child regions
+#[test]
+fn test_nested_text_extracted_per_innermost_box() {
+    let diagram = "┌──────────────────┐
+│ Outer            │
+│                  │
+│  ┌─────┐         │
+│  │ Hi  │  note   │
+│  └─────┘         │
+│                  │
+└──────────────────┘";
+    let lines: Vec<&str> = diagram.lines().collect();
+    let grid = ascfix::grid::Grid::from_lines(&lines);
+    let inventory = ascfix::detector::detect_all_primitives(&grid);
+
+    assert_eq!(inventory.boxes.len(), 2);
+    assert_eq!(inventory.boxes[1].parent_idx, Some(0));
+    assert_eq!(inventory.boxes[0].child_indices, vec![1]);
+
+    let row4: Vec<(usize, usize, &str)> = inventory
+        .text_rows
+        .iter()
+        .filter(|t| t.row == 4)
+        .map(|t| (t.start_col, t.end_col, t.content.as_str()))
+        .collect();
+    // Child interior, then the parent segment to the right of the child
+    assert!(row4.contains(&(4, 8, " Hi  ")));
+    assert!(row4.contains(&(10, 18, "  note   ")));
+    assert!(inventory
+        .text_rows
+        .iter()
+        .any(|t| t.content.contains("Outer")));
+}
+
+/// Nested diagrams with text in every level round-trip unchanged
+#[test]
+fn test_nested_text_preserved_end_to_end() {
+    let diagram = "┌──────────────────┐
+│ Outer            │
+│                  │
+│  ┌─────┐         │
+│  │ Hi  │  note   │
+│  └─────┘         │
+│         ↓        │
+│  ┌─────┐         │
+│  │ Bye │         │
+│  └─────┘         │
+└──────────────────┘";
+    let config = ascfix::config::Config::default();
+    let result =
+        ascfix::modes::process_by_mode(&ascfix::cli::Mode::Diagram, diagram, false, &config);
+    assert_eq!(result, diagram);
+}
@@ -12,6 +12,7 @@ fn validate_all_golden_fixtures() {
         max_line_count_delta: 2,     // Allow some formatting additions
         allow_text_corruption: false, // Still prevent destructive corruption
         allow_data_loss: false,      // Still prevent data loss
+        ..QualityConfig::default()
     };
 
     let fixtures = vec![
@@ -97,93 +98,89 @@ fn validate_all_golden_fixtures() {
     );
 }
 
-fn get_integration_fixtures() -> Vec<(&'static str, &'static str)> {
+/// Diagram mode fixtures, with whether fences are repaired too.
+///
+/// Table and list fixtures are checked against their expected output in
+/// `malformed_fixture_tests`; the diagram quality metrics do not apply to them.
+fn get_integration_fixtures() -> Vec<(&'static str, &'static str, bool)> {
     vec![
-        // Table and text wrapping fixtures (stable, well-handled by ascfix)
-        (
-            "tests/data/integration/dirty/malformed_wrapped_cells.md",
-            "tests/data/integration/clean/malformed_wrapped_cells.md",
-        ),
-        (
-            "tests/data/integration/dirty/malformed_wrapped_with_links.md",
-            "tests/data/integration/clean/malformed_wrapped_with_links.md",
-        ),
+        // Wrapped text around code blocks (nothing for diagram mode to change)
         (
             "tests/data/integration/dirty/malformed_wrapped_with_code.md",
             "tests/data/integration/clean/malformed_wrapped_with_code.md",
-        ),
-        (
-            "tests/data/integration/dirty/malformed_broken_tables.md",
-            "tests/data/integration/clean/malformed_broken_tables.md",
+            false,
         ),
         // Box and diagram alignment fixtures (stable, well-handled by ascfix)
         (
             "tests/data/integration/dirty/malformed_box_alignment.md",
             "tests/data/integration/clean/malformed_box_alignment.md",
+            false,
         ),
-        // Arrow alignment fixtures (common LLM issue)
+        // Arrow alignment fixtures (common LLM issue); the README examples sit in
+        // code fences, which are never rewritten
         (
             "tests/data/integration/dirty/readme_arrow_alignment.md",
             "tests/data/integration/clean/readme_arrow_alignment.md",
+            false,
         ),
         (
             "tests/data/integration/dirty/llm_arrow_inconsistency.md",
             "tests/data/integration/clean/llm_arrow_inconsistency.md",
+            false,
         ),
         // Box styling fixtures (common LLM issue - mixed ┌/╔/╭)
         (
             "tests/data/integration/dirty/llm_mixed_box_styles.md",
             "tests/data/integration/clean/llm_mixed_box_styles.md",
+            false,
         ),
         // Side-by-side box balancing (common LLM issue)
         (
             "tests/data/integration/dirty/llm_side_by_side.md",
             "tests/data/integration/clean/llm_side_by_side.md",
+            false,
         ),
         // Code fence repair (common LLM issue)
         (
             "tests/data/integration/dirty/llm_fence_repair.md",
             "tests/data/integration/clean/llm_fence_repair.md",
+            true,
         ),
         // Unicode and mixed characters (less common but important)
         (
             "tests/data/integration/dirty/llm_unicode_mixed.md",
             "tests/data/integration/clean/llm_unicode_mixed.md",
-        ),
-        // List normalization - mixed bullet styles (common LLM issue)
-        (
-            "tests/data/integration/dirty/llm_list_normalization.md",
-            "tests/data/integration/clean/llm_list_normalization.md",
-        ),
-        // List normalization - missing blank lines before lists (very common LLM issue)
-        (
-            "tests/data/integration/dirty/llm_lists_no_blank_lines.md",
-            "tests/data/integration/clean/llm_lists_no_blank_lines.md",
+            false,
         ),
         // Connection paths/L-shaped connectors
         (
             "tests/data/integration/dirty/llm_connection_paths.md",
             "tests/data/integration/clean/llm_connection_paths.md",
+            false,
         ),
         // Mixed patterns: Tables + Diagrams
         (
             "tests/data/integration/dirty/llm_mixed_tables_diagrams.md",
             "tests/data/integration/clean/llm_mixed_tables_diagrams.md",
+            false,
         ),
         // Mixed patterns: Unicode + Tables
         (
             "tests/data/integration/dirty/llm_unicode_tables_mixed.md",
             "tests/data/integration/clean/llm_unicode_tables_mixed.md",
+            false,
         ),
         // Mixed patterns: Diagrams + Code blocks
         (
             "tests/data/integration/dirty/llm_diagrams_with_code.md",
             "tests/data/integration/clean/llm_diagrams_with_code.md",
+            false,
         ),
         // Mixed patterns: Everything together
         (
             "tests/data/integration/dirty/llm_everything_mixed.md",
             "tests/data/integration/clean/llm_everything_mixed.md",
+            true,
         ),
     ]
 }
@@ -196,17 +193,23 @@ fn validate_integration_fixtures() {
         max_line_count_delta: 10,
         allow_text_corruption: false,
         allow_data_loss: false,
+        ..QualityConfig::default()
     };
 
     let mut failed_fixtures = Vec::new();
 
-    for (input_path, expected_path) in get_integration_fixtures() {
+    for (input_path, expected_path, repair_fences) in get_integration_fixtures() {
         if !Path::new(input_path).exists() || !Path::new(expected_path).exists() {
             println!("Skipping {input_path} (files not found)");
             continue;
         }
 
-        match validate_fixture(input_path, expected_path, &config) {
+        let result = if repair_fences {
+            validate_fixture_with_fences(input_path, expected_path, &config)
+        } else {
+            validate_fixture(input_path, expected_path, &config)
+        };
+        match result {
             Ok(()) => {
                 println!("✓ {input_path} passed quality validation");
             }
@@ -217,12 +220,10 @@ fn validate_integration_fixtures() {
         }
     }
 
-    if !failed_fixtures.is_empty() {
-        println!(
-            "Note: {} integration fixtures need quality improvements",
-            failed_fixtures.len()
-        );
-    }
+    assert!(
+        failed_fixtures.is_empty(),
+        "integration fixtures failed quality validation: {failed_fixtures:?}"
+    );
 }
 
 #[test]
@@ -234,6 +235,7 @@ fn validate_fence_repair_quality() {
         max_line_count_delta: 0, // Fence repair shouldn't change line count
         allow_text_corruption: false,
         allow_data_loss: false,
+        ..QualityConfig::default()
     };
 
     let fixtures = vec![